@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::{interval, Duration};
+
+use crate::calendar;
+use crate::config::ConfigStore;
+use crate::indicators::{self, Candle};
+use crate::models::{AlertCondition, AlertRule, AlertTriggered, IndicatorInterval, Operand, PriceUpdate};
+use crate::poller::QuoteSnapshot;
+use crate::AppState;
+
+/// 预警检查间隔
+const EVALUATE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 计算取值在每根 K 线上的序列
+fn operand_series(operand: &Operand, candles: &[Candle]) -> Result<Vec<Option<f64>>> {
+    match operand {
+        Operand::Close => Ok(candles.iter().map(|c| Some(c.close)).collect()),
+        Operand::Value { value } => Ok(vec![Some(*value); candles.len()]),
+        Operand::Indicator { spec, line } => indicators::compute(spec, candles)
+            .remove(line)
+            .ok_or_else(|| anyhow::anyhow!("指标 {:?} 没有输出线 {}", spec, line)),
+    }
+}
+
+/// 校验条件中的指标参数和输出线名称
+pub fn validate(condition: &AlertCondition, interval: IndicatorInterval) -> Result<()> {
+    let (AlertCondition::Above { left, right }
+    | AlertCondition::Below { left, right }
+    | AlertCondition::CrossAbove { left, right }
+    | AlertCondition::CrossBelow { left, right }) = condition;
+    for operand in [left, right] {
+        if let Operand::Indicator { spec, .. } = operand {
+            indicators::validate(spec, interval)?;
+            operand_series(operand, &[])?;
+        }
+    }
+    Ok(())
+}
+
+/// 判断条件在最后一根 K 线上是否成立；数据不足时返回 false
+pub fn evaluate(condition: &AlertCondition, candles: &[Candle]) -> Result<bool> {
+    let (AlertCondition::Above { left, right }
+    | AlertCondition::Below { left, right }
+    | AlertCondition::CrossAbove { left, right }
+    | AlertCondition::CrossBelow { left, right }) = condition;
+    let left = operand_series(left, candles)?;
+    let right = operand_series(right, candles)?;
+    let pair = |i: usize| left.get(i).copied().flatten().zip(right.get(i).copied().flatten());
+
+    let Some(last) = candles.len().checked_sub(1) else {
+        return Ok(false);
+    };
+    let Some((l, r)) = pair(last) else {
+        return Ok(false);
+    };
+    let previous = last.checked_sub(1).and_then(pair);
+
+    Ok(match condition {
+        AlertCondition::Above { .. } => l > r,
+        AlertCondition::Below { .. } => l < r,
+        AlertCondition::CrossAbove { .. } => previous.is_some_and(|(pl, pr)| pl <= pr) && l > r,
+        AlertCondition::CrossBelow { .. } => previous.is_some_and(|(pl, pr)| pl >= pr) && l < r,
+    })
+}
+
+/// 用最新行情更新日/周/月 K 线的最后一根（当前周期尚未收盘时）
+/// 行情属于新的周期时追加一根；分钟与分时 K 线本身已是实时数据，不做处理
+pub fn patch_live(candles: &mut Vec<Candle>, interval: IndicatorInterval, quote: &PriceUpdate) {
+    let Some(now) = calendar::china_time_from_millis(quote.timestamp) else {
+        return;
+    };
+    let today = now.date();
+    if quote.stale || !calendar::is_trading_day(today) {
+        return;
+    }
+    let same_period = |label: &str| {
+        label.parse::<NaiveDate>().is_ok_and(|date| match interval {
+            IndicatorInterval::Day => date == today,
+            IndicatorInterval::Week => date.iso_week() == today.iso_week(),
+            IndicatorInterval::Month => date.year() == today.year() && date.month() == today.month(),
+            _ => false,
+        })
+    };
+    if !matches!(interval, IndicatorInterval::Day | IndicatorInterval::Week | IndicatorInterval::Month) {
+        return;
+    }
+
+    match candles.last_mut() {
+        Some(last) if same_period(&last.label) => {
+            last.high = last.high.max(quote.price);
+            last.low = last.low.min(quote.price);
+            last.close = quote.price;
+            last.label = today.to_string();
+        }
+        _ => candles.push(Candle {
+            label: today.to_string(),
+            open: quote.price,
+            high: quote.high.max(quote.price),
+            low: if quote.low > 0.0 { quote.low.min(quote.price) } else { quote.price },
+            close: quote.price,
+            volume: 0.0,
+        }),
+    }
+}
+
+/// 规则的简短描述，用于通知文本
+fn describe(rule: &AlertRule) -> String {
+    if !rule.note.is_empty() {
+        return rule.note.clone();
+    }
+    let operand = |o: &Operand| match o {
+        Operand::Close => "价格".to_string(),
+        Operand::Value { value } => format!("{}", value),
+        Operand::Indicator { spec, line } => format!("{:?}.{}", spec, line),
+    };
+    let (verb, left, right) = match &rule.condition {
+        AlertCondition::Above { left, right } => ("高于", left, right),
+        AlertCondition::Below { left, right } => ("低于", left, right),
+        AlertCondition::CrossAbove { left, right } => ("上穿", left, right),
+        AlertCondition::CrossBelow { left, right } => ("下穿", left, right),
+    };
+    format!("{} {} {}", operand(left), verb, operand(right))
+}
+
+/// 启动预警检查任务：交易时段内定期用最新行情评估所有启用的规则
+/// 条件由不成立变为成立时发出 alert-triggered 事件（同一状态不重复触发）
+/// 日/周/月 K 线每天从数据源补全一次，其余时间用最新行情更新最后一根
+pub fn spawn_evaluator(app: AppHandle, config_store: Arc<ConfigStore>, quotes: QuoteSnapshot) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(EVALUATE_INTERVAL);
+        let mut history: HashMap<(String, IndicatorInterval), (NaiveDate, Vec<Candle>)> = HashMap::new();
+        let mut last_state: HashMap<String, bool> = HashMap::new();
+
+        loop {
+            ticker.tick().await;
+
+            let now = calendar::china_now();
+            if !calendar::market_phase(now).is_trading() {
+                continue;
+            }
+            let config = config_store.get();
+            let rules: Vec<&AlertRule> = config.alerts.iter().filter(|r| r.enabled).collect();
+            last_state.retain(|id, _| rules.iter().any(|r| &r.id == id));
+            if rules.is_empty() {
+                continue;
+            }
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+
+            for rule in rules {
+                let Some(quote) = quotes.read().unwrap().get(&rule.stock_id).cloned() else {
+                    continue;
+                };
+
+                let key = (rule.stock_id.clone(), rule.interval);
+                let daily = matches!(
+                    rule.interval,
+                    IndicatorInterval::Day | IndicatorInterval::Week | IndicatorInterval::Month
+                );
+                let cached = history
+                    .get(&key)
+                    .filter(|(date, _)| daily && *date == now.date())
+                    .map(|(_, candles)| candles.clone());
+                let mut candles = match cached {
+                    Some(candles) => candles,
+                    None => match indicators::load_candles(
+                        &state.tick_store,
+                        &state.intraday,
+                        &config.app.data_sources,
+                        &rule.stock_id,
+                        rule.interval,
+                    )
+                    .await
+                    {
+                        Ok(candles) => {
+                            history.insert(key, (now.date(), candles.clone()));
+                            candles
+                        }
+                        Err(e) => {
+                            log::warn!("预警 {} 加载K线失败: {}", rule.id, e);
+                            continue;
+                        }
+                    },
+                };
+                patch_live(&mut candles, rule.interval, &quote);
+
+                let matched = match evaluate(&rule.condition, &candles) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        log::warn!("预警 {} 计算失败: {}", rule.id, e);
+                        continue;
+                    }
+                };
+                let was_matched = last_state.insert(rule.id.clone(), matched).unwrap_or(false);
+                if matched && !was_matched {
+                    let message = describe(rule);
+                    log::info!("预警触发: {} {}", quote.name, message);
+                    let _ = app.emit(
+                        "alert-triggered",
+                        AlertTriggered {
+                            rule_id: rule.id.clone(),
+                            stock_id: rule.stock_id.clone(),
+                            name: quote.name.clone(),
+                            message,
+                            price: quote.price,
+                            timestamp: quote.timestamp,
+                        },
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IndicatorSpec;
+    use crate::test_support;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Candle {
+                label: format!("2024-06-{:02}", i + 1),
+                open: *c,
+                high: *c,
+                low: *c,
+                close: *c,
+                volume: 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_close_crosses_above_ma() {
+        let condition = AlertCondition::CrossAbove {
+            left: Operand::Close,
+            right: Operand::Indicator {
+                spec: IndicatorSpec::Ma { period: 3 },
+                line: "ma".to_string(),
+            },
+        };
+        // MA3 最后两根: 10, 10.33；收盘 10 -> 11
+        assert!(evaluate(&condition, &candles(&[10.0, 10.0, 10.0, 11.0])).unwrap());
+        // 已经在均线上方，不算上穿
+        assert!(!evaluate(&condition, &candles(&[10.0, 10.0, 11.0, 12.0])).unwrap());
+        // 数据不足
+        assert!(!evaluate(&condition, &candles(&[10.0, 11.0])).unwrap());
+    }
+
+    #[test]
+    fn test_price_level_and_unknown_line() {
+        let above = AlertCondition::Above {
+            left: Operand::Close,
+            right: Operand::Value { value: 1800.0 },
+        };
+        assert!(evaluate(&above, &candles(&[1790.0, 1801.0])).unwrap());
+        assert!(!evaluate(&above, &candles(&[1801.0, 1799.0])).unwrap());
+
+        let bad = AlertCondition::Below {
+            left: Operand::Indicator {
+                spec: IndicatorSpec::Ma { period: 3 },
+                line: "dif".to_string(),
+            },
+            right: Operand::Close,
+        };
+        assert!(evaluate(&bad, &candles(&[1.0, 2.0, 3.0])).is_err());
+
+        let vwap = AlertCondition::CrossAbove {
+            left: Operand::Close,
+            right: Operand::Indicator { spec: IndicatorSpec::Vwap, line: "vwap".to_string() },
+        };
+        assert!(validate(&vwap, IndicatorInterval::Intraday).is_ok());
+        assert!(validate(&vwap, IndicatorInterval::Min1).is_err());
+    }
+
+    #[test]
+    fn test_patch_live_updates_or_appends_daily_candle() {
+        // 2024-06-03 10:00 北京时间
+        let timestamp = 1_717_380_000_000;
+        let quote = PriceUpdate {
+            high: 10.6,
+            low: 9.9,
+            timestamp,
+            ..test_support::quote("sh600000", 10.5, 10.0)
+        };
+
+        let mut data = candles(&[10.0, 10.0]);
+        patch_live(&mut data, IndicatorInterval::Day, &quote);
+        assert_eq!(data.len(), 3);
+        assert_eq!(data[2].label, "2024-06-03");
+        assert_eq!(data[2].close, 10.5);
+
+        patch_live(&mut data, IndicatorInterval::Day, &PriceUpdate { price: 10.8, ..quote.clone() });
+        assert_eq!(data.len(), 3);
+        assert_eq!(data[2].close, 10.8);
+        assert_eq!(data[2].high, 10.8);
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::{HashMap, HashSet};
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::ledger::LedgerStore;
+use crate::models::{
+    Broker, ImportPreview, ImportRow, ImportRowStatus, NewTransaction, PortfolioConfig,
+    TransactionKind,
+};
+
+/// 券商对账单的列名配置，每个字段按顺序尝试多个列名
+struct Profile {
+    broker: Broker,
+    date: &'static [&'static str],
+    code: &'static [&'static str],
+    name: &'static [&'static str],
+    action: &'static [&'static str],
+    shares: &'static [&'static str],
+    price: &'static [&'static str],
+    amount: &'static [&'static str],
+    /// 资金发生额（分红、红利税等以此为准，带符号）
+    settle: &'static [&'static str],
+    commission: &'static [&'static str],
+    stamp_duty: &'static [&'static str],
+    transfer_fee: &'static [&'static str],
+    /// 合同/成交编号，用于重复导入去重
+    reference: &'static [&'static str],
+}
+
+const HUATAI: Profile = Profile {
+    broker: Broker::Huatai,
+    date: &["成交日期"],
+    code: &["证券代码"],
+    name: &["证券名称"],
+    action: &["操作", "买卖标志"],
+    shares: &["成交数量"],
+    price: &["成交均价", "成交价格"],
+    amount: &["成交金额"],
+    settle: &["发生金额"],
+    commission: &["佣金"],
+    stamp_duty: &["印花税"],
+    transfer_fee: &["过户费"],
+    reference: &["合同编号"],
+};
+
+const CITIC: Profile = Profile {
+    broker: Broker::Citic,
+    date: &["发生日期"],
+    code: &["证券代码"],
+    name: &["证券名称"],
+    action: &["业务名称"],
+    shares: &["成交数量"],
+    price: &["成交价格"],
+    amount: &["成交金额"],
+    settle: &["发生金额", "清算金额"],
+    commission: &["手续费", "佣金"],
+    stamp_duty: &["印花税"],
+    transfer_fee: &["过户费"],
+    reference: &["委托编号", "合同编号"],
+};
+
+const EASTMONEY: Profile = Profile {
+    broker: Broker::Eastmoney,
+    date: &["交割日期", "成交日期"],
+    code: &["证券代码"],
+    name: &["证券名称"],
+    action: &["委托类别", "业务名称"],
+    shares: &["成交数量"],
+    price: &["成交价格"],
+    amount: &["成交金额"],
+    settle: &["发生金额"],
+    commission: &["佣金"],
+    stamp_duty: &["印花税"],
+    transfer_fee: &["过户费"],
+    reference: &["成交编号", "合同编号"],
+};
+
+/// 通用配置：汇总各券商常见列名
+const GENERIC: Profile = Profile {
+    broker: Broker::Generic,
+    date: &["成交日期", "交割日期", "发生日期", "日期"],
+    code: &["证券代码", "股票代码", "代码"],
+    name: &["证券名称", "股票名称", "名称"],
+    action: &["操作", "买卖标志", "业务名称", "委托类别", "摘要"],
+    shares: &["成交数量", "成交股数", "数量"],
+    price: &["成交均价", "成交价格", "价格"],
+    amount: &["成交金额"],
+    settle: &["发生金额", "清算金额"],
+    commission: &["佣金", "手续费"],
+    stamp_duty: &["印花税"],
+    transfer_fee: &["过户费"],
+    reference: &["合同编号", "委托编号", "成交编号"],
+};
+
+const PROFILES: [&Profile; 4] = [&HUATAI, &CITIC, &EASTMONEY, &GENERIC];
+
+fn profile_for(broker: Broker) -> &'static Profile {
+    PROFILES.iter().find(|p| p.broker == broker).unwrap_or(&&GENERIC)
+}
+
+fn broker_key(broker: Broker) -> &'static str {
+    match broker {
+        Broker::Huatai => "huatai",
+        Broker::Citic => "citic",
+        Broker::Eastmoney => "eastmoney",
+        Broker::Generic => "generic",
+    }
+}
+
+/// 表头中各字段所在的列
+struct Columns {
+    date: usize,
+    code: usize,
+    action: usize,
+    shares: usize,
+    name: Option<usize>,
+    price: Option<usize>,
+    amount: Option<usize>,
+    settle: Option<usize>,
+    commission: Option<usize>,
+    stamp_duty: Option<usize>,
+    transfer_fee: Option<usize>,
+    reference: Option<usize>,
+    /// 命中的列数，用于自动识别券商
+    matched: usize,
+}
+
+impl Columns {
+    fn resolve(profile: &Profile, header: &[String]) -> Option<Self> {
+        let find = |names: &[&str]| names.iter().find_map(|n| header.iter().position(|h| h == n));
+        let optional = [
+            find(profile.name),
+            find(profile.price),
+            find(profile.amount),
+            find(profile.settle),
+            find(profile.commission),
+            find(profile.stamp_duty),
+            find(profile.transfer_fee),
+            find(profile.reference),
+        ];
+        let [name, price, amount, settle, commission, stamp_duty, transfer_fee, reference] = optional;
+        Some(Self {
+            date: find(profile.date)?,
+            code: find(profile.code)?,
+            action: find(profile.action)?,
+            shares: find(profile.shares)?,
+            name,
+            price,
+            amount,
+            settle,
+            commission,
+            stamp_duty,
+            transfer_fee,
+            reference,
+            matched: 4 + optional.iter().flatten().count(),
+        })
+    }
+}
+
+/// 将文件内容解码为文本：UTF-8（可带 BOM）原样使用，否则按 GBK 解码
+/// 券商导出的 .xls 多为制表符分隔的文本，真正的二进制 Excel 文件不支持
+pub(crate) fn decode(bytes: &[u8]) -> Result<String> {
+    if bytes.starts_with(&[0xD0, 0xCF, 0x11, 0xE0]) || bytes.starts_with(b"PK\x03\x04") {
+        anyhow::bail!("不支持二进制 Excel 文件，请在券商软件中导出为 CSV/TXT，或另存为 CSV 后导入");
+    }
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text.to_string()),
+        Err(_) => Ok(encoding_rs::GBK.decode(bytes).0.into_owned()),
+    }
+}
+
+/// 拆分一行，支持双引号包裹的字段；去掉 Excel 文本格式的 ="..." 前缀和首尾空白
+pub(crate) fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    for c in line.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+        .into_iter()
+        .map(|c| c.trim().trim_start_matches('=').trim().to_string())
+        .collect()
+}
+
+fn parse_number(cell: Option<&String>) -> Option<f64> {
+    let text: String = cell?.chars().filter(|c| *c != ',' && !c.is_whitespace()).collect();
+    text.parse().ok()
+}
+
+fn parse_date(cell: &str) -> Option<NaiveDate> {
+    let cell = cell.get(..10).unwrap_or(cell);
+    ["%Y%m%d", "%Y-%m-%d", "%Y/%m/%d"]
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(cell, f).ok())
+        .or_else(|| NaiveDate::parse_from_str(cell.get(..8)?, "%Y%m%d").ok())
+}
+
+/// 根据业务名称识别交易类型，不支持的业务返回跳过原因
+fn classify(action: &str) -> std::result::Result<TransactionKind, String> {
+    if action.contains("红股") || action.contains("送股") || action.contains("转增") {
+        Err("送转股需手动录入送转比例".to_string())
+    } else if action.contains("红利") || action.contains("股息") {
+        Ok(TransactionKind::Dividend)
+    } else if action.contains("卖") {
+        Ok(TransactionKind::Sell)
+    } else if action.contains("买") {
+        Ok(TransactionKind::Buy)
+    } else {
+        Err(format!("不支持的业务类型: {}", action))
+    }
+}
+
+/// 解析对账单，返回识别出的券商和逐行结果（此时尚未做去重，可导入的行均为 New）
+/// broker 为空时根据表头自动识别
+pub fn parse_statement(bytes: &[u8], broker: Option<Broker>) -> Result<(Broker, Vec<ImportRow>)> {
+    let text = decode(bytes)?;
+    let lines: Vec<&str> = text.lines().collect();
+
+    // 表头之前可能有标题、账号等说明行，找到第一行能识别出必需列的作为表头
+    let mut found = None;
+    for (index, line) in lines.iter().enumerate() {
+        let delimiter = if line.contains('\t') { '\t' } else { ',' };
+        let header = split_line(line, delimiter);
+        let candidates: Vec<&Profile> = match broker {
+            Some(b) => vec![profile_for(b)],
+            None => PROFILES.to_vec(),
+        };
+        let best = candidates
+            .into_iter()
+            .filter_map(|p| Columns::resolve(p, &header).map(|c| (p, c)))
+            .max_by_key(|(p, c)| (c.matched, p.broker != Broker::Generic));
+        if let Some((profile, columns)) = best {
+            found = Some((index, delimiter, profile, columns));
+            break;
+        }
+    }
+    let Some((header_index, delimiter, profile, columns)) = found else {
+        anyhow::bail!("未找到对账单表头（需要包含成交日期、证券代码、业务类型、成交数量等列）");
+    };
+
+    let key = broker_key(profile.broker);
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut rows = Vec::new();
+
+    for (index, line) in lines.iter().enumerate().skip(header_index + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let cells = split_line(line, delimiter);
+        let cell = |i: usize| cells.get(i).map(String::as_str).unwrap_or("");
+        let name = columns.name.map(cell).unwrap_or("").to_string();
+        let mut row = ImportRow {
+            line: index + 1,
+            name,
+            status: ImportRowStatus::New,
+            transaction: None,
+        };
+
+        let code = cell(columns.code);
+        let action = cell(columns.action);
+        let result = (|| {
+            if code.is_empty() {
+                return Err("非证券交易".to_string());
+            }
+            if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("无法识别的证券代码: {}", code));
+            }
+            let date = parse_date(cell(columns.date)).ok_or_else(|| format!("无法识别的日期: {}", cell(columns.date)))?;
+            let kind = classify(action)?;
+
+            let shares = parse_number(cells.get(columns.shares)).unwrap_or(0.0).abs();
+            let price = parse_number(columns.price.and_then(|i| cells.get(i))).unwrap_or(0.0);
+            let amount = parse_number(columns.amount.and_then(|i| cells.get(i))).map(f64::abs);
+            let settle = parse_number(columns.settle.and_then(|i| cells.get(i)));
+            let fee = |col: Option<usize>| parse_number(col.and_then(|i| cells.get(i))).map(f64::abs);
+
+            let mut tx = NewTransaction {
+                code: code.to_string(),
+                kind,
+                date,
+                shares: 0.0,
+                price: 0.0,
+                amount: 0.0,
+                ratio: 0.0,
+                commission: fee(columns.commission),
+                stamp_duty: fee(columns.stamp_duty),
+                transfer_fee: fee(columns.transfer_fee),
+                note: action.to_string(),
+                source_ref: String::new(),
+            };
+            match kind {
+                TransactionKind::Buy | TransactionKind::Sell => {
+                    if shares <= 0.0 {
+                        return Err("成交数量为 0（可能为撤单）".to_string());
+                    }
+                    tx.shares = shares;
+                    // 部分券商不导出均价，按成交金额反推
+                    tx.price = if price > 0.0 { price } else { amount.unwrap_or(0.0) / shares };
+                }
+                // 红利入账为正、红利税补缴为负，以资金发生额为准
+                _ => tx.amount = settle.or(amount).unwrap_or(0.0),
+            }
+
+            let reference = columns.reference.map(cell).unwrap_or("");
+            let base = if reference.is_empty() {
+                format!("{}:{}:{}:{}:{}:{}", key, date, code, action, shares, price)
+            } else {
+                format!("{}:{}:{}", key, date, reference)
+            };
+            // 同一文件中完全相同的行按出现次数区分，保证重复导入时结果一致
+            let count = occurrences.entry(base.clone()).or_insert(0);
+            *count += 1;
+            tx.source_ref = if *count == 1 { base } else { format!("{}#{}", base, count) };
+            Ok(tx)
+        })();
+
+        match result {
+            Ok(tx) => row.transaction = Some(tx),
+            Err(reason) => row.status = ImportRowStatus::Skipped(reason),
+        }
+        rows.push(row);
+    }
+
+    Ok((profile.broker, rows))
+}
+
+/// 生成导入预览：解析对账单，标记已导入过的行，并校验追加后的流水是否一致
+pub fn preview(
+    bytes: &[u8],
+    broker: Option<Broker>,
+    ledger: &LedgerStore,
+    portfolio: &PortfolioConfig,
+) -> Result<ImportPreview> {
+    let (broker, mut rows) = parse_statement(bytes, broker)?;
+    mark_duplicates(&mut rows, &ledger.source_refs());
+
+    let new: Vec<NewTransaction> = new_transactions(&rows);
+    let error = ledger
+        .check_append(&new, &portfolio.fees, portfolio.cost_method)
+        .err()
+        .map(|e| e.to_string());
+
+    let count = |f: fn(&ImportRowStatus) -> bool| rows.iter().filter(|r| f(&r.status)).count();
+    Ok(ImportPreview {
+        broker,
+        new_count: count(|s| *s == ImportRowStatus::New),
+        duplicate_count: count(|s| *s == ImportRowStatus::Duplicate),
+        skipped_count: count(|s| matches!(s, ImportRowStatus::Skipped(_))),
+        rows,
+        error,
+    })
+}
+
+/// 预览中将被导入的交易
+pub fn new_transactions(rows: &[ImportRow]) -> Vec<NewTransaction> {
+    rows.iter()
+        .filter(|r| r.status == ImportRowStatus::New)
+        .filter_map(|r| r.transaction.clone())
+        .collect()
+}
+
+fn mark_duplicates(rows: &mut [ImportRow], existing: &HashSet<String>) {
+    for row in rows.iter_mut() {
+        if let Some(tx) = &row.transaction {
+            if existing.contains(&tx.source_ref) {
+                row.status = ImportRowStatus::Duplicate;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gbk(text: &str) -> Vec<u8> {
+        encoding_rs::GBK.encode(text).0.into_owned()
+    }
+
+    #[test]
+    fn test_parse_huatai_gbk_csv() {
+        let text = "资金账号:12345678\n\
+            成交日期,证券代码,证券名称,操作,成交数量,成交均价,成交金额,佣金,印花税,过户费,发生金额,合同编号\n\
+            20240102,600519,贵州茅台,证券买入,100,1700.00,170000.00,42.50,0.00,1.70,-170044.20,1001\n\
+            20240105,600519,贵州茅台,证券卖出,-100,1750.00,175000.00,43.75,87.50,1.75,174867.00,1002\n\
+            20240110,,,银证转入,0,0,0,0,0,0,50000.00,\n";
+        let (broker, rows) = parse_statement(&gbk(text), None).unwrap();
+        assert_eq!(broker, Broker::Huatai);
+        assert_eq!(rows.len(), 3);
+
+        let buy = rows[0].transaction.as_ref().unwrap();
+        assert_eq!(rows[0].name, "贵州茅台");
+        assert_eq!(buy.kind, TransactionKind::Buy);
+        assert_eq!(buy.date, NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+        assert_eq!(buy.commission, Some(42.5));
+        assert_eq!(buy.source_ref, "huatai:2024-01-02:1001");
+
+        let sell = rows[1].transaction.as_ref().unwrap();
+        assert_eq!(sell.kind, TransactionKind::Sell);
+        assert_eq!(sell.shares, 100.0);
+        assert_eq!(sell.stamp_duty, Some(87.5));
+
+        assert_eq!(rows[2].status, ImportRowStatus::Skipped("非证券交易".to_string()));
+    }
+
+    #[test]
+    fn test_parse_citic_tab_separated_with_text_cells() {
+        let text = "发生日期\t业务名称\t证券代码\t证券名称\t成交数量\t成交价格\t成交金额\t手续费\t印花税\t过户费\t发生金额\t委托编号\n\
+            20240301\t证券买入\t=\"000001\"\t平安银行\t1,000\t10.50\t10500.00\t5.00\t0.00\t0.11\t-10505.11\t=\"A1\"\n\
+            20240620\t红利入账\t=\"000001\"\t平安银行\t0\t0\t0\t0\t0\t0\t719.00\t\n\
+            20240621\t股息红利税补缴\t=\"000001\"\t平安银行\t0\t0\t0\t0\t0\t0\t-71.90\t\n\
+            20240701\t红股入账\t=\"000001\"\t平安银行\t300\t0\t0\t0\t0\t0\t0\t\n";
+        let (broker, rows) = parse_statement(text.as_bytes(), None).unwrap();
+        assert_eq!(broker, Broker::Citic);
+
+        let buy = rows[0].transaction.as_ref().unwrap();
+        assert_eq!(buy.code, "000001");
+        assert_eq!(buy.shares, 1000.0);
+        assert_eq!(buy.commission, Some(5.0));
+
+        assert_eq!(rows[1].transaction.as_ref().unwrap().amount, 719.0);
+        assert_eq!(rows[2].transaction.as_ref().unwrap().kind, TransactionKind::Dividend);
+        assert_eq!(rows[2].transaction.as_ref().unwrap().amount, -71.9);
+        assert!(matches!(rows[3].status, ImportRowStatus::Skipped(_)));
+    }
+
+    #[test]
+    fn test_duplicates_are_marked_by_source_ref() {
+        // 无合同编号时按行内容生成标识，相同的两行按出现次数区分
+        let text = "交割日期,证券代码,证券名称,委托类别,成交数量,成交价格,成交金额\n\
+            2024-05-06,300750,宁德时代,买入,100,180.00,18000.00\n\
+            2024-05-06,300750,宁德时代,买入,100,180.00,18000.00\n";
+        let (broker, mut rows) = parse_statement(text.as_bytes(), None).unwrap();
+        assert_eq!(broker, Broker::Eastmoney);
+
+        let first_ref = rows[0].transaction.as_ref().unwrap().source_ref.clone();
+        let second_ref = rows[1].transaction.as_ref().unwrap().source_ref.clone();
+        assert_ne!(first_ref, second_ref);
+
+        let existing: HashSet<String> = [first_ref].into_iter().collect();
+        mark_duplicates(&mut rows, &existing);
+        assert_eq!(rows[0].status, ImportRowStatus::Duplicate);
+        assert_eq!(rows[1].status, ImportRowStatus::New);
+        assert_eq!(new_transactions(&rows).len(), 1);
+    }
+
+    #[test]
+    fn test_binary_excel_and_missing_header_are_rejected() {
+        assert!(parse_statement(&[0xD0, 0xCF, 0x11, 0xE0, 0, 0], None).is_err());
+        assert!(parse_statement("日期,金额\n20240101,100\n".as_bytes(), None).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Once;
+use chrono::{Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+
+/// (年, 月, 日)
+type Ymd = (i32, u32, u32);
+
+/// 沪深交易所休市日期区间（含首尾，周末无需列出也不影响）
+/// 每年年底交易所公布次年安排后需补充；未收录的年份仅按周末判断
+const HOLIDAYS: &[(Ymd, Ymd)] = &[
+    // 2024
+    ((2024, 1, 1), (2024, 1, 1)),
+    ((2024, 2, 9), (2024, 2, 17)),
+    ((2024, 4, 4), (2024, 4, 6)),
+    ((2024, 5, 1), (2024, 5, 5)),
+    ((2024, 6, 10), (2024, 6, 10)),
+    ((2024, 9, 16), (2024, 9, 17)),
+    ((2024, 10, 1), (2024, 10, 7)),
+    // 2025
+    ((2025, 1, 1), (2025, 1, 1)),
+    ((2025, 1, 28), (2025, 2, 4)),
+    ((2025, 4, 4), (2025, 4, 6)),
+    ((2025, 5, 1), (2025, 5, 5)),
+    ((2025, 5, 31), (2025, 6, 2)),
+    ((2025, 10, 1), (2025, 10, 8)),
+    // 2026
+    ((2026, 1, 1), (2026, 1, 3)),
+    ((2026, 2, 15), (2026, 2, 23)),
+    ((2026, 4, 4), (2026, 4, 6)),
+    ((2026, 5, 1), (2026, 5, 5)),
+    ((2026, 6, 19), (2026, 6, 21)),
+    ((2026, 9, 25), (2026, 9, 27)),
+    ((2026, 10, 1), (2026, 10, 7)),
+];
+
+/// HOLIDAYS 收录的最后一年
+fn last_covered_year() -> i32 {
+    HOLIDAYS.last().map(|&((year, _, _), _)| year).unwrap_or(0)
+}
+
+/// 收盘时间（北京时间，含收盘集合竞价）
+pub fn market_close() -> NaiveTime {
+    NaiveTime::from_hms_opt(15, 0, 0).unwrap()
+}
+
+/// 收盘价稳定时间：收盘集合竞价的结果在收盘后几分钟内才会反映到各数据源的行情中
+pub fn market_settle() -> NaiveTime {
+    NaiveTime::from_hms_opt(15, 10, 0).unwrap()
+}
+
+/// 当前北京时间
+pub fn china_now() -> NaiveDateTime {
+    let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+    Utc::now().with_timezone(&offset).naive_local()
+}
+
+/// 毫秒时间戳对应的北京时间
+pub fn china_time_from_millis(millis: u64) -> Option<NaiveDateTime> {
+    let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+    chrono::DateTime::from_timestamp_millis(millis as i64).map(|t| t.with_timezone(&offset).naive_local())
+}
+
+/// 北京时间某日 0 点的毫秒时间戳
+pub fn china_day_start_millis(date: NaiveDate) -> u64 {
+    let millis = date.and_time(NaiveTime::MIN).and_utc().timestamp_millis() - 8 * 3600 * 1000;
+    millis.max(0) as u64
+}
+
+fn ymd((y, m, d): Ymd) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// 是否为 A 股交易日
+/// 超出 HOLIDAYS 收录范围的年份只按周末判断，节假日会被当作交易日，首次遇到时记录警告
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        return false;
+    }
+    if date.year() > last_covered_year() {
+        static WARN: Once = Once::new();
+        WARN.call_once(|| {
+            log::warn!(
+                "休市日历只收录到 {} 年，{} 年的节假日将被视为交易日，请更新 calendar::HOLIDAYS",
+                last_covered_year(),
+                date.year()
+            );
+        });
+    }
+    !HOLIDAYS
+        .iter()
+        .any(|&(start, end)| date >= ymd(start) && date <= ymd(end))
+}
+
+/// 给定时刻是否处于交易日收盘之后
+pub fn is_after_close(now: NaiveDateTime) -> bool {
+    is_trading_day(now.date()) && now.time() >= market_close()
+}
+
+/// 交易日内的时段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketPhase {
+    /// 非交易日
+    Closed,
+    /// 开盘前（含集合竞价前）
+    PreOpen,
+    /// 上午连续交易（9:25 集合竞价结果起）
+    Morning,
+    /// 午间休市
+    Lunch,
+    /// 下午连续交易
+    Afternoon,
+    /// 收盘后
+    AfterClose,
+}
+
+impl MarketPhase {
+    /// 行情是否仍在变化
+    pub fn is_trading(self) -> bool {
+        matches!(self, MarketPhase::Morning | MarketPhase::Afternoon)
+    }
+}
+
+/// 给定时刻所处的交易时段
+pub fn market_phase(now: NaiveDateTime) -> MarketPhase {
+    if !is_trading_day(now.date()) {
+        return MarketPhase::Closed;
+    }
+    let hm = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+    let time = now.time();
+    if time < hm(9, 25) {
+        MarketPhase::PreOpen
+    } else if time <= hm(11, 30) {
+        MarketPhase::Morning
+    } else if time < hm(13, 0) {
+        MarketPhase::Lunch
+    } else if time < market_close() {
+        MarketPhase::Afternoon
+    } else {
+        MarketPhase::AfterClose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(d: &str) -> NaiveDate {
+        d.parse().unwrap()
+    }
+
+    #[test]
+    fn test_trading_days() {
+        assert!(is_trading_day(date("2024-06-03")));
+        // 周末
+        assert!(!is_trading_day(date("2024-06-08")));
+        // 国庆、春节休市
+        assert!(!is_trading_day(date("2024-10-02")));
+        assert!(!is_trading_day(date("2025-01-30")));
+        assert!(is_trading_day(date("2025-02-05")));
+    }
+
+    #[test]
+    fn test_day_start_millis() {
+        // 2024-06-03 00:00 北京时间 = 2024-06-02 16:00 UTC
+        assert_eq!(china_day_start_millis(date("2024-06-03")), 1_717_344_000_000);
+        assert_eq!(
+            china_time_from_millis(china_day_start_millis(date("2024-06-03"))),
+            date("2024-06-03").and_hms_opt(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_years_beyond_holiday_table() {
+        assert_eq!(last_covered_year(), 2026);
+        // 未收录的年份只按周末判断，元旦也被当作交易日
+        assert!(is_trading_day(date("2027-01-01")));
+        assert!(!is_trading_day(date("2027-01-02")));
+    }
+
+    #[test]
+    fn test_after_close() {
+        let close = date("2024-06-03").and_hms_opt(15, 0, 0).unwrap();
+        assert!(is_after_close(close));
+        assert!(!is_after_close(date("2024-06-03").and_hms_opt(14, 59, 0).unwrap()));
+        assert!(!is_after_close(date("2024-06-08").and_hms_opt(16, 0, 0).unwrap()));
+    }
+}
@@ -1,7 +1,25 @@
-use std::path::PathBuf;
-use std::sync::RwLock;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
 use anyhow::{Context, Result};
-use crate::models::Config;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::de::DeserializeOwned;
+use crate::{groups, migrate, validate, watchlist};
+use crate::undo::EditHistory;
+use crate::models::{
+    AlertRule, Config, ConfigBackup, DerivedPosition, Holding, ImportMode, StockOverrides, UndoState,
+    WatchGroup, WatchlistFormat, WatchlistImport,
+};
+
+/// 保留的历史备份数量
+const MAX_BACKUPS: usize = 10;
+
+/// 两次自动备份的最小间隔（秒），避免连续保存时备份很快被轮换掉
+const BACKUP_MIN_INTERVAL_SECS: i64 = 600;
+
+/// 备份文件名中的时间格式（本地时间）
+const BACKUP_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
 
 /// 获取配置文件路径
 /// macOS: ~/Library/Application Support/com.wolf.stealth-stock-monitor/config.json
@@ -33,6 +51,184 @@ fn config_file_path() -> Result<PathBuf> {
     Ok(get_config_dir()?.join("config.json"))
 }
 
+fn backup_dir() -> Result<PathBuf> {
+    let dir = get_config_dir()?.join("backups");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 配置方案目录，每个非当前方案一个完整的配置文件，如 profiles/办公.json
+fn profiles_dir() -> Result<PathBuf> {
+    let dir = get_config_dir()?.join("profiles");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// 配置方案文件路径（名称无效时返回错误，避免写到 profiles 目录以外）
+fn profile_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    if !validate::is_valid_profile_name(name) {
+        anyhow::bail!("无效的配置方案名称: {}", name);
+    }
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// 列出目录中保存的方案和当前方案的名称（按名称排序）
+fn list_profiles_in(dir: &Path, current: &str) -> Result<Vec<String>> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .context("读取配置方案目录失败")?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.strip_suffix(".json")?.to_string();
+            validate::is_valid_profile_name(&name).then_some(name)
+        })
+        .collect();
+    names.push(current.to_string());
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// 先写入同目录下的临时文件并 fsync，再重命名覆盖目标文件
+/// 写入过程中崩溃或断电时，目标文件要么是旧内容，要么是完整的新内容
+pub fn atomic_write(path: &Path, content: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().context("无效的文件路径")?.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    {
+        let mut file = File::create(&tmp).context("创建临时文件失败")?;
+        file.write_all(content).context("写入临时文件失败")?;
+        file.sync_all().context("同步临时文件失败")?;
+    }
+    std::fs::rename(&tmp, path).context("替换文件失败")?;
+    // 同步目录项，确保重命名本身落盘（Windows 不支持打开目录，跳过）
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// 读取应用数据文件（交易流水、收盘记录等）；文件不存在时返回默认值
+/// 无法读取或解析时（如手动编辑出错）不阻止启动：原文件重命名为 *.bad 保留，返回默认值
+pub fn read_data_file<T: DeserializeOwned + Default>(path: &Path) -> T {
+    if !path.exists() {
+        return T::default();
+    }
+    let result = std::fs::read_to_string(path)
+        .context("读取失败")
+        .and_then(|content| serde_json::from_str(&content).context("解析失败"));
+    match result {
+        Ok(data) => data,
+        Err(e) => {
+            let mut bad_name = path.file_name().unwrap_or_default().to_os_string();
+            bad_name.push(".bad");
+            let bad = path.with_file_name(bad_name);
+            log::error!("数据文件 {} 无效，已改名为 {} 并使用空数据: {:#}", path.display(), bad.display(), e);
+            if let Err(e) = std::fs::rename(path, &bad) {
+                log::error!("保留无效数据文件失败: {}", e);
+            }
+            T::default()
+        }
+    }
+}
+
+/// 从备份文件名解析备份时间，如 config-20240603-153000.json
+fn backup_time(name: &str) -> Option<NaiveDateTime> {
+    let stamp = name.strip_prefix("config-")?.strip_suffix(".json")?;
+    NaiveDateTime::parse_from_str(stamp, BACKUP_TIME_FORMAT).ok()
+}
+
+/// 列出目录中的备份，最新的在前
+fn list_backups_in(dir: &Path) -> Result<Vec<ConfigBackup>> {
+    let mut backups: Vec<(NaiveDateTime, ConfigBackup)> = std::fs::read_dir(dir)
+        .context("读取备份目录失败")?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let time = backup_time(&name)?;
+            let size = entry.metadata().ok()?.len();
+            let timestamp = Local
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.timestamp_millis() as u64)
+                .unwrap_or(0);
+            Some((time, ConfigBackup { name, timestamp, size }))
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    Ok(backups.into_iter().map(|(_, b)| b).collect())
+}
+
+/// 将当前配置文件复制为带时间戳的备份，并删除超出 MAX_BACKUPS 的旧备份
+/// 内容与最新备份相同时跳过；非强制时距上次备份不足 BACKUP_MIN_INTERVAL_SECS 也跳过
+/// 返回是否新建了备份
+fn backup_into(dir: &Path, config_path: &Path, now: NaiveDateTime, force: bool) -> Result<bool> {
+    if !config_path.exists() {
+        return Ok(false);
+    }
+    let content = std::fs::read(config_path).context("读取配置文件失败")?;
+    let backups = list_backups_in(dir)?;
+    if let Some(latest) = backups.first() {
+        if std::fs::read(dir.join(&latest.name)).is_ok_and(|c| c == content) {
+            return Ok(false);
+        }
+        let recent = backup_time(&latest.name)
+            .is_some_and(|t| (now - t).num_seconds() < BACKUP_MIN_INTERVAL_SECS);
+        if recent && !force {
+            return Ok(false);
+        }
+    }
+
+    let name = format!("config-{}.json", now.format(BACKUP_TIME_FORMAT));
+    atomic_write(&dir.join(&name), &content)?;
+    for old in list_backups_in(dir)?.iter().skip(MAX_BACKUPS) {
+        let _ = std::fs::remove_file(dir.join(&old.name));
+    }
+    Ok(true)
+}
+
+/// 备份当前配置文件（见 backup_into）
+fn backup_current(force: bool) -> Result<bool> {
+    backup_into(&backup_dir()?, &config_file_path()?, Local::now().naive_local(), force)
+}
+
+/// 列出配置备份，最新的在前
+pub fn list_backups() -> Result<Vec<ConfigBackup>> {
+    list_backups_in(&backup_dir()?)
+}
+
+/// 按版本迁移并解析配置内容，并校验各字段取值
+fn parse_config(value: &mut serde_json::Value) -> Result<Config> {
+    migrate::migrate(value)?;
+    let mut config = serde_json::from_value(value.clone()).context("配置结构无效")?;
+    groups::normalize(&mut config);
+    validate::check(&config)?;
+    Ok(config)
+}
+
+/// 读取并解析指定备份（只接受 list_backups 中列出的文件名）
+fn read_backup(name: &str) -> Result<Config> {
+    // 文件名必须符合备份格式，避免读取备份目录以外的文件
+    if backup_time(name).is_none() {
+        anyhow::bail!("无效的备份名称: {}", name);
+    }
+    let content = std::fs::read_to_string(backup_dir()?.join(name))
+        .with_context(|| format!("读取备份失败: {}", name))?;
+    let mut value = serde_json::from_str(&content).context("备份不是有效的 JSON")?;
+    parse_config(&mut value)
+}
+
+/// 从最新的可解析备份中读取配置内容（配置文件损坏时使用）
+fn latest_valid_backup() -> Option<(String, serde_json::Value)> {
+    list_backups().ok()?.into_iter().find_map(|backup| {
+        let content = std::fs::read_to_string(backup_dir().ok()?.join(&backup.name)).ok()?;
+        let value = serde_json::from_str(&content).ok()?;
+        Some((backup.name, value))
+    })
+}
+
 /// 从文件加载配置
 pub fn load_config() -> Result<Config> {
     let path = config_file_path()?;
@@ -47,87 +243,695 @@ pub fn load_config() -> Result<Config> {
     let content = std::fs::read_to_string(&path)
         .context("读取配置文件失败")?;
 
-    match serde_json::from_str::<Config>(&content) {
-        Ok(config) => Ok(config),
+    let mut recovered = false;
+    let mut value: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            // 不是有效的 JSON（如写入中断）：保留损坏文件，优先从最近的备份恢复
+            log::warn!("配置文件不是有效的 JSON: {}", e);
+            let _ = std::fs::copy(&path, path.with_extension("json.bak"));
+            match latest_valid_backup() {
+                Some((name, value)) => {
+                    log::warn!("已从备份 {} 恢复配置", name);
+                    recovered = true;
+                    value
+                }
+                None => {
+                    log::warn!("没有可用的备份，使用默认配置");
+                    let config = Config::default();
+                    save_config(&config)?;
+                    return Ok(config);
+                }
+            }
+        }
+    };
+
+    // 按版本号升级旧配置；迁移失败时按原内容继续解析
+    let original = value.clone();
+    let migrated_from = migrate::migrate(&mut value).unwrap_or_else(|e| {
+        log::warn!("配置迁移失败，按原内容加载: {}", e);
+        value = original;
+        None
+    });
+
+    match serde_json::from_value::<Config>(value.clone()) {
+        Ok(mut config) => {
+            groups::normalize(&mut config);
+            // 结构正确但取值无效（如刷新间隔为 0）：备份原文件，移除无效条目、无效字段恢复默认值
+            if !validate::validate(&config).is_empty() {
+                if !recovered {
+                    let _ = std::fs::copy(&path, path.with_extension("json.bak"));
+                }
+                let config = validate::repair(config);
+                save_config(&config)?;
+                return Ok(config);
+            }
+            if let Some(from) = migrated_from {
+                // 保留迁移前的文件，再写回升级后的配置
+                if !recovered {
+                    let _ = std::fs::copy(&path, path.with_extension(format!("v{}.json.bak", from)));
+                }
+                save_config(&config)?;
+            } else if recovered {
+                save_config(&config)?;
+            }
+            Ok(config)
+        }
         Err(e) => {
-            // JSON 解析失败：备份损坏文件，使用默认配置
-            log::warn!("配置文件解析失败，使用默认配置: {}", e);
-            let backup_path = path.with_extension("json.bak");
-            let _ = std::fs::copy(&path, &backup_path);
-            let config = Config::default();
+            // 结构不符：备份原文件，逐节保留可用的配置
+            log::warn!("配置文件解析失败，尽量保留可用部分: {}", e);
+            let _ = std::fs::copy(&path, path.with_extension("json.bak"));
+            let config = validate::repair(migrate::salvage(&value));
             save_config(&config)?;
             Ok(config)
         }
     }
 }
 
-/// 保存配置到文件
-pub fn save_config(config: &Config) -> Result<()> {
+/// 解析外部写入的配置内容（按版本迁移后严格解析并校验，不做部分恢复）
+pub fn parse_content(content: &str) -> Result<Config> {
+    let mut value = serde_json::from_str(content).context("不是有效的 JSON")?;
+    parse_config(&mut value)
+}
+
+/// 保存配置到文件（原子替换，覆盖前按需备份旧文件），返回写入的内容
+pub fn save_config(config: &Config) -> Result<String> {
     let path = config_file_path()?;
     let content = serde_json::to_string_pretty(config)
         .context("序列化配置失败")?;
-    std::fs::write(&path, content)
+    if let Err(e) = backup_current(false) {
+        log::warn!("备份配置文件失败: {}", e);
+    }
+    atomic_write(&path, content.as_bytes())
         .context("写入配置文件失败")?;
+    Ok(content)
+}
+
+/// 读取并解析保存的配置方案
+fn read_profile(name: &str) -> Result<Config> {
+    let path = profile_path(&profiles_dir()?, name)?;
+    let content = std::fs::read_to_string(&path).with_context(|| format!("配置方案不存在: {}", name))?;
+    let mut config = parse_content(&content).with_context(|| format!("配置方案 {} 无效", name))?;
+    config.profile = name.to_string();
+    Ok(config)
+}
+
+/// 将配置保存为指定方案的文件
+fn write_profile(name: &str, config: &Config) -> Result<()> {
+    let path = profile_path(&profiles_dir()?, name)?;
+    let config = Config {
+        profile: name.to_string(),
+        ..config.clone()
+    };
+    let content = serde_json::to_string_pretty(&config).context("序列化配置失败")?;
+    atomic_write(&path, content.as_bytes()).context("写入配置方案失败")
+}
+
+/// 将股票加入当前分组，股票已在其他分组中时只加入当前分组
+fn add_to_active_group(config: &mut Config, stock: crate::models::Stock) -> Result<()> {
+    if !validate::is_valid_stock(&stock) {
+        return Err(anyhow::anyhow!("无效的股票代码: {}", stock.code));
+    }
+    let active = config.active_group.clone();
+    let group = config
+        .groups
+        .iter_mut()
+        .find(|g| g.id == active)
+        .ok_or_else(|| anyhow::anyhow!("当前分组不存在: {}", active))?;
+    // 重复检查
+    if group.members.iter().any(|m| m.id == stock.id) {
+        return Err(anyhow::anyhow!("股票已在列表中: {}", stock.id));
+    }
+    group.members.push(groups::member_of(&stock));
+    if !config.stocks.iter().any(|s| s.id == stock.id) {
+        config.stocks.push(stock);
+    }
+    Ok(())
+}
+
+/// 从当前分组移除股票；不再属于任何分组的股票同时从自选股中删除，其预警规则一并删除
+/// 有持仓的股票不会被删除（与导入替换一致），需先清除持仓
+fn remove_from_active_group(config: &mut Config, id: &str) -> Result<()> {
+    let active = config.active_group.clone();
+    let grouped = config
+        .groups
+        .iter()
+        .any(|g| g.id != active && g.members.iter().any(|m| m.id == id));
+    if !grouped && config.stocks.iter().any(|s| s.id == id && s.holding.is_some()) {
+        anyhow::bail!("{} 有持仓，请先清除持仓再移除", id);
+    }
+    for group in config.groups.iter_mut().filter(|g| g.id == active) {
+        group.members.retain(|m| m.id != id);
+    }
+    if !grouped {
+        config.stocks.retain(|s| s.id != id);
+    }
+    groups::normalize(config);
     Ok(())
 }
 
 /// 全局配置状态（线程安全）
 pub struct ConfigStore {
     config: RwLock<Config>,
+    /// 本程序最近一次写入（或加载）的文件内容，用于区分自身写入与外部修改
+    written: Mutex<String>,
+    /// 自选股编辑历史（撤销 / 重做）
+    history: Mutex<EditHistory>,
 }
 
 impl ConfigStore {
     pub fn new() -> Result<Self> {
         let config = load_config()?;
+        let written = std::fs::read_to_string(config_file_path()?).unwrap_or_default();
         Ok(Self {
             config: RwLock::new(config),
+            written: Mutex::new(written),
+            history: Mutex::new(EditHistory::default()),
         })
     }
 
+    /// 测试用：使用给定配置，不读取配置文件
+    #[cfg(test)]
+    pub(crate) fn in_memory(config: Config) -> Self {
+        Self {
+            config: RwLock::new(config),
+            written: Mutex::new(String::new()),
+            history: Mutex::new(EditHistory::default()),
+        }
+    }
+
+    /// 保存配置并记录写入的内容
+    /// 写入期间持有 written 锁，文件监听读取时不会看到未记录的自身写入
+    fn save(&self, config: &Config) -> Result<()> {
+        let mut written = self.written.lock().unwrap();
+        *written = save_config(config)?;
+        Ok(())
+    }
+
+    /// 保存自选股编辑并记入撤销历史（before 为编辑前的配置）
+    fn save_edit(&self, before: &Config, config: &Config) -> Result<()> {
+        self.save(config)?;
+        self.history.lock().unwrap().record(before, config);
+        Ok(())
+    }
+
+    /// 读取磁盘上的配置文件；内容与本程序最近一次写入的不同（即被外部修改）时返回该内容
+    pub fn external_content(&self) -> Result<Option<String>> {
+        let written = self.written.lock().unwrap();
+        let content = std::fs::read_to_string(config_file_path()?).context("读取配置文件失败")?;
+        Ok((content != *written).then_some(content))
+    }
+
+    /// 采用外部修改后的配置（文件已是该内容，不再写回）
+    /// 撤销历史基于被替换前的配置，一并清空
+    pub fn reload_external(&self, config: Config, content: String) {
+        *self.written.lock().unwrap() = content;
+        *self.config.write().unwrap() = config;
+        self.history.lock().unwrap().clear();
+    }
+
     /// 读取当前配置的克隆副本
     pub fn get(&self) -> Config {
         self.config.read().unwrap().clone()
     }
 
-    /// 更新配置并持久化
-    pub fn update(&self, new_config: Config) -> Result<()> {
-        save_config(&new_config)?;
+    /// 整理分组后校验并更新配置，无效时不保存并返回全部字段错误
+    /// 自选股有变化时（如设置窗口中切换显示状态）记入撤销历史
+    pub fn update(&self, mut new_config: Config) -> Result<()> {
+        groups::normalize(&mut new_config);
+        validate::check(&new_config)?;
         let mut config = self.config.write().unwrap();
+        self.save_edit(&config, &new_config)?;
         *config = new_config;
         Ok(())
     }
 
-    /// 添加股票
+    /// 用指定备份替换当前配置，替换前强制备份当前文件以便撤销
+    pub fn restore_backup(&self, name: &str) -> Result<Config> {
+        let restored = read_backup(name)?;
+        backup_current(true)?;
+        self.update(restored.clone())?;
+        log::info!("已从备份 {} 恢复配置", name);
+        Ok(restored)
+    }
+
+    /// 列出全部配置方案名称（含当前方案）
+    pub fn list_profiles(&self) -> Result<Vec<String>> {
+        list_profiles_in(&profiles_dir()?, &self.config.read().unwrap().profile)
+    }
+
+    /// 以当前配置为模板新建配置方案（不切换）
+    pub fn create_profile(&self, name: &str) -> Result<()> {
+        let name = name.trim();
+        let config = self.config.read().unwrap();
+        if list_profiles_in(&profiles_dir()?, &config.profile)?.iter().any(|n| n == name) {
+            anyhow::bail!("配置方案已存在: {}", name);
+        }
+        write_profile(name, &config)
+    }
+
+    /// 删除保存的配置方案（不能删除当前方案）
+    pub fn delete_profile(&self, name: &str) -> Result<()> {
+        if self.config.read().unwrap().profile == name {
+            anyhow::bail!("不能删除当前配置方案");
+        }
+        let path = profile_path(&profiles_dir()?, name)?;
+        std::fs::remove_file(path).with_context(|| format!("删除配置方案失败: {}", name))
+    }
+
+    /// 切换到指定配置方案：当前配置存回方案目录，目标方案写入 config.json 并替换内存中的配置
+    /// 全程持有写锁，其他线程只会看到切换前或切换后的完整配置；任何一步失败时当前配置保持不变
+    pub fn switch_profile(&self, name: &str) -> Result<Config> {
+        let mut config = self.config.write().unwrap();
+        if config.profile == name {
+            return Ok(config.clone());
+        }
+        let target = read_profile(name)?;
+        write_profile(&config.profile, &config)?;
+        self.save(&target)?;
+        *config = target.clone();
+        self.history.lock().unwrap().clear();
+        log::info!("已切换到配置方案: {}", name);
+        Ok(target)
+    }
+
+    /// 按名称顺序排在当前方案之后的方案；只有一个方案时返回 None
+    pub fn next_profile(&self) -> Result<Option<String>> {
+        let names = self.list_profiles()?;
+        if names.len() <= 1 {
+            return Ok(None);
+        }
+        let current = self.config.read().unwrap().profile.clone();
+        let index = names.iter().position(|n| *n == current).unwrap_or(0);
+        Ok(Some(names[(index + 1) % names.len()].clone()))
+    }
+
+    /// 添加股票到当前分组；股票已在其他分组中时只加入当前分组
     pub fn add_stock(&self, stock: crate::models::Stock) -> Result<()> {
+        self.add_stocks(vec![stock])
+    }
+
+    /// 批量添加股票到当前分组（交易软件导入），只保存一次、记为一步撤销
+    /// 任何一只无效或已在当前分组中时全部不添加
+    pub fn add_stocks(&self, stocks: Vec<crate::models::Stock>) -> Result<()> {
+        if stocks.is_empty() {
+            return Ok(());
+        }
         let mut config = self.config.write().unwrap();
-        // 重复检查
-        if config.stocks.iter().any(|s| s.id == stock.id) {
-            return Err(anyhow::anyhow!("股票已在列表中: {}", stock.id));
+        let mut updated = config.clone();
+        for stock in stocks {
+            add_to_active_group(&mut updated, stock)?;
         }
-        config.stocks.push(stock);
-        save_config(&config)?;
+        validate::check(&updated)?;
+        self.save_edit(&config, &updated)?;
+        *config = updated;
         Ok(())
     }
 
-    /// 移除股票
+    /// 从当前分组移除股票，可撤销（见 remove_from_active_group）
     pub fn remove_stock(&self, id: &str) -> Result<()> {
         let mut config = self.config.write().unwrap();
-        config.stocks.retain(|s| s.id != id);
-        save_config(&config)?;
+        let mut updated = config.clone();
+        remove_from_active_group(&mut updated, id)?;
+        self.save_edit(&config, &updated)?;
+        *config = updated;
+        Ok(())
+    }
+
+    /// 设置或清除股票的持仓信息
+    pub fn set_holding(&self, id: &str, holding: Option<Holding>) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        let mut updated = config.clone();
+        let stock = updated
+            .stocks
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| anyhow::anyhow!("股票不在列表中: {}", id))?;
+        stock.holding = holding;
+        validate::check(&updated)?;
+        self.save(&updated)?;
+        *config = updated;
+        Ok(())
+    }
+
+    /// 设置单只股票的显示与刷新选项（校验颜色、小数位数），可撤销
+    pub fn set_stock_overrides(&self, id: &str, overrides: StockOverrides) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        let mut updated = config.clone();
+        let stock = updated
+            .stocks
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| anyhow::anyhow!("股票不在列表中: {}", id))?;
+        stock.overrides = overrides;
+        validate::check(&updated)?;
+        self.save_edit(&config, &updated)?;
+        *config = updated;
+        Ok(())
+    }
+
+    /// 用交易流水推导出的持仓覆盖股票的 holding；不在列表中的持仓股会自动加入
+    /// 流水中出现过但已清仓的股票清除 holding，流水中未出现的股票保持手动录入的持仓不变
+    pub fn sync_holdings(&self, positions: &[DerivedPosition]) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        for position in positions {
+            let holding = (position.shares > 0.0).then_some(Holding {
+                shares: position.shares,
+                avg_cost: position.avg_cost,
+                open_date: position.open_date,
+            });
+            match config.stocks.iter_mut().find(|s| s.id == position.stock_id) {
+                Some(stock) => stock.holding = holding,
+                None if holding.is_some() => {
+                    let mut stock = crate::sources::make_stock(&position.stock_id);
+                    stock.holding = holding;
+                    config.stocks.push(stock);
+                }
+                None => {}
+            }
+        }
+        // 新加入的持仓股放入当前分组
+        groups::normalize(&mut config);
+        self.save(&config)?;
+        Ok(())
+    }
+
+    /// 添加预警规则
+    pub fn add_alert(&self, rule: AlertRule) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        config.alerts.push(rule);
+        self.save(&config)?;
         Ok(())
     }
 
-    /// 重新排序股票
+    /// 删除预警规则
+    pub fn remove_alert(&self, id: &str) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        config.alerts.retain(|r| r.id != id);
+        self.save(&config)?;
+        Ok(())
+    }
+
+    /// 重新排序当前分组中的股票；ids 中没有列出的股票保持原有相对顺序排在末尾，不会被删除
     pub fn reorder_stocks(&self, ids: &[String]) -> Result<()> {
         let mut config = self.config.write().unwrap();
-        let mut reordered = Vec::new();
-        for id in ids {
-            if let Some(stock) = config.stocks.iter().find(|s| &s.id == id) {
-                reordered.push(stock.clone());
+        let before = config.clone();
+        let active = config.active_group.clone();
+        if let Some(group) = config.groups.iter_mut().find(|g| g.id == active) {
+            let mut reordered = Vec::new();
+            for id in ids {
+                if let Some(member) = group.members.iter().find(|m| &m.id == id) {
+                    reordered.push(member.clone());
+                }
             }
+            let omitted = group.members.iter().filter(|m| !ids.contains(&m.id));
+            reordered.extend(omitted.cloned());
+            group.members = reordered;
         }
-        config.stocks = reordered;
-        save_config(&config)?;
+        groups::normalize(&mut config);
+        self.save_edit(&before, &config)?;
         Ok(())
     }
+
+    /// 检查分组名称：不能为空，不能与其他分组重名
+    fn check_group_name(config: &Config, name: &str, except: Option<&str>) -> Result<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("分组名称不能为空");
+        }
+        if config.groups.iter().any(|g| g.name == name && Some(g.id.as_str()) != except) {
+            anyhow::bail!("分组已存在: {}", name);
+        }
+        Ok(name.to_string())
+    }
+
+    /// 新建空分组
+    pub fn create_group(&self, name: &str) -> Result<WatchGroup> {
+        let mut config = self.config.write().unwrap();
+        let before = config.clone();
+        let group = WatchGroup {
+            id: groups::new_group_id(),
+            name: Self::check_group_name(&config, name, None)?,
+            members: Vec::new(),
+        };
+        config.groups.push(group.clone());
+        self.save_edit(&before, &config)?;
+        Ok(group)
+    }
+
+    /// 重命名分组
+    pub fn rename_group(&self, id: &str, name: &str) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        let before = config.clone();
+        let name = Self::check_group_name(&config, name, Some(id))?;
+        let group = config
+            .groups
+            .iter_mut()
+            .find(|g| g.id == id)
+            .ok_or_else(|| anyhow::anyhow!("分组不存在: {}", id))?;
+        group.name = name;
+        self.save_edit(&before, &config)?;
+        Ok(())
+    }
+
+    /// 删除分组（至少保留一个分组）
+    /// 只属于该分组的股票一并删除，有持仓的股票保留并移入当前分组
+    pub fn delete_group(&self, id: &str) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        let before = config.clone();
+        if config.groups.len() <= 1 {
+            anyhow::bail!("至少需要保留一个分组");
+        }
+        let index = config
+            .groups
+            .iter()
+            .position(|g| g.id == id)
+            .ok_or_else(|| anyhow::anyhow!("分组不存在: {}", id))?;
+        let removed = config.groups.remove(index);
+        let Config { stocks, groups: remaining, .. } = &mut *config;
+        stocks.retain(|s| {
+            s.holding.is_some()
+                || !removed.members.iter().any(|m| m.id == s.id)
+                || remaining.iter().any(|g| g.members.iter().any(|m| m.id == s.id))
+        });
+        groups::normalize(&mut config);
+        self.save_edit(&before, &config)?;
+        Ok(())
+    }
+
+    /// 切换当前分组
+    pub fn switch_group(&self, id: &str) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        if !config.groups.iter().any(|g| g.id == id) {
+            anyhow::bail!("分组不存在: {}", id);
+        }
+        config.active_group = id.to_string();
+        self.save(&config)?;
+        Ok(())
+    }
+
+    /// 循环切换到下一个（step 为负数时上一个）分组，返回切换后的分组
+    pub fn cycle_group(&self, step: isize) -> Result<WatchGroup> {
+        let mut config = self.config.write().unwrap();
+        let group = groups::cycle(&mut config, step).ok_or_else(|| anyhow::anyhow!("没有分组"))?;
+        self.save(&config)?;
+        Ok(group)
+    }
+
+    /// 将导入的自选股应用到当前分组；dry_run 时只返回导入结果，不修改配置
+    pub fn import_watchlist(
+        &self,
+        entries: &[watchlist::Entry],
+        format: WatchlistFormat,
+        mode: ImportMode,
+        dry_run: bool,
+    ) -> Result<WatchlistImport> {
+        let mut config = self.config.write().unwrap();
+        let mut updated = config.clone();
+        let report = watchlist::apply(&mut updated, entries, format, mode)?;
+        if !dry_run {
+            validate::check(&updated)?;
+            self.save_edit(&config, &updated)?;
+            *config = updated;
+        }
+        Ok(report)
+    }
+
+    /// 撤销上一次自选股编辑，返回撤销后的状态
+    pub fn undo(&self) -> Result<UndoState> {
+        let mut config = self.config.write().unwrap();
+        let mut history = self.history.lock().unwrap();
+        let updated = history.undo(&config).ok_or_else(|| anyhow::anyhow!("没有可撤销的操作"))?;
+        self.save(&updated)?;
+        history.commit_undo(&config);
+        *config = updated;
+        Ok(history.state())
+    }
+
+    /// 重做上一次撤销的编辑，返回重做后的状态
+    pub fn redo(&self) -> Result<UndoState> {
+        let mut config = self.config.write().unwrap();
+        let mut history = self.history.lock().unwrap();
+        let updated = history.redo(&config).ok_or_else(|| anyhow::anyhow!("没有可重做的操作"))?;
+        self.save(&updated)?;
+        history.commit_redo(&config);
+        *config = updated;
+        Ok(history.state())
+    }
+
+    pub fn undo_state(&self) -> UndoState {
+        self.history.lock().unwrap().state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AlertCondition, Operand};
+    use crate::test_support::stock;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ssm-config-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_parse_content_is_strict() {
+        let config = parse_content(include_str!("../fixtures/config/v1_baseline.json")).unwrap();
+        assert_eq!(config.stocks[1].id, "sh600036");
+        // 外部修改不做部分恢复：任何无效字段都拒绝整个文件
+        assert!(parse_content(r#"{ "window": { "opacity": "半透明" } }"#).is_err());
+        assert!(parse_content("{ \"stocks\": [").is_err());
+    }
+
+    #[test]
+    fn test_add_to_active_group() {
+        let mut config = Config::default();
+        config.stocks.clear();
+        config.groups[0].members.clear();
+        for code in ["600519", "000001"] {
+            add_to_active_group(&mut config, crate::sources::make_stock(code)).unwrap();
+        }
+        assert_eq!(config.stocks.len(), 2);
+        assert_eq!(config.groups[0].members.len(), 2);
+        assert!(add_to_active_group(&mut config, crate::sources::make_stock("sh600519")).is_err());
+        assert!(add_to_active_group(&mut config, crate::sources::make_stock("12345")).is_err());
+        assert!(validate::check(&config).is_ok());
+    }
+
+    fn alert(id: &str, stock_id: &str) -> AlertRule {
+        AlertRule {
+            id: id.to_string(),
+            stock_id: stock_id.to_string(),
+            interval: Default::default(),
+            condition: AlertCondition::Above { left: Operand::Close, right: Operand::Value { value: 1.0 } },
+            enabled: true,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_remove_from_active_group() {
+        let mut config = Config {
+            stocks: vec![
+                stock("sh600519", None),
+                stock("sz000001", Some(Holding { shares: 100.0, avg_cost: 10.0, open_date: None })),
+            ],
+            alerts: vec![alert("a1", "sh600519"), alert("a2", "sz000001")],
+            ..Config::default()
+        };
+        config.groups[0].members.clear();
+        groups::normalize(&mut config);
+
+        // 不再属于任何分组的股票连同预警规则一起删除
+        remove_from_active_group(&mut config, "sh600519").unwrap();
+        assert_eq!(config.stocks.len(), 1);
+        assert_eq!(config.alerts.len(), 1);
+        assert_eq!(config.alerts[0].stock_id, "sz000001");
+
+        // 有持仓的股票不删除
+        assert!(remove_from_active_group(&mut config, "sz000001").is_err());
+        assert_eq!(config.groups[0].members.len(), 1);
+        assert!(config.stocks[0].holding.is_some());
+        assert!(validate::check(&config).is_ok());
+    }
+
+    #[test]
+    fn test_profile_files() {
+        let dir = temp_dir("profiles");
+        assert!(profile_path(&dir, "../config").is_err());
+        assert_eq!(profile_path(&dir, "办公").unwrap(), dir.join("办公.json"));
+
+        std::fs::write(dir.join("办公.json"), "{}").unwrap();
+        std::fs::write(dir.join("家里.json"), "{}").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        std::fs::write(dir.join(".hidden.json"), "{}").unwrap();
+        // 当前方案即使还没有保存过文件也会列出
+        assert_eq!(list_profiles_in(&dir, "默认").unwrap(), ["办公", "家里", "默认"]);
+        assert_eq!(list_profiles_in(&dir, "办公").unwrap(), ["办公", "家里"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_atomic_write_replaces_file() {
+        let dir = temp_dir("atomic");
+        let path = dir.join("config.json");
+        atomic_write(&path, b"old").unwrap();
+        atomic_write(&path, b"new").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert!(!dir.join("config.json.tmp").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_data_file_sets_aside_invalid_file() {
+        let dir = temp_dir("data");
+        let path = dir.join("ledger.json");
+        assert_eq!(read_data_file::<Vec<u32>>(&path), Vec::<u32>::new());
+
+        std::fs::write(&path, "[1, 2]").unwrap();
+        assert_eq!(read_data_file::<Vec<u32>>(&path), [1, 2]);
+
+        // 损坏的文件被保留为 .bad，返回空数据
+        std::fs::write(&path, "[1, 2").unwrap();
+        assert!(read_data_file::<Vec<u32>>(&path).is_empty());
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(dir.join("ledger.json.bad")).unwrap(), "[1, 2");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_backup_rotation() {
+        let dir = temp_dir("backup");
+        let backups = dir.join("backups");
+        std::fs::create_dir_all(&backups).unwrap();
+        let path = dir.join("config.json");
+
+        std::fs::write(&path, "v0").unwrap();
+        assert!(backup_into(&backups, &path, at("2024-06-03 09:00:00"), false).unwrap());
+        // 内容未变化不重复备份
+        assert!(!backup_into(&backups, &path, at("2024-06-03 10:00:00"), false).unwrap());
+        // 间隔过短时跳过，强制备份不受限制
+        std::fs::write(&path, "v1").unwrap();
+        assert!(!backup_into(&backups, &path, at("2024-06-03 09:05:00"), false).unwrap());
+        assert!(backup_into(&backups, &path, at("2024-06-03 09:05:00"), true).unwrap());
+
+        for i in 0..MAX_BACKUPS {
+            std::fs::write(&path, format!("v{}", i + 2)).unwrap();
+            let now = at("2024-06-03 12:00:00") + chrono::Duration::hours(i as i64);
+            assert!(backup_into(&backups, &path, now, false).unwrap());
+        }
+        let list = list_backups_in(&backups).unwrap();
+        assert_eq!(list.len(), MAX_BACKUPS);
+        assert_eq!(list[0].name, "config-20240603-210000.json");
+        assert_eq!(std::fs::read_to_string(backups.join(&list[0].name)).unwrap(), format!("v{}", MAX_BACKUPS + 1));
+        assert!(!backups.join("config-20240603-090000.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
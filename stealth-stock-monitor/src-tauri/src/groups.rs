@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+
+use crate::models::{Config, GroupMember, Stock, WatchGroup};
+
+/// 默认分组（旧版本配置迁移、分组全部缺失时使用）
+pub const DEFAULT_GROUP_ID: &str = "default";
+pub const DEFAULT_GROUP_NAME: &str = "自选";
+
+/// 生成新的分组 ID
+pub fn new_group_id() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!("group{:x}", nanos)
+}
+
+/// 以股票自身的显示状态加入分组
+pub fn member_of(stock: &Stock) -> GroupMember {
+    GroupMember {
+        id: stock.id.clone(),
+        visible: stock.visible,
+    }
+}
+
+/// 维护分组与股票列表的一致性（加载、外部修改和整体更新配置后调用）
+/// - 没有分组时创建默认分组
+/// - 移除引用了不存在股票的成员和分组内的重复成员
+/// - 不属于任何分组的股票加入当前分组（如手动编辑 config.json 添加的股票）
+/// - 当前分组不存在时切换到第一个分组
+/// - 删除股票已不在自选中的预警规则（不会再触发）
+pub fn normalize(config: &mut Config) {
+    if config.groups.is_empty() {
+        config.groups.push(WatchGroup {
+            id: DEFAULT_GROUP_ID.to_string(),
+            name: DEFAULT_GROUP_NAME.to_string(),
+            members: Vec::new(),
+        });
+    }
+    if !config.groups.iter().any(|g| g.id == config.active_group) {
+        config.active_group = config.groups[0].id.clone();
+    }
+
+    let known: HashSet<&str> = config.stocks.iter().map(|s| s.id.as_str()).collect();
+    config.alerts.retain(|r| known.contains(r.stock_id.as_str()));
+    let mut grouped: HashSet<String> = HashSet::new();
+    for group in &mut config.groups {
+        let mut seen = HashSet::new();
+        group
+            .members
+            .retain(|m| known.contains(m.id.as_str()) && seen.insert(m.id.clone()));
+        grouped.extend(seen);
+    }
+
+    let orphans: Vec<GroupMember> = config
+        .stocks
+        .iter()
+        .filter(|s| !grouped.contains(&s.id))
+        .map(member_of)
+        .collect();
+    if !orphans.is_empty() {
+        let active = config.active_group.clone();
+        if let Some(group) = config.groups.iter_mut().find(|g| g.id == active) {
+            group.members.extend(orphans);
+        }
+    }
+}
+
+/// 当前分组；当前分组 ID 无效时为第一个分组
+pub fn active_group(config: &Config) -> Option<&WatchGroup> {
+    config
+        .groups
+        .iter()
+        .find(|g| g.id == config.active_group)
+        .or_else(|| config.groups.first())
+}
+
+/// 当前分组中的股票（按分组内顺序，visible 为分组内的显示状态）
+/// 没有任何分组时返回全部股票
+pub fn active_stocks(config: &Config) -> Vec<Stock> {
+    let Some(group) = active_group(config) else {
+        return config.stocks.clone();
+    };
+    group
+        .members
+        .iter()
+        .filter_map(|member| {
+            let stock = config.stocks.iter().find(|s| s.id == member.id)?;
+            Some(Stock {
+                visible: member.visible,
+                ..stock.clone()
+            })
+        })
+        .collect()
+}
+
+/// 按 step 循环切换当前分组，返回切换后的分组
+pub fn cycle(config: &mut Config, step: isize) -> Option<WatchGroup> {
+    let count = config.groups.len() as isize;
+    if count == 0 {
+        return None;
+    }
+    let current = config
+        .groups
+        .iter()
+        .position(|g| g.id == config.active_group)
+        .unwrap_or(0) as isize;
+    let next = config.groups[(current + step).rem_euclid(count) as usize].clone();
+    config.active_group = next.id.clone();
+    Some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::make_stock;
+
+    fn group(id: &str, members: &[&str]) -> WatchGroup {
+        WatchGroup {
+            id: id.to_string(),
+            name: id.to_string(),
+            members: members
+                .iter()
+                .map(|id| GroupMember {
+                    id: id.to_string(),
+                    visible: true,
+                })
+                .collect(),
+        }
+    }
+
+    fn ids(stocks: &[Stock]) -> Vec<&str> {
+        stocks.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_normalize_and_active_stocks() {
+        let mut config = Config {
+            stocks: ["600519", "000001", "300750"].into_iter().map(make_stock).collect(),
+            groups: vec![
+                group("hold", &["sz000001", "sh600000", "sz000001"]),
+                group("watch", &["sh600519"]),
+            ],
+            active_group: "missing".to_string(),
+            ..Config::default()
+        };
+        normalize(&mut config);
+
+        // 当前分组无效时切到第一个分组；未分组的股票加入当前分组；无效和重复成员被移除
+        assert_eq!(config.active_group, "hold");
+        assert_eq!(ids(&active_stocks(&config)), ["sz000001", "sz300750"]);
+
+        config.groups[1].members[0].visible = false;
+        assert_eq!(cycle(&mut config, 1).unwrap().id, "watch");
+        let watch = active_stocks(&config);
+        assert_eq!(ids(&watch), ["sh600519"]);
+        assert!(!watch[0].visible);
+        assert_eq!(cycle(&mut config, 1).unwrap().id, "hold");
+        assert_eq!(cycle(&mut config, -1).unwrap().id, "watch");
+
+        config.groups.clear();
+        normalize(&mut config);
+        assert_eq!(config.active_group, DEFAULT_GROUP_ID);
+        assert_eq!(ids(&active_stocks(&config)), ["sh600519", "sz000001", "sz300750"]);
+    }
+}
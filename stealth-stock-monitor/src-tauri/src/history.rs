@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::time::{interval, Duration};
+
+use crate::calendar;
+use crate::config::{atomic_write, get_config_dir, read_data_file, ConfigStore};
+use crate::models::{DailySnapshot, EodQuote, PriceUpdate, Stock};
+use crate::poller::QuoteSnapshot;
+use crate::portfolio;
+
+/// 收盘检查间隔
+const RECORD_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 每日收盘记录文件格式
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    version: u32,
+    days: BTreeMap<NaiveDate, DailySnapshot>,
+}
+
+fn history_file_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("history.json"))
+}
+
+/// 用收盘后获取的行情生成当日快照
+/// 只采用当日收盘之后抓取、且非旧数据的行情；没有可用行情时返回 None
+pub fn build_snapshot(
+    date: NaiveDate,
+    stocks: &[Stock],
+    quotes: &HashMap<String, PriceUpdate>,
+) -> Option<DailySnapshot> {
+    let close = date.and_time(calendar::market_close());
+    let closing: HashMap<String, PriceUpdate> = stocks
+        .iter()
+        .filter_map(|s| quotes.get(&s.id))
+        .filter(|q| !q.stale)
+        .filter(|q| {
+            calendar::china_time_from_millis(q.timestamp)
+                .is_some_and(|t| t.date() == date && t >= close)
+        })
+        .map(|q| (q.id.clone(), q.clone()))
+        .collect();
+    if closing.is_empty() {
+        return None;
+    }
+
+    let eod_quotes = stocks
+        .iter()
+        .filter_map(|s| closing.get(&s.id))
+        .map(|q| EodQuote {
+            id: q.id.clone(),
+            name: q.name.clone(),
+            close: q.price,
+            prev_close: q.prev_close,
+            change: q.change,
+            percent: q.percent,
+        })
+        .collect();
+    let timestamp = closing.values().map(|q| q.timestamp).max().unwrap_or(0);
+
+    Some(DailySnapshot {
+        date,
+        quotes: eod_quotes,
+        portfolio: portfolio::compute(stocks, &closing, date, timestamp),
+    })
+}
+
+/// 是否用新快照替换已有的当日记录
+/// 收盘价稳定前行情可能仍是集合竞价前的价格，有变化就替换；稳定后仅在覆盖更多股票时替换
+fn should_replace(existing: Option<&DailySnapshot>, snapshot: &DailySnapshot, settled: bool) -> bool {
+    match existing {
+        None => true,
+        Some(existing) if settled => existing.quotes.len() < snapshot.quotes.len(),
+        Some(existing) => existing.quotes != snapshot.quotes,
+    }
+}
+
+/// 每日收盘记录存储（config.json 同目录下的 history.json）
+pub struct HistoryStore {
+    days: RwLock<BTreeMap<NaiveDate, DailySnapshot>>,
+}
+
+impl HistoryStore {
+    /// 加载收盘记录；文件损坏时保留为 history.json.bad 并以空记录启动
+    pub fn new() -> Result<Self> {
+        let file: HistoryFile = read_data_file(&history_file_path()?);
+        Ok(Self {
+            days: RwLock::new(file.days),
+        })
+    }
+
+    fn save(days: &BTreeMap<NaiveDate, DailySnapshot>) -> Result<()> {
+        let file = HistoryFile {
+            version: 1,
+            days: days.clone(),
+        };
+        let content = serde_json::to_string_pretty(&file).context("序列化收盘记录失败")?;
+        atomic_write(&history_file_path()?, content.as_bytes()).context("写入收盘记录失败")?;
+        Ok(())
+    }
+
+    /// 写入当日快照，settled 为是否已过收盘价稳定时间（见 should_replace）
+    /// 返回是否写入
+    pub fn record(&self, snapshot: DailySnapshot, settled: bool) -> Result<bool> {
+        let mut days = self.days.write().unwrap();
+        if !should_replace(days.get(&snapshot.date), &snapshot, settled) {
+            return Ok(false);
+        }
+        let mut updated = days.clone();
+        updated.insert(snapshot.date, snapshot);
+        Self::save(&updated)?;
+        *days = updated;
+        Ok(true)
+    }
+
+    /// 返回日期区间内（含首尾）的收盘快照，按日期升序
+    pub fn range(&self, start: NaiveDate, end: NaiveDate) -> Vec<DailySnapshot> {
+        if start > end {
+            return Vec::new();
+        }
+        self.days
+            .read()
+            .unwrap()
+            .range(start..=end)
+            .map(|(_, s)| s.clone())
+            .collect()
+    }
+}
+
+/// 启动收盘记录任务：交易日收盘后，用轮询器快照中的收盘行情写入当日记录
+/// 写入后发出 daily-snapshot 事件；收盘后轮询器未运行（如隐藏时暂停）则当日不会记录
+pub fn spawn_recorder(
+    app: AppHandle,
+    config_store: Arc<ConfigStore>,
+    quotes: QuoteSnapshot,
+    history: Arc<HistoryStore>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = interval(RECORD_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+
+            let now = calendar::china_now();
+            if !calendar::is_after_close(now) {
+                continue;
+            }
+            let snapshot = {
+                let quotes = quotes.read().unwrap();
+                build_snapshot(now.date(), &config_store.get().stocks, &quotes)
+            };
+            let Some(snapshot) = snapshot else {
+                continue;
+            };
+
+            let settled = now.time() >= calendar::market_settle();
+            match history.record(snapshot.clone(), settled) {
+                Ok(true) => {
+                    log::info!("已记录 {} 收盘数据 ({} 只股票)", snapshot.date, snapshot.quotes.len());
+                    let _ = app.emit("daily-snapshot", &snapshot);
+                }
+                Ok(false) => {}
+                Err(e) => log::error!("写入收盘记录失败: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Holding;
+    use crate::test_support::{quote, stock};
+
+    fn millis(date: NaiveDate, h: u32, m: u32) -> u64 {
+        // 北京时间转 UTC 毫秒
+        (date.and_hms_opt(h, m, 0).unwrap().and_utc().timestamp_millis() - 8 * 3600 * 1000) as u64
+    }
+
+    fn quote_at(id: &str, price: f64, prev_close: f64, timestamp: u64) -> PriceUpdate {
+        PriceUpdate { timestamp, ..quote(id, price, prev_close) }
+    }
+
+    #[test]
+    fn test_build_snapshot_uses_only_post_close_quotes() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let stocks = vec![
+            stock("sh600000", Some(Holding { shares: 1000.0, avg_cost: 10.0, open_date: None })),
+            stock("sz000001", None),
+            stock("sh600519", None),
+        ];
+        let mut quotes = HashMap::new();
+        quotes.insert("sh600000".to_string(), quote_at("sh600000", 11.0, 10.5, millis(date, 15, 1)));
+        // 收盘前抓取的行情不计入
+        quotes.insert("sz000001".to_string(), quote_at("sz000001", 12.4, 12.5, millis(date, 14, 58)));
+        let mut stale = quote_at("sh600519", 1700.0, 1690.0, millis(date, 15, 2));
+        stale.stale = true;
+        quotes.insert("sh600519".to_string(), stale);
+
+        let snapshot = build_snapshot(date, &stocks, &quotes).unwrap();
+        assert_eq!(snapshot.quotes.len(), 1);
+        assert_eq!(snapshot.quotes[0].close, 11.0);
+        let portfolio = snapshot.portfolio.unwrap();
+        assert!((portfolio.daily_pnl - 500.0).abs() < 1e-9);
+
+        // 前一日的行情不能作为当日收盘
+        let next_day = date.succ_opt().unwrap();
+        assert!(build_snapshot(next_day, &stocks, &quotes).is_none());
+    }
+
+    #[test]
+    fn test_replace_snapshot_until_settled() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let stocks = vec![stock("sh600000", None), stock("sz000001", None)];
+        let snapshot_of = |quotes: &[PriceUpdate]| {
+            let quotes = quotes.iter().map(|q| (q.id.clone(), q.clone())).collect();
+            build_snapshot(date, &stocks, &quotes).unwrap()
+        };
+        // 15:00 刚过抓到的仍是集合竞价前的价格
+        let early = snapshot_of(&[quote_at("sh600000", 10.9, 10.5, millis(date, 15, 0))]);
+        let auction = snapshot_of(&[quote_at("sh600000", 11.0, 10.5, millis(date, 15, 3))]);
+        assert!(should_replace(None, &early, false));
+        assert!(should_replace(Some(&early), &auction, false));
+        assert!(!should_replace(Some(&auction), &auction, false));
+
+        // 稳定后只在覆盖更多股票时替换
+        let later = snapshot_of(&[quote_at("sh600000", 11.1, 10.5, millis(date, 15, 20))]);
+        assert!(!should_replace(Some(&auction), &later, true));
+        let more = snapshot_of(&[
+            quote_at("sh600000", 11.0, 10.5, millis(date, 15, 20)),
+            quote_at("sz000001", 12.4, 12.5, millis(date, 15, 20)),
+        ]);
+        assert!(should_replace(Some(&auction), &more, true));
+    }
+}
@@ -5,6 +5,14 @@ use crate::config::ConfigStore;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+/// 解析快捷键，空字符串表示未设置（配置修复时清除了冲突的快捷键）
+fn parse_shortcut(shortcut_str: &str) -> Result<Option<Shortcut>> {
+    if shortcut_str.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(shortcut_str.parse()?))
+}
+
 /// 全局快捷键管理器
 pub struct HotkeyManager {
     app: AppHandle,
@@ -30,6 +38,15 @@ impl HotkeyManager {
         // 注册打开设置快捷键
         self.register_open_settings(&config.shortcuts.open_settings)?;
 
+        // 注册打开详情快捷键
+        self.register_open_detail(&config.shortcuts.open_detail)?;
+
+        // 注册切换分组快捷键
+        self.register_cycle_group(&config.shortcuts.cycle_group)?;
+
+        // 注册切换配置方案快捷键
+        self.register_cycle_profile(&config.shortcuts.cycle_profile)?;
+
         log::info!("全局快捷键已注册");
         Ok(())
     }
@@ -50,7 +67,9 @@ impl HotkeyManager {
 
     fn register_toggle_visible(&self, shortcut_str: &str) -> Result<()> {
         let app = self.app.clone();
-        let shortcut: Shortcut = shortcut_str.parse()?;
+        let Some(shortcut) = parse_shortcut(shortcut_str)? else {
+            return Ok(());
+        };
         
         let last_trigger = Arc::new(Mutex::new(Instant::now()));
         
@@ -63,19 +82,8 @@ impl HotkeyManager {
                 *last = Instant::now();
 
                 log::info!("快捷键 triggered: toggle_visible");
-                if let Some(window) = app.get_webview_window("monitor") {
-                    let is_visible = window.is_visible().unwrap_or(false);
-                    log::info!("当前窗口可见性: {}", is_visible);
-                    if is_visible {
-                        let _ = window.hide();
-                        log::info!("执行 hide()");
-                    } else {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        log::info!("执行 show()");
-                    }
-                } else {
-                    log::error!("找不到 monitor 窗口");
+                if let Err(e) = crate::window::toggle_monitor(&app) {
+                    log::error!("切换窗口显隐失败: {}", e);
                 }
             }
         })?;
@@ -86,7 +94,9 @@ impl HotkeyManager {
     fn register_next_stock(&self, shortcut_str: &str) -> Result<()> {
         let config_store = self.config_store.clone();
         let app = self.app.clone();
-        let shortcut: Shortcut = shortcut_str.parse()?;
+        let Some(shortcut) = parse_shortcut(shortcut_str)? else {
+            return Ok(());
+        };
         
         let last_trigger = Arc::new(Mutex::new(Instant::now()));
 
@@ -100,7 +110,10 @@ impl HotkeyManager {
                 
                 log::info!("快捷键 triggered: next_stock");
                 let config = config_store.get();
-                let visible_stocks: Vec<_> = config.stocks.iter().filter(|s| s.visible).collect();
+                let visible_stocks: Vec<_> = crate::groups::active_stocks(&config)
+                    .into_iter()
+                    .filter(|s| s.visible && !s.overrides.pinned)
+                    .collect();
                 
                 log::info!("Visible stocks count: {}", visible_stocks.len());
 
@@ -121,7 +134,9 @@ impl HotkeyManager {
     fn register_prev_stock(&self, shortcut_str: &str) -> Result<()> {
         let config_store = self.config_store.clone();
         let app = self.app.clone();
-        let shortcut: Shortcut = shortcut_str.parse()?;
+        let Some(shortcut) = parse_shortcut(shortcut_str)? else {
+            return Ok(());
+        };
 
         let last_trigger = Arc::new(Mutex::new(Instant::now()));
 
@@ -134,7 +149,10 @@ impl HotkeyManager {
                 *last = Instant::now();
 
                 let config = config_store.get();
-                let visible_stocks: Vec<_> = config.stocks.iter().filter(|s| s.visible).collect();
+                let visible_stocks: Vec<_> = crate::groups::active_stocks(&config)
+                    .into_iter()
+                    .filter(|s| s.visible && !s.overrides.pinned)
+                    .collect();
 
                 log::info!("Visible stocks count (prev): {}", visible_stocks.len());
 
@@ -152,7 +170,9 @@ impl HotkeyManager {
     /// 注册打开设置快捷键（用于鼠标穿透后无法通过右键菜单进入设置的情况）
     fn register_open_settings(&self, shortcut_str: &str) -> Result<()> {
         let app = self.app.clone();
-        let shortcut: Shortcut = shortcut_str.parse()?;
+        let Some(shortcut) = parse_shortcut(shortcut_str)? else {
+            return Ok(());
+        };
 
         let last_trigger = Arc::new(Mutex::new(Instant::now()));
 
@@ -177,4 +197,91 @@ impl HotkeyManager {
 
         Ok(())
     }
+
+    /// 注册打开详情快捷键：悬浮窗当前首行的股票由前端决定，这里只通知前端
+    fn register_open_detail(&self, shortcut_str: &str) -> Result<()> {
+        let app = self.app.clone();
+        let Some(shortcut) = parse_shortcut(shortcut_str)? else {
+            return Ok(());
+        };
+
+        let last_trigger = Arc::new(Mutex::new(Instant::now()));
+
+        self.app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                let mut last = last_trigger.lock().unwrap();
+                if last.elapsed() < Duration::from_millis(300) {
+                    return;
+                }
+                *last = Instant::now();
+
+                log::info!("快捷键 triggered: open_detail");
+                let _ = app.emit("hotkey-open-detail", ());
+            }
+        })?;
+
+        Ok(())
+    }
+
+    /// 注册切换分组快捷键：切换到下一个自选股分组并通知前端
+    fn register_cycle_group(&self, shortcut_str: &str) -> Result<()> {
+        let config_store = self.config_store.clone();
+        let app = self.app.clone();
+        let Some(shortcut) = parse_shortcut(shortcut_str)? else {
+            return Ok(());
+        };
+
+        let last_trigger = Arc::new(Mutex::new(Instant::now()));
+
+        self.app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                let mut last = last_trigger.lock().unwrap();
+                if last.elapsed() < Duration::from_millis(300) {
+                    return;
+                }
+                *last = Instant::now();
+
+                log::info!("快捷键 triggered: cycle_group");
+                match config_store.cycle_group(1) {
+                    Ok(group) => {
+                        log::info!("已切换到分组: {}", group.name);
+                        let _ = app.emit("config-changed", &config_store.get());
+                        let _ = app.emit("group-switched", &group);
+                    }
+                    Err(e) => log::error!("切换分组失败: {}", e),
+                }
+            }
+        })?;
+
+        Ok(())
+    }
+
+    /// 注册切换配置方案快捷键
+    /// 切换后会重新注册快捷键，不能在快捷键回调中直接执行，放到异步任务中
+    fn register_cycle_profile(&self, shortcut_str: &str) -> Result<()> {
+        let app = self.app.clone();
+        let Some(shortcut) = parse_shortcut(shortcut_str)? else {
+            return Ok(());
+        };
+
+        let last_trigger = Arc::new(Mutex::new(Instant::now()));
+
+        self.app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                let mut last = last_trigger.lock().unwrap();
+                if last.elapsed() < Duration::from_millis(300) {
+                    return;
+                }
+                *last = Instant::now();
+
+                log::info!("快捷键 triggered: cycle_profile");
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    crate::cycle_profile(&app);
+                });
+            }
+        })?;
+
+        Ok(())
+    }
 }
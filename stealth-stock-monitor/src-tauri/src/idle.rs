@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+/// 查询系统级用户空闲时长（距上次键盘/鼠标输入的时间）
+/// 锁屏期间不会产生输入，因此锁屏超过阈值同样视为空闲
+/// 无法获取时返回 None，调用方应按"非空闲"处理
+/// macOS/Linux 需要启动外部进程，以异步方式等待，不阻塞轮询所在的运行时线程
+pub async fn idle_duration() -> Option<Duration> {
+    platform::idle_duration().await
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::time::Duration;
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    pub async fn idle_duration() -> Option<Duration> {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        // SAFETY: info 为已初始化的 LASTINPUTINFO，cbSize 已正确设置
+        if unsafe { GetLastInputInfo(&mut info) } == 0 {
+            return None;
+        }
+        let now = unsafe { GetTickCount() };
+        Some(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::time::Duration;
+
+    /// 通过 ioreg 读取 IOHIDSystem 的 HIDIdleTime（纳秒）
+    pub async fn idle_duration() -> Option<Duration> {
+        let output = tokio::process::Command::new("ioreg")
+            .args(["-c", "IOHIDSystem", "-d", "4"])
+            .output()
+            .await
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let line = text.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+        let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+        Some(Duration::from_nanos(nanos))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use std::time::Duration;
+
+    /// 依赖 xprintidle（X11），未安装或在 Wayland 下返回 None
+    pub async fn idle_duration() -> Option<Duration> {
+        let output = tokio::process::Command::new("xprintidle").output().await.ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let millis: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+        Some(Duration::from_millis(millis))
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+use anyhow::Result;
+
+use crate::calendar;
+use crate::intraday::IntradayCache;
+use crate::kline;
+use crate::models::{Adjust, BarInterval, IndicatorInterval, IndicatorSpec, KlinePeriod};
+use crate::tickstore::TickStore;
+
+/// 指标计算的输入 K 线
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    /// 时间标签（日期或 "HH:MM"）
+    pub label: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+/// 指标的各条输出线
+pub type Lines = BTreeMap<String, Vec<Option<f64>>>;
+
+/// 简单移动平均，前 period - 1 根为 None
+fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 {
+        return result;
+    }
+    let mut sum = 0.0;
+    for (i, v) in values.iter().enumerate() {
+        sum += v;
+        if i >= period {
+            sum -= values[i - period];
+        }
+        if i + 1 >= period {
+            result[i] = Some(sum / period as f64);
+        }
+    }
+    result
+}
+
+/// 指数移动平均，以第一根为初值（与通达信等国内软件一致）
+fn ema(values: &[f64], period: usize) -> Vec<f64> {
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut result = Vec::with_capacity(values.len());
+    for (i, v) in values.iter().enumerate() {
+        let value = if i == 0 { *v } else { alpha * v + (1.0 - alpha) * result[i - 1] };
+        result.push(value);
+    }
+    result
+}
+
+/// 国内软件的 SMA(X, N, M)：Y = (M × X + (N - M) × Y') / N，以 initial 为初值
+fn weighted_sma(values: &[f64], n: usize, m: usize, initial: f64) -> Vec<f64> {
+    let (n, m) = (n.max(1) as f64, m as f64);
+    let mut prev = initial;
+    values
+        .iter()
+        .map(|v| {
+            prev = (m * v + (n - m) * prev) / n;
+            prev
+        })
+        .collect()
+}
+
+fn macd(closes: &[f64], fast: usize, slow: usize, signal: usize) -> Lines {
+    let fast = ema(closes, fast);
+    let slow = ema(closes, slow);
+    let dif: Vec<f64> = fast.iter().zip(&slow).map(|(f, s)| f - s).collect();
+    let dea = ema(&dif, signal);
+    let bar: Vec<f64> = dif.iter().zip(&dea).map(|(d, e)| 2.0 * (d - e)).collect();
+    BTreeMap::from([
+        ("dif".to_string(), dif.into_iter().map(Some).collect()),
+        ("dea".to_string(), dea.into_iter().map(Some).collect()),
+        ("macd".to_string(), bar.into_iter().map(Some).collect()),
+    ])
+}
+
+fn rsi(closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    if closes.len() < 2 {
+        return vec![None; closes.len()];
+    }
+    let diffs: Vec<f64> = closes.windows(2).map(|w| w[1] - w[0]).collect();
+    let gains: Vec<f64> = diffs.iter().map(|d| d.max(0.0)).collect();
+    let moves: Vec<f64> = diffs.iter().map(|d| d.abs()).collect();
+    let avg_gain = weighted_sma(&gains, period, 1, gains[0]);
+    let avg_move = weighted_sma(&moves, period, 1, moves[0]);
+
+    std::iter::once(None)
+        .chain(avg_gain.iter().zip(&avg_move).map(|(g, m)| {
+            // 区间内价格完全不变时记为 50
+            Some(if *m > 0.0 { g / m * 100.0 } else { 50.0 })
+        }))
+        .collect()
+}
+
+fn kdj(candles: &[Candle], n: usize, m1: usize, m2: usize) -> Lines {
+    let n = n.max(1);
+    let rsv: Vec<f64> = (0..candles.len())
+        .map(|i| {
+            let window = &candles[i.saturating_sub(n - 1)..=i];
+            let high = window.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+            let low = window.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+            if high > low {
+                (candles[i].close - low) / (high - low) * 100.0
+            } else {
+                50.0
+            }
+        })
+        .collect();
+    let k = weighted_sma(&rsv, m1, 1, 50.0);
+    let d = weighted_sma(&k, m2, 1, 50.0);
+    let j: Vec<f64> = k.iter().zip(&d).map(|(k, d)| 3.0 * k - 2.0 * d).collect();
+    BTreeMap::from([
+        ("k".to_string(), k.into_iter().map(Some).collect()),
+        ("d".to_string(), d.into_iter().map(Some).collect()),
+        ("j".to_string(), j.into_iter().map(Some).collect()),
+    ])
+}
+
+fn boll(closes: &[f64], period: usize, k: f64) -> Lines {
+    let mid = sma(closes, period);
+    let mut upper = vec![None; closes.len()];
+    let mut lower = vec![None; closes.len()];
+    for (i, m) in mid.iter().enumerate() {
+        let Some(m) = m else { continue };
+        let window = &closes[i + 1 - period..=i];
+        let variance = window.iter().map(|c| (c - m).powi(2)).sum::<f64>() / period as f64;
+        let std = variance.sqrt();
+        upper[i] = Some(m + k * std);
+        lower[i] = Some(m - k * std);
+    }
+    BTreeMap::from([
+        ("mid".to_string(), mid),
+        ("upper".to_string(), upper),
+        ("lower".to_string(), lower),
+    ])
+}
+
+/// 从第一根起累计的成交量加权均价，成交量为 0 时为 None
+fn vwap(candles: &[Candle]) -> Vec<Option<f64>> {
+    let mut amount = 0.0;
+    let mut volume = 0.0;
+    candles
+        .iter()
+        .map(|c| {
+            amount += (c.high + c.low + c.close) / 3.0 * c.volume;
+            volume += c.volume;
+            (volume > 0.0).then(|| amount / volume)
+        })
+        .collect()
+}
+
+/// 计算一个指标
+pub fn compute(spec: &IndicatorSpec, candles: &[Candle]) -> Lines {
+    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+    match *spec {
+        IndicatorSpec::Ma { period } => BTreeMap::from([("ma".to_string(), sma(&closes, period))]),
+        IndicatorSpec::Ema { period } => BTreeMap::from([(
+            "ema".to_string(),
+            ema(&closes, period).into_iter().map(Some).collect(),
+        )]),
+        IndicatorSpec::Macd { fast, slow, signal } => macd(&closes, fast, slow, signal),
+        IndicatorSpec::Rsi { period } => BTreeMap::from([("rsi".to_string(), rsi(&closes, period))]),
+        IndicatorSpec::Kdj { n, m1, m2 } => kdj(candles, n, m1, m2),
+        IndicatorSpec::Boll { period, k } => boll(&closes, period, k),
+        IndicatorSpec::Vwap => BTreeMap::from([("vwap".to_string(), vwap(candles))]),
+    }
+}
+
+/// 校验指标参数
+/// 校验指标参数；本地分钟 K 线没有成交量，不支持 VWAP
+pub fn validate(spec: &IndicatorSpec, interval: IndicatorInterval) -> Result<()> {
+    if *spec == IndicatorSpec::Vwap && !has_volume(interval) {
+        anyhow::bail!("{:?} 周期没有成交量数据，无法计算 VWAP", interval);
+    }
+    let ok = match *spec {
+        IndicatorSpec::Ma { period } | IndicatorSpec::Ema { period } | IndicatorSpec::Rsi { period } => period > 0,
+        IndicatorSpec::Macd { fast, slow, signal } => fast > 0 && slow > fast && signal > 0,
+        IndicatorSpec::Kdj { n, m1, m2 } => n > 0 && m1 > 0 && m2 > 0,
+        IndicatorSpec::Boll { period, k } => period > 0 && k > 0.0,
+        IndicatorSpec::Vwap => true,
+    };
+    if !ok {
+        anyhow::bail!("指标参数无效: {:?}", spec);
+    }
+    Ok(())
+}
+
+/// 该周期的 K 线是否带有成交量（本地记录的分钟 K 线只有价格）
+fn has_volume(interval: IndicatorInterval) -> bool {
+    !matches!(
+        interval,
+        IndicatorInterval::Min1
+            | IndicatorInterval::Min5
+            | IndicatorInterval::Min15
+            | IndicatorInterval::Min30
+            | IndicatorInterval::Min60
+    )
+}
+
+/// 按周期加载指标计算所需的 K 线
+/// - intraday: 当日分时（每分钟一根）
+/// - 1m ~ 60m: 本地存储中当日的分钟 K 线（需开启行情存储）
+/// - day / week / month: 前复权历史 K 线
+pub async fn load_candles(
+    tick_store: &TickStore,
+    intraday: &IntradayCache,
+    source_names: &[String],
+    id: &str,
+    interval: IndicatorInterval,
+) -> Result<Vec<Candle>> {
+    let bar_interval = match interval {
+        IndicatorInterval::Intraday => {
+            let data = intraday.get(id, source_names).await?;
+            return Ok(data
+                .points
+                .into_iter()
+                .map(|p| Candle {
+                    label: p.time,
+                    open: p.price,
+                    high: p.price,
+                    low: p.price,
+                    close: p.price,
+                    volume: p.volume,
+                })
+                .collect());
+        }
+        IndicatorInterval::Day | IndicatorInterval::Week | IndicatorInterval::Month => {
+            let period = match interval {
+                IndicatorInterval::Week => KlinePeriod::Week,
+                IndicatorInterval::Month => KlinePeriod::Month,
+                _ => KlinePeriod::Day,
+            };
+            let klines = kline::get_klines(tick_store, source_names, id, period, Adjust::Forward, None, None).await?;
+            return Ok(klines
+                .into_iter()
+                .map(|k| Candle {
+                    label: k.date.to_string(),
+                    open: k.open,
+                    high: k.high,
+                    low: k.low,
+                    close: k.close,
+                    volume: k.volume,
+                })
+                .collect());
+        }
+        IndicatorInterval::Min1 => BarInterval::Min1,
+        IndicatorInterval::Min5 => BarInterval::Min5,
+        IndicatorInterval::Min15 => BarInterval::Min15,
+        IndicatorInterval::Min30 => BarInterval::Min30,
+        IndicatorInterval::Min60 => BarInterval::Min60,
+    };
+
+    // 只取当日的分钟 K 线，不加载历史上存储的全部数据
+    let day_start = calendar::china_day_start_millis(calendar::china_now().date());
+    let bars = tick_store.bars(id, bar_interval, day_start, u64::MAX)?;
+    Ok(bars
+        .into_iter()
+        .map(|b| Candle {
+            label: calendar::china_time_from_millis(b.timestamp)
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+            open: b.open,
+            high: b.high,
+            low: b.low,
+            close: b.close,
+            volume: 0.0,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: &[f64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Candle {
+                label: i.to_string(),
+                open: *c,
+                high: c + 1.0,
+                low: c - 1.0,
+                close: *c,
+                volume: 100.0,
+            })
+            .collect()
+    }
+
+    fn approx(a: Option<f64>, b: f64) -> bool {
+        a.is_some_and(|a| (a - b).abs() < 1e-9)
+    }
+
+    #[test]
+    fn test_ma_and_ema() {
+        let data = candles(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let ma = &compute(&IndicatorSpec::Ma { period: 3 }, &data)["ma"];
+        assert_eq!(ma[1], None);
+        assert!(approx(ma[2], 2.0));
+        assert!(approx(ma[4], 4.0));
+
+        // alpha = 2 / 4 = 0.5: 1, 1.5, 2.25
+        let ema = &compute(&IndicatorSpec::Ema { period: 3 }, &data)["ema"];
+        assert!(approx(ema[2], 2.25));
+    }
+
+    #[test]
+    fn test_macd_of_constant_series_is_zero() {
+        let data = candles(&[10.0; 40]);
+        let lines = compute(&IndicatorSpec::Macd { fast: 12, slow: 26, signal: 9 }, &data);
+        assert!(approx(lines["dif"][39], 0.0));
+        assert!(approx(lines["macd"][39], 0.0));
+    }
+
+    #[test]
+    fn test_rsi_bounds() {
+        let rising: Vec<f64> = (1..=20).map(f64::from).collect();
+        let rsi = &compute(&IndicatorSpec::Rsi { period: 6 }, &candles(&rising))["rsi"];
+        assert_eq!(rsi[0], None);
+        assert!(approx(rsi[19], 100.0));
+
+        let falling: Vec<f64> = (1..=20).rev().map(f64::from).collect();
+        let rsi = &compute(&IndicatorSpec::Rsi { period: 6 }, &candles(&falling))["rsi"];
+        assert!(approx(rsi[19], 0.0));
+    }
+
+    #[test]
+    fn test_kdj_boll_vwap() {
+        let rising: Vec<f64> = (1..=30).map(f64::from).collect();
+        let data = candles(&rising);
+
+        let kdj = compute(&IndicatorSpec::Kdj { n: 9, m1: 3, m2: 3 }, &data);
+        let k = kdj["k"][29].unwrap();
+        assert!(k > 80.0 && k <= 100.0);
+
+        let boll = compute(&IndicatorSpec::Boll { period: 20, k: 2.0 }, &candles(&[5.0; 25]));
+        assert_eq!(boll["upper"][18], None);
+        assert!(approx(boll["upper"][24], 5.0));
+        assert!(approx(boll["lower"][24], 5.0));
+
+        // 等量成交时 VWAP 为典型价格的平均
+        let vwap = &compute(&IndicatorSpec::Vwap, &candles(&[1.0, 3.0]))["vwap"];
+        assert!(approx(vwap[1], 2.0));
+        // 本地分钟 K 线没有成交量，不支持 VWAP
+        assert!(validate(&IndicatorSpec::Vwap, IndicatorInterval::Intraday).is_ok());
+        assert!(validate(&IndicatorSpec::Vwap, IndicatorInterval::Min5).is_err());
+        assert!(validate(&IndicatorSpec::Ma { period: 5 }, IndicatorInterval::Min5).is_ok());
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use anyhow::Result;
+use chrono::NaiveDateTime;
+
+use crate::calendar;
+use crate::models::IntradayData;
+use crate::sources;
+
+/// 交易时段内分时缓存的有效期（秒）
+const TRADING_TTL_SECS: i64 = 60;
+
+/// 缓存是否仍可使用：同一天、同一交易时段内有效；交易时段中超过 TRADING_TTL_SECS 需重新获取
+fn is_fresh(fetched_at: NaiveDateTime, now: NaiveDateTime) -> bool {
+    if fetched_at.date() != now.date() {
+        return false;
+    }
+    let phase = calendar::market_phase(now);
+    if calendar::market_phase(fetched_at) != phase {
+        return false;
+    }
+    !phase.is_trading() || (now - fetched_at).num_seconds() < TRADING_TTL_SECS
+}
+
+/// 分时数据缓存（按股票、按自然日）
+/// 收盘后或非交易日当天只请求一次，交易时段内最多每分钟请求一次
+pub struct IntradayCache {
+    entries: Mutex<HashMap<String, (NaiveDateTime, IntradayData)>>,
+}
+
+impl IntradayCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取分时数据，按 source_names 顺序尝试支持分时的数据源
+    pub async fn get(&self, id: &str, source_names: &[String]) -> Result<IntradayData> {
+        let now = calendar::china_now();
+        if let Some((fetched_at, data)) = self.entries.lock().unwrap().get(id) {
+            if is_fresh(*fetched_at, now) {
+                return Ok(data.clone());
+            }
+        }
+
+        let (market, code) = sources::parse_code(id);
+        let mut last_error = anyhow::anyhow!("没有可用的数据源");
+        for source in source_names.iter().filter_map(|name| sources::create_source(name)) {
+            match source.fetch_intraday(&market, &code).await {
+                Ok(data) => {
+                    self.entries
+                        .lock()
+                        .unwrap()
+                        .insert(id.to_string(), (now, data.clone()));
+                    return Ok(data);
+                }
+                Err(e) => {
+                    log::debug!("数据源 {} 获取分时失败: {}", source.name(), e);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+impl Default for IntradayCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_cache_freshness() {
+        // 交易时段内一分钟有效
+        assert!(is_fresh(at("2024-06-03 10:00:00"), at("2024-06-03 10:00:30")));
+        assert!(!is_fresh(at("2024-06-03 10:00:00"), at("2024-06-03 10:01:30")));
+        // 午休、收盘后在同一时段内一直有效，跨时段失效
+        assert!(is_fresh(at("2024-06-03 11:45:00"), at("2024-06-03 12:50:00")));
+        assert!(!is_fresh(at("2024-06-03 14:59:00"), at("2024-06-03 15:00:10")));
+        assert!(is_fresh(at("2024-06-03 15:10:00"), at("2024-06-03 22:00:00")));
+        // 跨日失效
+        assert!(!is_fresh(at("2024-06-03 22:00:00"), at("2024-06-04 08:00:00")));
+    }
+}
@@ -0,0 +1,162 @@
+use std::future::Future;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::models::{Adjust, Kline, KlinePeriod};
+use crate::sources;
+use crate::tickstore::TickStore;
+
+/// 判断缓存是否需要整体重建
+/// 前复权价格在每次除权除息后整体变化：若重新获取的锚点 K 线收盘价与缓存不一致，说明历史数据已失效
+fn needs_rebuild(anchor: &Kline, fetched: &[Kline]) -> bool {
+    fetched
+        .iter()
+        .find(|k| k.date == anchor.date)
+        .is_none_or(|k| (k.close - anchor.close).abs() > 1e-4)
+}
+
+/// 按 source_names 顺序尝试支持历史 K 线的数据源
+async fn fetch(
+    source_names: &[String],
+    market: &str,
+    code: &str,
+    period: KlinePeriod,
+    adjust: Adjust,
+    since: Option<NaiveDate>,
+) -> Result<Vec<Kline>> {
+    let mut last_error = anyhow::anyhow!("没有可用的数据源");
+    for source in source_names.iter().filter_map(|name| sources::create_source(name)) {
+        match source.fetch_kline(market, code, period, adjust, since).await {
+            Ok(klines) => return Ok(klines),
+            Err(e) => {
+                log::debug!("数据源 {} 获取K线失败: {}", source.name(), e);
+                last_error = e;
+            }
+        }
+    }
+    // 配置中的数据源都不支持时退回东方财富
+    if !source_names.iter().any(|n| n == "eastmoney") {
+        if let Some(source) = sources::create_source("eastmoney") {
+            return source.fetch_kline(market, code, period, adjust, since).await;
+        }
+    }
+    Err(last_error)
+}
+
+/// 获取历史 K 线：先从缓存中最后一根已完成的 K 线起增量补全，再返回 [start, end] 区间的数据
+/// 首次请求或前复权数据失效时整体重新获取；网络失败且已有缓存时返回缓存
+pub async fn get_klines(
+    store: &TickStore,
+    source_names: &[String],
+    id: &str,
+    period: KlinePeriod,
+    adjust: Adjust,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> Result<Vec<Kline>> {
+    let (market, code) = sources::parse_code(id);
+    let stock_id = sources::make_stock_id(&market, &code);
+    let fetch_since = |since| fetch(source_names, &market, &code, period, adjust, since);
+    get_klines_with(store, &stock_id, period, adjust, start, end, fetch_since).await
+}
+
+/// get_klines 的实现，fetch_since(since) 获取 since（含）之后的 K 线，为空时获取全部
+async fn get_klines_with<F, Fut>(
+    store: &TickStore,
+    stock_id: &str,
+    period: KlinePeriod,
+    adjust: Adjust,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    fetch_since: F,
+) -> Result<Vec<Kline>>
+where
+    F: Fn(Option<NaiveDate>) -> Fut,
+    Fut: Future<Output = Result<Vec<Kline>>>,
+{
+    // 最新一根可能尚未走完（当日/本周/本月），以倒数第二根作为增量起点和校验锚点
+    let latest = store.latest_klines(stock_id, period, adjust, 2)?;
+    let anchor = latest.get(1).cloned();
+
+    let topped_up = match fetch_since(anchor.as_ref().map(|k| k.date)).await {
+        Ok(fetched) => match &anchor {
+            Some(anchor) if needs_rebuild(anchor, &fetched) => {
+                log::info!("{} K线复权数据已变化，重新获取全部历史", stock_id);
+                let full = fetch_since(None).await?;
+                store.clear_klines(stock_id, period, adjust)?;
+                store.upsert_klines(stock_id, period, adjust, &full)
+            }
+            _ => store.upsert_klines(stock_id, period, adjust, &fetched),
+        },
+        Err(e) if !latest.is_empty() => {
+            log::warn!("{} K线补全失败，使用缓存: {}", stock_id, e);
+            Ok(())
+        }
+        Err(e) => Err(e),
+    };
+    topped_up?;
+
+    store.klines(stock_id, period, adjust, start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kline(d: &str, close: f64) -> Kline {
+        Kline {
+            date: d.parse().unwrap(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+            amount: 0.0,
+        }
+    }
+
+    /// 周线：本周未走完的一根以最近交易日为日期，每天补全后日期都会变化
+    #[tokio::test]
+    async fn test_get_klines_tops_up_cache() {
+        let store = TickStore::open_in_memory();
+        let (period, adjust) = (KlinePeriod::Week, Adjust::Forward);
+        let first = vec![kline("2024-05-24", 9.8), kline("2024-05-31", 10.0), kline("2024-06-03", 10.1)];
+        let all = get_klines_with(&store, "sh600000", period, adjust, None, None, |since| {
+            assert_eq!(since, None);
+            let first = first.clone();
+            async move { Ok(first) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(all.len(), 3);
+
+        // 次日补全：从锚点 2024-05-31 起获取，本周的一根日期变为 2024-06-04
+        let topped_up = get_klines_with(&store, "sh600000", period, adjust, None, None, |since| {
+            assert_eq!(since, Some("2024-05-31".parse().unwrap()));
+            async { Ok(vec![kline("2024-05-31", 10.0), kline("2024-06-04", 10.3)]) }
+        })
+        .await
+        .unwrap();
+        let dates: Vec<String> = topped_up.iter().map(|k| k.date.to_string()).collect();
+        assert_eq!(dates, ["2024-05-24", "2024-05-31", "2024-06-04"]);
+
+        // 网络失败时返回缓存，并按区间过滤
+        let cached = get_klines_with(&store, "sh600000", period, adjust, "2024-05-31".parse().ok(), None, |_| async {
+            anyhow::bail!("offline")
+        })
+        .await
+        .unwrap();
+        assert_eq!(cached.len(), 2);
+    }
+
+    #[test]
+    fn test_needs_rebuild_after_ex_dividend() {
+        let anchor = kline("2024-05-31", 10.0);
+        assert!(!needs_rebuild(&anchor, &[kline("2024-05-31", 10.0), kline("2024-06-03", 10.2)]));
+        // 除息后前复权价格下调
+        assert!(needs_rebuild(&anchor, &[kline("2024-05-31", 9.7), kline("2024-06-03", 9.9)]));
+        // 返回数据中缺少锚点
+        assert!(needs_rebuild(&anchor, &[kline("2024-06-03", 10.2)]));
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{atomic_write, get_config_dir, read_data_file};
+use crate::models::{
+    CostMethod, DerivedPosition, FeeSchedule, NewTransaction, Transaction, TransactionKind,
+};
+use crate::sources;
+
+/// 持仓数量的比较精度（小于此值视为清仓）
+const SHARE_EPSILON: f64 = 1e-6;
+
+/// 交易流水文件格式
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LedgerFile {
+    version: u32,
+    transactions: Vec<Transaction>,
+}
+
+fn ledger_file_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("ledger.json"))
+}
+
+/// 四舍五入到分
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// 按费用标准计算一笔买卖的 (佣金, 印花税, 过户费)
+/// 佣金有最低收费；印花税仅卖出收取；分红和送转不产生费用
+pub fn compute_fees(kind: TransactionKind, shares: f64, price: f64, fees: &FeeSchedule) -> (f64, f64, f64) {
+    if !matches!(kind, TransactionKind::Buy | TransactionKind::Sell) {
+        return (0.0, 0.0, 0.0);
+    }
+    let turnover = shares * price;
+    let commission = round_cents((turnover * fees.commission_rate).max(fees.min_commission));
+    let stamp_duty = if kind == TransactionKind::Sell {
+        round_cents(turnover * fees.stamp_duty_rate)
+    } else {
+        0.0
+    };
+    let transfer_fee = round_cents(turnover * fees.transfer_fee_rate);
+    (commission, stamp_duty, transfer_fee)
+}
+
+/// 一个买入批次
+struct Lot {
+    shares: f64,
+    unit_cost: f64,
+    date: NaiveDate,
+}
+
+/// 单只股票的核算状态
+struct Account {
+    position: DerivedPosition,
+    lots: VecDeque<Lot>,
+}
+
+impl Account {
+    fn new(stock_id: &str) -> Self {
+        Self {
+            position: DerivedPosition {
+                stock_id: stock_id.to_string(),
+                ..Default::default()
+            },
+            lots: VecDeque::new(),
+        }
+    }
+
+    fn shares(&self) -> f64 {
+        self.lots.iter().map(|l| l.shares).sum()
+    }
+
+    fn apply(&mut self, tx: &Transaction, method: CostMethod) -> Result<()> {
+        let fees = tx.commission + tx.stamp_duty + tx.transfer_fee;
+        self.position.total_fees += fees;
+
+        match tx.kind {
+            TransactionKind::Buy => {
+                if tx.shares <= 0.0 {
+                    anyhow::bail!("{} 买入数量必须大于 0", tx.date);
+                }
+                let cost = tx.shares * tx.price + fees;
+                match (method, self.lots.front_mut()) {
+                    // 加权平均：所有持仓合并为一个批次，保留最早的建仓日期
+                    (CostMethod::WeightedAverage, Some(lot)) => {
+                        let total = lot.shares * lot.unit_cost + cost;
+                        lot.shares += tx.shares;
+                        lot.unit_cost = total / lot.shares;
+                    }
+                    _ => self.lots.push_back(Lot {
+                        shares: tx.shares,
+                        unit_cost: cost / tx.shares,
+                        date: tx.date,
+                    }),
+                }
+            }
+            TransactionKind::Sell => {
+                let held = self.shares();
+                if tx.shares <= 0.0 {
+                    anyhow::bail!("{} 卖出数量必须大于 0", tx.date);
+                }
+                if tx.shares > held + SHARE_EPSILON {
+                    anyhow::bail!(
+                        "{} {} 卖出数量 {} 超过当时持仓 {}",
+                        tx.date,
+                        tx.stock_id,
+                        tx.shares,
+                        held
+                    );
+                }
+                let mut remaining = tx.shares;
+                let mut cost_removed = 0.0;
+                while remaining > SHARE_EPSILON {
+                    let Some(lot) = self.lots.front_mut() else { break };
+                    let take = remaining.min(lot.shares);
+                    cost_removed += take * lot.unit_cost;
+                    lot.shares -= take;
+                    remaining -= take;
+                    if lot.shares <= SHARE_EPSILON {
+                        self.lots.pop_front();
+                    }
+                }
+                let proceeds = tx.shares * tx.price - fees;
+                self.position.realized_pnl += proceeds - cost_removed;
+            }
+            TransactionKind::Dividend => {
+                self.position.realized_pnl += tx.amount - fees;
+            }
+            TransactionKind::Split => {
+                if tx.ratio <= 0.0 {
+                    anyhow::bail!("{} 送转比例必须大于 0", tx.date);
+                }
+                for lot in self.lots.iter_mut() {
+                    lot.shares *= tx.ratio;
+                    lot.unit_cost /= tx.ratio;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> DerivedPosition {
+        let shares = self.shares();
+        let cost_basis: f64 = self.lots.iter().map(|l| l.shares * l.unit_cost).sum();
+        self.position.shares = shares;
+        self.position.cost_basis = cost_basis;
+        self.position.avg_cost = if shares > SHARE_EPSILON { cost_basis / shares } else { 0.0 };
+        self.position.open_date = self.lots.front().map(|l| l.date);
+        self.position
+    }
+}
+
+/// 按日期顺序回放交易，推导每只股票的持仓、成本和已实现盈亏
+/// 同一天的交易按录入顺序处理；卖出超过持仓时返回错误
+pub fn derive_positions(transactions: &[Transaction], method: CostMethod) -> Result<Vec<DerivedPosition>> {
+    let mut ordered: Vec<&Transaction> = transactions.iter().collect();
+    ordered.sort_by_key(|tx| tx.date);
+
+    let mut accounts: Vec<Account> = Vec::new();
+    for tx in ordered {
+        let index = match accounts.iter().position(|a| a.position.stock_id == tx.stock_id) {
+            Some(i) => i,
+            None => {
+                accounts.push(Account::new(&tx.stock_id));
+                accounts.len() - 1
+            }
+        };
+        accounts[index].apply(tx, method)?;
+    }
+
+    Ok(accounts.into_iter().map(Account::finish).collect())
+}
+
+/// 由输入构建交易记录：规范化股票 ID，未填写的费用按费用标准计算
+fn build_transaction(id: String, input: NewTransaction, fees: &FeeSchedule) -> Transaction {
+    let (market, code) = sources::parse_code(&input.code);
+    let (commission, stamp_duty, transfer_fee) =
+        compute_fees(input.kind, input.shares, input.price, fees);
+    Transaction {
+        id,
+        stock_id: sources::make_stock_id(&market, &code),
+        kind: input.kind,
+        date: input.date,
+        shares: input.shares,
+        price: input.price,
+        amount: input.amount,
+        ratio: input.ratio,
+        commission: input.commission.unwrap_or(commission),
+        stamp_duty: input.stamp_duty.unwrap_or(stamp_duty),
+        transfer_fee: input.transfer_fee.unwrap_or(transfer_fee),
+        note: input.note,
+        source_ref: input.source_ref,
+    }
+}
+
+/// 交易流水存储（config.json 同目录下的 ledger.json）
+pub struct LedgerStore {
+    transactions: RwLock<Vec<Transaction>>,
+}
+
+impl LedgerStore {
+    /// 加载交易流水；文件损坏时保留为 ledger.json.bad 并以空流水启动
+    pub fn new() -> Result<Self> {
+        let file: LedgerFile = read_data_file(&ledger_file_path()?);
+        Ok(Self {
+            transactions: RwLock::new(file.transactions),
+        })
+    }
+
+    fn save(transactions: &[Transaction]) -> Result<()> {
+        let file = LedgerFile {
+            version: 1,
+            transactions: transactions.to_vec(),
+        };
+        let content = serde_json::to_string_pretty(&file).context("序列化交易流水失败")?;
+        atomic_write(&ledger_file_path()?, content.as_bytes()).context("写入交易流水失败")?;
+        Ok(())
+    }
+
+    /// 列出交易记录（按日期排序），可按股票过滤
+    pub fn list(&self, stock_id: Option<&str>) -> Vec<Transaction> {
+        let mut list: Vec<Transaction> = self
+            .transactions
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|tx| stock_id.is_none_or(|id| tx.stock_id == id))
+            .cloned()
+            .collect();
+        list.sort_by_key(|tx| tx.date);
+        list
+    }
+
+    /// 新增交易；费用未填写时按费用标准计算，导致持仓为负的记录会被拒绝
+    pub fn add(&self, input: NewTransaction, fees: &FeeSchedule, method: CostMethod) -> Result<Transaction> {
+        let mut added = self.add_all(vec![input], fees, method)?;
+        Ok(added.remove(0))
+    }
+
+    /// 批量新增交易（如券商对账单导入），全部通过校验后才一次性写入
+    pub fn add_all(
+        &self,
+        inputs: Vec<NewTransaction>,
+        fees: &FeeSchedule,
+        method: CostMethod,
+    ) -> Result<Vec<Transaction>> {
+        let mut transactions = self.transactions.write().unwrap();
+        let mut updated = transactions.clone();
+        let mut added = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let tx = build_transaction(Self::next_id(&updated), input, fees);
+            updated.push(tx.clone());
+            added.push(tx);
+        }
+
+        derive_positions(&updated, method)?;
+        Self::save(&updated)?;
+        *transactions = updated;
+        Ok(added)
+    }
+
+    /// 校验追加这些交易后流水是否一致（用于导入预览，不写入）
+    pub fn check_append(&self, inputs: &[NewTransaction], fees: &FeeSchedule, method: CostMethod) -> Result<()> {
+        let mut updated = self.transactions.read().unwrap().clone();
+        for (i, input) in inputs.iter().enumerate() {
+            updated.push(build_transaction(format!("preview{}", i), input.clone(), fees));
+        }
+        derive_positions(&updated, method).map(|_| ())
+    }
+
+    /// 已导入记录的来源标识（用于导入去重）
+    pub fn source_refs(&self) -> HashSet<String> {
+        self.transactions
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|tx| !tx.source_ref.is_empty())
+            .map(|tx| tx.source_ref.clone())
+            .collect()
+    }
+
+    /// 删除交易；删除后若导致后续卖出超过持仓则拒绝
+    pub fn delete(&self, id: &str, method: CostMethod) -> Result<()> {
+        let mut transactions = self.transactions.write().unwrap();
+        if !transactions.iter().any(|tx| tx.id == id) {
+            anyhow::bail!("交易记录不存在: {}", id);
+        }
+        let updated: Vec<Transaction> = transactions.iter().filter(|tx| tx.id != id).cloned().collect();
+        derive_positions(&updated, method).context("删除后交易流水不一致")?;
+        Self::save(&updated)?;
+        *transactions = updated;
+        Ok(())
+    }
+
+    /// 推导所有股票的持仓（包括已清仓、仅有已实现盈亏的股票）
+    pub fn positions(&self, method: CostMethod) -> Result<Vec<DerivedPosition>> {
+        derive_positions(&self.transactions.read().unwrap(), method)
+    }
+
+    /// 生成唯一的交易 ID
+    fn next_id(existing: &[Transaction]) -> String {
+        let mut nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        loop {
+            let id = format!("tx{:x}", nanos);
+            if !existing.iter().any(|tx| tx.id == id) {
+                return id;
+            }
+            nanos += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(d: &str) -> NaiveDate {
+        d.parse().unwrap()
+    }
+
+    fn trade(kind: TransactionKind, d: &str, shares: f64, price: f64, fees: &FeeSchedule) -> Transaction {
+        let (commission, stamp_duty, transfer_fee) = compute_fees(kind, shares, price, fees);
+        Transaction {
+            id: format!("{:?}-{}-{}", kind, d, shares),
+            stock_id: "sh600000".to_string(),
+            kind,
+            date: date(d),
+            shares,
+            price,
+            amount: 0.0,
+            ratio: 0.0,
+            commission,
+            stamp_duty,
+            transfer_fee,
+            note: String::new(),
+            source_ref: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_fees_buy_uses_min_commission_and_no_stamp_duty() {
+        let fees = FeeSchedule::default();
+        // 成交额 10000：佣金 2.5 不足 5 元按 5 元收；买入不收印花税；过户费 0.1
+        let (commission, stamp_duty, transfer_fee) =
+            compute_fees(TransactionKind::Buy, 1000.0, 10.0, &fees);
+        assert_eq!(commission, 5.0);
+        assert_eq!(stamp_duty, 0.0);
+        assert_eq!(transfer_fee, 0.1);
+    }
+
+    #[test]
+    fn test_fees_sell_charges_stamp_duty() {
+        let fees = FeeSchedule::default();
+        // 成交额 100000：佣金 25，印花税 50，过户费 1
+        let (commission, stamp_duty, transfer_fee) =
+            compute_fees(TransactionKind::Sell, 5000.0, 20.0, &fees);
+        assert_eq!(commission, 25.0);
+        assert_eq!(stamp_duty, 50.0);
+        assert_eq!(transfer_fee, 1.0);
+
+        // 分红不产生费用
+        assert_eq!(compute_fees(TransactionKind::Dividend, 0.0, 0.0, &fees), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_weighted_average_realized_pnl_includes_fees() {
+        let fees = FeeSchedule::default();
+        let txs = vec![
+            trade(TransactionKind::Buy, "2024-01-02", 1000.0, 10.0, &fees), // 成本 10005.1
+            trade(TransactionKind::Buy, "2024-01-03", 1000.0, 12.0, &fees), // 成本 12005.12
+            trade(TransactionKind::Sell, "2024-01-04", 1000.0, 13.0, &fees),
+        ];
+        let positions = derive_positions(&txs, CostMethod::WeightedAverage).unwrap();
+        let p = &positions[0];
+
+        let avg = (10005.1 + 12005.12) / 2000.0;
+        // 卖出：成交额 13000，佣金 5，印花税 6.5，过户费 0.13
+        let proceeds = 13000.0 - 5.0 - 6.5 - 0.13;
+        assert!((p.shares - 1000.0).abs() < 1e-9);
+        assert!((p.avg_cost - avg).abs() < 1e-9);
+        assert!((p.realized_pnl - (proceeds - avg * 1000.0)).abs() < 1e-6);
+        assert!((p.total_fees - (5.1 + 5.12 + 11.63)).abs() < 1e-6);
+        assert_eq!(p.open_date, Some(date("2024-01-02")));
+    }
+
+    #[test]
+    fn test_fifo_consumes_oldest_lot_first() {
+        let fees = FeeSchedule {
+            commission_rate: 0.0,
+            min_commission: 0.0,
+            stamp_duty_rate: 0.0,
+            transfer_fee_rate: 0.0,
+        };
+        let txs = vec![
+            trade(TransactionKind::Buy, "2024-01-02", 1000.0, 10.0, &fees),
+            trade(TransactionKind::Buy, "2024-01-03", 1000.0, 12.0, &fees),
+            trade(TransactionKind::Sell, "2024-01-04", 1500.0, 13.0, &fees),
+        ];
+        let positions = derive_positions(&txs, CostMethod::Fifo).unwrap();
+        let p = &positions[0];
+
+        // 卖出 1000@10 + 500@12，剩余 500@12
+        assert!((p.realized_pnl - (1500.0 * 13.0 - 10000.0 - 6000.0)).abs() < 1e-9);
+        assert!((p.shares - 500.0).abs() < 1e-9);
+        assert!((p.avg_cost - 12.0).abs() < 1e-9);
+        assert_eq!(p.open_date, Some(date("2024-01-03")));
+    }
+
+    #[test]
+    fn test_split_and_dividend() {
+        let fees = FeeSchedule::default();
+        let mut split = trade(TransactionKind::Split, "2024-06-01", 0.0, 0.0, &fees);
+        split.ratio = 1.5;
+        let mut dividend = trade(TransactionKind::Dividend, "2024-06-02", 0.0, 0.0, &fees);
+        dividend.amount = 300.0;
+
+        let txs = vec![trade(TransactionKind::Buy, "2024-01-02", 1000.0, 15.0, &fees), split, dividend];
+        let p = &derive_positions(&txs, CostMethod::Fifo).unwrap()[0];
+        assert!((p.shares - 1500.0).abs() < 1e-9);
+        // 送转不改变总成本
+        assert!((p.cost_basis - (15000.0 + 5.0 + 0.15)).abs() < 1e-6);
+        assert!((p.realized_pnl - 300.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_oversell_is_rejected_and_closed_position_has_no_open_date() {
+        let fees = FeeSchedule::default();
+        let buy = trade(TransactionKind::Buy, "2024-01-02", 100.0, 10.0, &fees);
+        let oversell = trade(TransactionKind::Sell, "2024-01-03", 200.0, 11.0, &fees);
+        assert!(derive_positions(&[buy.clone(), oversell], CostMethod::Fifo).is_err());
+
+        let close = trade(TransactionKind::Sell, "2024-01-03", 100.0, 11.0, &fees);
+        let p = &derive_positions(&[buy, close], CostMethod::Fifo).unwrap()[0];
+        assert_eq!(p.shares, 0.0);
+        assert_eq!(p.open_date, None);
+    }
+}
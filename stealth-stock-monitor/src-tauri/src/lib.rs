@@ -1,14 +1,44 @@
 pub mod models;
 pub mod config;
+pub mod migrate;
+pub mod validate;
+pub mod groups;
 pub mod sources;
 pub mod poller;
 pub mod hotkey;
+pub mod server;
+pub mod idle;
+pub mod window;
+pub mod portfolio;
+pub mod ledger;
+pub mod broker_import;
+pub mod calendar;
+pub mod history;
+pub mod tickstore;
+pub mod intraday;
+pub mod kline;
+pub mod indicators;
+pub mod alerts;
+pub mod market;
+pub mod watcher;
+pub mod watchlist;
+pub mod undo;
+#[cfg(test)]
+mod test_support;
 
-use std::sync::Arc;
-use tauri::{Manager, Emitter, AppHandle, State};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{Manager, Emitter, AppHandle, RunEvent, State};
 use tauri::tray::{TrayIconBuilder, MouseButton, MouseButtonState, TrayIconEvent};
-use tauri::menu::{MenuBuilder, MenuItemBuilder};
-use models::{Config, Stock};
+use tauri::menu::{CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+use models::{
+    Adjust, AlertCondition, AlertRule, Bar, BarInterval, Broker, Config, ConfigBackup,
+    DailySnapshot, DerivedPosition, Holding, ImportMode, ImportPreview, IndicatorInterval,
+    IndicatorResult, IndicatorSeries, IndicatorSpec, IntradayData, Kline, KlinePeriod,
+    MarketSummary, NewTransaction, PortfolioUpdate, PriceUpdate, Stock, StockDetail,
+    StockOverrides, Tick, TradingSoftware, Transaction, UndoState, WatchGroup, WatchlistFormat,
+    WatchlistImport,
+};
 use config::ConfigStore;
 use poller::Poller;
 
@@ -17,6 +47,13 @@ pub struct AppState {
     pub config_store: Arc<ConfigStore>,
     pub poller: Poller,
     pub hotkey_manager: Arc<hotkey::HotkeyManager>,
+    pub api_server: server::ApiServer,
+    pub ledger: Arc<ledger::LedgerStore>,
+    pub history: Arc<history::HistoryStore>,
+    pub tick_store: Arc<tickstore::TickStore>,
+    pub intraday: intraday::IntradayCache,
+    /// 详情窗口当前显示的股票
+    pub detail_stock: Mutex<Option<String>>,
 }
 
 // ==================== Tauri Commands ====================
@@ -27,6 +64,121 @@ fn get_config(state: State<AppState>) -> Result<Config, String> {
     Ok(state.config_store.get())
 }
 
+/// 配置整体替换后应用各项副作用：快捷键、轮询、本地 API、持仓推导，并通知前端
+fn apply_config_change(state: &AppState, app: &AppHandle, old_config: &Config) {
+    let config = state.config_store.get();
+
+    // 置顶、任务栏图标、鼠标穿透变更时应用到悬浮窗口（如切换配置方案）
+    if old_config.window.always_on_top != config.window.always_on_top
+        || old_config.window.hide_in_taskbar != config.window.hide_in_taskbar
+        || old_config.window.click_through != config.window.click_through
+    {
+        if let Err(e) = window::apply_window_config(app, &config.window) {
+            log::error!("应用窗口配置失败: {}", e);
+        }
+    }
+
+    // 如果快捷键配置有变更，重新注册快捷键
+    if old_config.shortcuts != config.shortcuts {
+        if let Err(e) = state.hotkey_manager.reload() {
+            log::error!("重新注册快捷键失败: {}", e);
+        }
+    }
+
+    // 刷新间隔或数据源变更时立即重启轮询
+    if old_config.app.refresh_interval_ms != config.app.refresh_interval_ms
+        || old_config.app.data_sources != config.app.data_sources
+    {
+        state.poller.restart(app.clone(), state.config_store.clone());
+    }
+
+    // 切换"隐藏时暂停"后按当前窗口状态立即生效
+    if old_config.app.pause_when_hidden != config.app.pause_when_hidden {
+        window::sync_poller_pause(app, window::is_monitor_visible(app));
+    }
+
+    // 本地行情 API 配置变更时启停服务
+    if old_config.api != config.api {
+        if let Err(e) = state.api_server.apply(&config.api) {
+            log::error!("本地行情 API 配置应用失败: {}", e);
+            let _ = app.emit(
+                "error",
+                serde_json::json!({
+                    "code": "API_SERVER_ERROR",
+                    "message": e.to_string()
+                }),
+            );
+        }
+    }
+
+    // 成本计算方式变更后按新方式重新推导持仓
+    if old_config.portfolio.cost_method != config.portfolio.cost_method {
+        if let Err(e) = sync_ledger_holdings(state) {
+            log::error!("按新的成本计算方式同步持仓失败: {}", e);
+        }
+    }
+
+    let _ = app.emit("config-changed", &state.config_store.get());
+}
+
+/// 切换配置方案并应用新配置（命令、托盘菜单和快捷键共用）
+fn activate_profile(state: &AppState, app: &AppHandle, name: &str) -> anyhow::Result<()> {
+    let old_config = state.config_store.get();
+    state.config_store.switch_profile(name)?;
+    apply_config_change(state, app, &old_config);
+    refresh_tray_menu(app);
+    Ok(())
+}
+
+/// 切换到下一个配置方案（快捷键）
+pub fn cycle_profile(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let result = state.config_store.next_profile().and_then(|next| match next {
+        Some(name) => activate_profile(&state, app, &name),
+        None => {
+            log::info!("只有一个配置方案，无需切换");
+            Ok(())
+        }
+    });
+    if let Err(e) = result {
+        log::error!("切换配置方案失败: {:#}", e);
+        let _ = app.emit(
+            "error",
+            serde_json::json!({ "code": "PROFILE_SWITCH_ERROR", "message": format!("{:#}", e) }),
+        );
+    }
+}
+
+/// 列出配置方案名称（含当前方案）
+#[tauri::command]
+fn list_profiles(state: State<AppState>) -> Result<Vec<String>, String> {
+    state.config_store.list_profiles().map_err(|e| e.to_string())
+}
+
+/// 以当前配置为模板新建配置方案
+#[tauri::command]
+fn create_profile(state: State<AppState>, app: AppHandle, name: String) -> Result<(), String> {
+    state.config_store.create_profile(&name).map_err(|e| e.to_string())?;
+    refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// 删除配置方案（不能删除当前方案）
+#[tauri::command]
+fn delete_profile(state: State<AppState>, app: AppHandle, name: String) -> Result<(), String> {
+    state.config_store.delete_profile(&name).map_err(|e| e.to_string())?;
+    refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// 切换配置方案
+#[tauri::command]
+fn switch_profile(state: State<AppState>, app: AppHandle, name: String) -> Result<(), String> {
+    activate_profile(&state, &app, &name).map_err(|e| format!("{:#}", e))
+}
+
 /// 更新配置
 #[tauri::command]
 fn update_config(
@@ -34,39 +186,37 @@ fn update_config(
     app: AppHandle,
     config: Config,
 ) -> Result<(), String> {
-    // 检查快捷键是否有变更
     let old_config = state.config_store.get();
-    let shortcuts_changed = old_config.shortcuts != config.shortcuts;
-
     state
         .config_store
-        .update(config.clone())
+        .update(config)
         .map_err(|e| e.to_string())?;
+    apply_config_change(&state, &app, &old_config);
+    Ok(())
+}
 
-    // 如果快捷键配置有变更，重新注册快捷键
-    if shortcuts_changed {
-        if let Err(e) = state.hotkey_manager.reload() {
-            log::error!("重新注册快捷键失败: {}", e);
-        }
-    }
+/// 列出配置备份（最新的在前）
+#[tauri::command]
+fn list_config_backups() -> Result<Vec<ConfigBackup>, String> {
+    config::list_backups().map_err(|e| e.to_string())
+}
 
-    let _ = app.emit("config-changed", &config);
-    Ok(())
+/// 用指定备份恢复配置（恢复前会备份当前配置）
+#[tauri::command]
+fn restore_config_backup(state: State<AppState>, app: AppHandle, name: String) -> Result<Config, String> {
+    let old_config = state.config_store.get();
+    let config = state
+        .config_store
+        .restore_backup(&name)
+        .map_err(|e| e.to_string())?;
+    apply_config_change(&state, &app, &old_config);
+    Ok(config)
 }
 
 /// 添加股票
 #[tauri::command]
 fn add_stock(state: State<AppState>, app: AppHandle, code: String) -> Result<Stock, String> {
-    let market = sources::detect_market(&code);
-    let id = sources::make_stock_id(market, &code);
-
-    let stock = Stock {
-        id: id.clone(),
-        code: code.clone(),
-        market: market.to_string(),
-        alias: String::new(), // 后续通过数据源获取名称
-        visible: true,
-    };
+    let stock = sources::make_stock(&code);
 
     state
         .config_store
@@ -100,19 +250,476 @@ fn reorder_stocks(state: State<AppState>, app: AppHandle, ids: Vec<String>) -> R
     Ok(())
 }
 
-/// 控制窗口显隐
+/// 撤销上一次自选股编辑（添加、删除、排序、分组修改、导入等）
 #[tauri::command]
-fn set_window_visible(app: AppHandle, visible: bool) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("monitor") {
-        if visible {
-            window.show().map_err(|e| e.to_string())?;
-        } else {
-            window.hide().map_err(|e| e.to_string())?;
+fn undo_watchlist(state: State<AppState>, app: AppHandle) -> Result<UndoState, String> {
+    let undo = state.config_store.undo().map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(undo)
+}
+
+/// 重做上一次撤销的自选股编辑
+#[tauri::command]
+fn redo_watchlist(state: State<AppState>, app: AppHandle) -> Result<UndoState, String> {
+    let undo = state.config_store.redo().map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(undo)
+}
+
+/// 获取撤销 / 重做状态
+#[tauri::command]
+fn get_undo_state(state: State<AppState>) -> UndoState {
+    state.config_store.undo_state()
+}
+
+/// 新建自选股分组
+#[tauri::command]
+fn create_group(state: State<AppState>, app: AppHandle, name: String) -> Result<WatchGroup, String> {
+    let group = state
+        .config_store
+        .create_group(&name)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(group)
+}
+
+/// 重命名分组
+#[tauri::command]
+fn rename_group(state: State<AppState>, app: AppHandle, id: String, name: String) -> Result<(), String> {
+    state
+        .config_store
+        .rename_group(&id, &name)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(())
+}
+
+/// 删除分组（只属于该分组且无持仓的股票一并删除）
+#[tauri::command]
+fn delete_group(state: State<AppState>, app: AppHandle, id: String) -> Result<(), String> {
+    state
+        .config_store
+        .delete_group(&id)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(())
+}
+
+/// 切换当前分组
+#[tauri::command]
+fn switch_group(state: State<AppState>, app: AppHandle, id: String) -> Result<(), String> {
+    state
+        .config_store
+        .switch_group(&id)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(())
+}
+
+/// 导出当前分组的自选股
+#[tauri::command]
+fn export_watchlist(state: State<AppState>, format: WatchlistFormat) -> Result<String, String> {
+    watchlist::export(&state.config_store.get(), format).map_err(|e| e.to_string())
+}
+
+/// 解析导入内容并应用到当前分组，format 为空时按内容识别
+fn run_watchlist_import(
+    state: &AppState,
+    content: &str,
+    format: Option<WatchlistFormat>,
+    mode: ImportMode,
+    dry_run: bool,
+) -> Result<WatchlistImport, String> {
+    let format = format.unwrap_or_else(|| watchlist::detect_format(content));
+    let entries = watchlist::parse(content, format).map_err(|e| format!("{:#}", e))?;
+    state
+        .config_store
+        .import_watchlist(&entries, format, mode, dry_run)
+        .map_err(|e| e.to_string())
+}
+
+/// 预览自选股导入结果（不写入）
+#[tauri::command]
+fn preview_watchlist_import(
+    state: State<AppState>,
+    content: String,
+    format: Option<WatchlistFormat>,
+    mode: ImportMode,
+) -> Result<WatchlistImport, String> {
+    run_watchlist_import(&state, &content, format, mode, true)
+}
+
+/// 导入自选股到当前分组（合并或替换），返回新增、移除和冲突的条目
+#[tauri::command]
+fn import_watchlist(
+    state: State<AppState>,
+    app: AppHandle,
+    content: String,
+    format: Option<WatchlistFormat>,
+    mode: ImportMode,
+) -> Result<WatchlistImport, String> {
+    let report = run_watchlist_import(&state, &content, format, mode, false)?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(report)
+}
+
+/// 预览交易软件自选股导入结果（不写入），bytes 为导出文件的原始内容，software 为空时自动识别
+#[tauri::command]
+fn preview_software_watchlist(
+    state: State<AppState>,
+    bytes: Vec<u8>,
+    software: Option<TradingSoftware>,
+) -> Result<WatchlistImport, String> {
+    let (software, entries) =
+        watchlist::parse_software(&bytes, software).map_err(|e| format!("{:#}", e))?;
+    watchlist::add_entries(&state.config_store, &entries, software, true).map_err(|e| e.to_string())
+}
+
+/// 从同花顺 / 东方财富 / 通达信导出的文件追加自选股到当前分组
+#[tauri::command]
+fn import_software_watchlist(
+    state: State<AppState>,
+    app: AppHandle,
+    bytes: Vec<u8>,
+    software: Option<TradingSoftware>,
+) -> Result<WatchlistImport, String> {
+    let (software, entries) =
+        watchlist::parse_software(&bytes, software).map_err(|e| format!("{:#}", e))?;
+    let result = watchlist::add_entries(&state.config_store, &entries, software, false);
+    // 中途失败时已添加的股票保留，同样通知界面
+    let _ = app.emit("config-changed", &state.config_store.get());
+    result.map_err(|e| e.to_string())
+}
+
+/// 设置单只股票的显示与刷新选项（小数位数、涨跌颜色、置顶、备注、只显示涨跌幅、刷新优先级）
+#[tauri::command]
+fn set_stock_overrides(
+    state: State<AppState>,
+    app: AppHandle,
+    id: String,
+    overrides: StockOverrides,
+) -> Result<(), String> {
+    state
+        .config_store
+        .set_stock_overrides(&id, overrides)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(())
+}
+
+/// 获取最新行情快照（id -> 行情），窗口打开或刷新时用于立即显示
+#[tauri::command]
+fn get_quotes(state: State<AppState>) -> Result<HashMap<String, PriceUpdate>, String> {
+    Ok(state.poller.quotes())
+}
+
+/// 设置或清除持仓（holding 为 null 时清除）
+#[tauri::command]
+fn set_holding(
+    state: State<AppState>,
+    app: AppHandle,
+    id: String,
+    holding: Option<Holding>,
+) -> Result<(), String> {
+    state
+        .config_store
+        .set_holding(&id, holding)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(())
+}
+
+/// 根据最新行情快照计算当前组合盈亏，没有持仓时返回 null
+#[tauri::command]
+fn get_portfolio(state: State<AppState>) -> Result<Option<PortfolioUpdate>, String> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis() as u64;
+    Ok(portfolio::compute(
+        &state.config_store.get().stocks,
+        &state.poller.quotes(),
+        portfolio::china_today(),
+        timestamp,
+    ))
+}
+
+/// 获取最新的大盘概况（未启用大盘概况或尚未获取时为空）
+#[tauri::command]
+fn get_market_summary(state: State<AppState>) -> Result<Option<MarketSummary>, String> {
+    Ok(state.poller.market_summary())
+}
+
+/// 用交易流水推导的持仓同步自选股的 holding
+fn sync_ledger_holdings(state: &AppState) -> anyhow::Result<()> {
+    let method = state.config_store.get().portfolio.cost_method;
+    let positions = state.ledger.positions(method)?;
+    state.config_store.sync_holdings(&positions)
+}
+
+/// 记录一笔交易（买入/卖出/分红/送转），费用留空时按费用标准自动计算
+#[tauri::command]
+fn add_transaction(
+    state: State<AppState>,
+    app: AppHandle,
+    transaction: NewTransaction,
+) -> Result<Transaction, String> {
+    let portfolio = state.config_store.get().portfolio;
+    let transaction = state
+        .ledger
+        .add(transaction, &portfolio.fees, portfolio.cost_method)
+        .map_err(|e| e.to_string())?;
+    sync_ledger_holdings(&state).map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(transaction)
+}
+
+/// 列出交易记录，可按股票过滤
+#[tauri::command]
+fn list_transactions(state: State<AppState>, stock_id: Option<String>) -> Result<Vec<Transaction>, String> {
+    Ok(state.ledger.list(stock_id.as_deref()))
+}
+
+/// 删除交易记录
+#[tauri::command]
+fn delete_transaction(state: State<AppState>, app: AppHandle, id: String) -> Result<(), String> {
+    let method = state.config_store.get().portfolio.cost_method;
+    state.ledger.delete(&id, method).map_err(|e| e.to_string())?;
+    sync_ledger_holdings(&state).map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(())
+}
+
+/// 获取由交易流水推导的持仓与已实现盈亏（包括已清仓股票）
+#[tauri::command]
+fn get_positions(state: State<AppState>) -> Result<Vec<DerivedPosition>, String> {
+    let method = state.config_store.get().portfolio.cost_method;
+    state.ledger.positions(method).map_err(|e| e.to_string())
+}
+
+/// 预览券商对账单导入结果（不写入），broker 为空时自动识别
+#[tauri::command]
+fn preview_broker_import(
+    state: State<AppState>,
+    path: String,
+    broker: Option<Broker>,
+) -> Result<ImportPreview, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+    broker_import::preview(&bytes, broker, &state.ledger, &state.config_store.get().portfolio)
+        .map_err(|e| e.to_string())
+}
+
+/// 导入券商对账单：跳过已导入过的记录，写入交易流水并同步持仓
+#[tauri::command]
+fn import_broker_statement(
+    state: State<AppState>,
+    app: AppHandle,
+    path: String,
+    broker: Option<Broker>,
+) -> Result<ImportPreview, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let portfolio = state.config_store.get().portfolio;
+    let preview = broker_import::preview(&bytes, broker, &state.ledger, &portfolio)
+        .map_err(|e| e.to_string())?;
+    if let Some(error) = &preview.error {
+        return Err(error.clone());
+    }
+
+    let transactions = broker_import::new_transactions(&preview.rows);
+    if !transactions.is_empty() {
+        state
+            .ledger
+            .add_all(transactions, &portfolio.fees, portfolio.cost_method)
+            .map_err(|e| e.to_string())?;
+        sync_ledger_holdings(&state).map_err(|e| e.to_string())?;
+        let _ = app.emit("config-changed", &state.config_store.get());
+    }
+    log::info!(
+        "导入对账单: 新增 {} 条, 重复 {} 条, 跳过 {} 条",
+        preview.new_count,
+        preview.duplicate_count,
+        preview.skipped_count
+    );
+    Ok(preview)
+}
+
+/// 获取日期区间内（含首尾）的每日收盘记录，用于盈亏日历
+#[tauri::command]
+fn get_daily_history(
+    state: State<AppState>,
+    start: chrono::NaiveDate,
+    end: chrono::NaiveDate,
+) -> Result<Vec<DailySnapshot>, String> {
+    Ok(state.history.range(start, end))
+}
+
+/// 查询本地记录的 K 线（start/end 为毫秒时间戳，缺省为全部）
+#[tauri::command]
+fn get_bars(
+    state: State<AppState>,
+    id: String,
+    interval: BarInterval,
+    start: Option<u64>,
+    end: Option<u64>,
+) -> Result<Vec<Bar>, String> {
+    state
+        .tick_store
+        .bars(&id, interval, start.unwrap_or(0), end.unwrap_or(u64::MAX))
+        .map_err(|e| e.to_string())
+}
+
+/// 查询本地记录的逐笔行情（start/end 为毫秒时间戳，缺省为全部）
+#[tauri::command]
+fn get_ticks(
+    state: State<AppState>,
+    id: String,
+    start: Option<u64>,
+    end: Option<u64>,
+) -> Result<Vec<Tick>, String> {
+    state
+        .tick_store
+        .ticks(&id, start.unwrap_or(0), end.unwrap_or(u64::MAX))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取当日分时数据（按数据源配置顺序尝试支持分时的数据源，按天缓存）
+#[tauri::command]
+async fn get_intraday(state: State<'_, AppState>, id: String) -> Result<IntradayData, String> {
+    let source_names = state.config_store.get().app.data_sources;
+    state
+        .intraday
+        .get(&id, &source_names)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取历史 K 线（日/周/月，前复权/后复权/不复权），每次请求从本地缓存增量补全
+#[tauri::command]
+async fn get_klines(
+    state: State<'_, AppState>,
+    id: String,
+    period: KlinePeriod,
+    adjust: Option<Adjust>,
+    start: Option<chrono::NaiveDate>,
+    end: Option<chrono::NaiveDate>,
+) -> Result<Vec<Kline>, String> {
+    let source_names = state.config_store.get().app.data_sources;
+    kline::get_klines(
+        &state.tick_store,
+        &source_names,
+        &id,
+        period,
+        adjust.unwrap_or_default(),
+        start,
+        end,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 获取五档盘口与关键统计（按数据源配置顺序尝试支持盘口的数据源）
+#[tauri::command]
+async fn get_stock_detail(state: State<'_, AppState>, id: String) -> Result<StockDetail, String> {
+    let source_names = state.config_store.get().app.data_sources;
+    let (market, code) = sources::parse_code(&id);
+    let mut last_error = "没有可用的数据源".to_string();
+    for source in source_names.iter().filter_map(|name| sources::create_source(name)) {
+        match source.fetch_detail(&market, &code).await {
+            Ok(detail) => return Ok(detail),
+            Err(e) => {
+                log::debug!("数据源 {} 获取盘口失败: {}", source.name(), e);
+                last_error = e.to_string();
+            }
         }
     }
+    Err(last_error)
+}
+
+/// 计算技术指标（MA/EMA/MACD/RSI/KDJ/BOLL/VWAP）
+#[tauri::command]
+async fn get_indicators(
+    state: State<'_, AppState>,
+    id: String,
+    interval: IndicatorInterval,
+    specs: Vec<IndicatorSpec>,
+) -> Result<IndicatorResult, String> {
+    for spec in &specs {
+        indicators::validate(spec, interval).map_err(|e| e.to_string())?;
+    }
+    let source_names = state.config_store.get().app.data_sources;
+    let candles = indicators::load_candles(&state.tick_store, &state.intraday, &source_names, &id, interval)
+        .await
+        .map_err(|e| e.to_string())?;
+    let series = specs
+        .into_iter()
+        .map(|spec| IndicatorSeries {
+            lines: indicators::compute(&spec, &candles),
+            spec,
+        })
+        .collect();
+    Ok(IndicatorResult {
+        id,
+        interval,
+        labels: candles.iter().map(|c| c.label.clone()).collect(),
+        close: candles.iter().map(|c| c.close).collect(),
+        series,
+    })
+}
+
+/// 添加预警规则（股票不在列表中时自动添加）
+#[tauri::command]
+fn add_alert_rule(
+    state: State<AppState>,
+    app: AppHandle,
+    code: String,
+    interval: Option<IndicatorInterval>,
+    condition: AlertCondition,
+    note: Option<String>,
+) -> Result<AlertRule, String> {
+    let interval = interval.unwrap_or_default();
+    alerts::validate(&condition, interval).map_err(|e| e.to_string())?;
+    let stock = sources::make_stock(&code);
+    if !state.config_store.get().stocks.iter().any(|s| s.id == stock.id) {
+        state.config_store.add_stock(stock.clone()).map_err(|e| e.to_string())?;
+    }
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_nanos();
+    let rule = AlertRule {
+        id: format!("alert{:x}", nanos),
+        stock_id: stock.id,
+        interval,
+        condition,
+        enabled: true,
+        note: note.unwrap_or_default(),
+    };
+    state
+        .config_store
+        .add_alert(rule.clone())
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
+    Ok(rule)
+}
+
+/// 删除预警规则
+#[tauri::command]
+fn remove_alert_rule(state: State<AppState>, app: AppHandle, id: String) -> Result<(), String> {
+    state
+        .config_store
+        .remove_alert(&id)
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit("config-changed", &state.config_store.get());
     Ok(())
 }
 
+/// 控制窗口显隐
+#[tauri::command]
+fn set_window_visible(app: AppHandle, visible: bool) -> Result<(), String> {
+    window::set_monitor_visible(&app, visible).map_err(|e| e.to_string())
+}
+
 /// 设置鼠标穿透
 #[tauri::command]
 fn set_ignore_cursor_events(app: AppHandle, ignore: bool) -> Result<(), String> {
@@ -132,6 +739,18 @@ fn open_settings(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// 打开详情窗口并显示指定股票
+#[tauri::command]
+fn open_detail(app: AppHandle, id: String) -> Result<(), String> {
+    window::open_detail(&app, &id).map_err(|e| e.to_string())
+}
+
+/// 详情窗口当前显示的股票（窗口加载时读取）
+#[tauri::command]
+fn get_detail_stock(state: State<AppState>) -> Result<Option<String>, String> {
+    Ok(state.detail_stock.lock().unwrap().clone())
+}
+
 /// 立即刷新数据
 #[tauri::command]
 fn force_refresh() -> Result<(), String> {
@@ -228,12 +847,52 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             get_config,
             update_config,
+            list_config_backups,
+            restore_config_backup,
+            list_profiles,
+            create_profile,
+            delete_profile,
+            switch_profile,
             add_stock,
             remove_stock,
             reorder_stocks,
+            set_stock_overrides,
+            undo_watchlist,
+            redo_watchlist,
+            get_undo_state,
+            create_group,
+            rename_group,
+            delete_group,
+            switch_group,
+            export_watchlist,
+            preview_watchlist_import,
+            import_watchlist,
+            preview_software_watchlist,
+            import_software_watchlist,
+            get_quotes,
+            set_holding,
+            get_portfolio,
+            get_market_summary,
+            add_transaction,
+            list_transactions,
+            delete_transaction,
+            get_positions,
+            preview_broker_import,
+            import_broker_statement,
+            get_daily_history,
+            get_bars,
+            get_ticks,
+            get_intraday,
+            get_klines,
+            get_stock_detail,
+            get_indicators,
+            add_alert_rule,
+            remove_alert_rule,
             set_window_visible,
             set_ignore_cursor_events,
             open_settings,
+            open_detail,
+            get_detail_stock,
             force_refresh,
             resize_monitor_window,
             move_monitor_window,
@@ -242,7 +901,7 @@ pub fn run() {
         ])
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                if window.label() == "settings" {
+                if window.label() == "settings" || window.label() == "detail" {
                     // 阻止关闭，改为隐藏
                     api.prevent_close();
                     let _ = window.hide();
@@ -262,17 +921,61 @@ pub fn run() {
                 log::error!("注册全局快捷键失败: {}", e);
             }
 
+            // 恢复上次退出时的行情快照，界面启动即可显示（标记为旧数据）
+            let poller = Poller::new();
+            if let Err(e) = poller.restore_snapshot() {
+                log::warn!("恢复行情快照失败: {}", e);
+            }
+
+            // 本地行情 API（默认关闭）
+            let api_server = server::ApiServer::new(
+                app_handle.clone(),
+                config_store.clone(),
+                poller.shared_quotes(),
+            );
+            if let Err(e) = api_server.apply(&config_store.get().api) {
+                log::error!("启动本地行情 API 失败: {}", e);
+            }
+
+            // 交易流水（与 config.json 同目录）
+            let ledger = Arc::new(ledger::LedgerStore::new().expect("无法确定交易流水路径"));
+
+            // 每日收盘记录，交易日收盘后自动写入
+            let history = Arc::new(history::HistoryStore::new().expect("无法确定收盘记录路径"));
+            history::spawn_recorder(
+                app_handle.clone(),
+                config_store.clone(),
+                poller.shared_quotes(),
+                history.clone(),
+            );
+
+            // 本地行情存储（默认关闭，开启后记录每批行情）
+            let tick_store = Arc::new(tickstore::TickStore::new());
+            tickstore::attach(&app_handle, config_store.clone(), tick_store.clone());
+
+            // 预警规则检查（交易时段内）
+            alerts::spawn_evaluator(app_handle.clone(), config_store.clone(), poller.shared_quotes());
+
             // 创建 AppState，包含 hotkey_manager
             app.manage(AppState {
                 config_store: config_store.clone(),
-                poller: Poller::new(),
+                poller,
                 hotkey_manager: Arc::new(hotkey_manager),
+                api_server,
+                ledger,
+                history,
+                tick_store,
+                intraday: intraday::IntradayCache::new(),
+                detail_stock: Mutex::new(None),
             });
 
             // 启动数据轮询
             let state: State<AppState> = app.state();
             state.poller.start(app_handle.clone(), state.config_store.clone());
 
+            // 监听配置文件的外部修改
+            watcher::spawn_watcher(app_handle.clone(), state.config_store.clone());
+
             // 设置应用菜单（系统菜单）
             setup_app_menu(app)?;
 
@@ -300,9 +1003,7 @@ pub fn run() {
                     }
                     "hide" => {
                         log::info!("隐藏应用");
-                        if let Some(win) = app.get_webview_window("monitor") {
-                            let _ = win.hide();
-                        }
+                        let _ = window::set_monitor_visible(app, false);
                     }
                     "hide_others" => {
                         log::info!("隐藏其他应用");
@@ -310,10 +1011,7 @@ pub fn run() {
                     }
                     "show_all" => {
                         log::info!("全部显示");
-                        if let Some(win) = app.get_webview_window("monitor") {
-                            let _ = win.show();
-                            let _ = win.set_focus();
-                        }
+                        let _ = window::set_monitor_visible(app, true);
                     }
                     "app_quit" => {
                         log::info!("退出应用（系统菜单）");
@@ -333,9 +1031,7 @@ pub fn run() {
                     }
                     "ctx_hide" => {
                         log::info!("隐藏窗口");
-                        if let Some(win) = app.get_webview_window("monitor") {
-                            let _ = win.hide();
-                        }
+                        let _ = window::set_monitor_visible(app, false);
                     }
                     "ctx_quit" => {
                         log::info!("退出应用（右键菜单）");
@@ -359,8 +1055,30 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("启动应用失败");
+        .build(tauri::generate_context!())
+        .expect("启动应用失败")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                let state: State<AppState> = app.state();
+                state.api_server.stop();
+
+                // 等待轮询任务退出（最多 3 秒），避免退出过程中仍在写快照
+                tauri::async_runtime::block_on(async {
+                    let shutdown = state.poller.shutdown();
+                    if tokio::time::timeout(std::time::Duration::from_secs(3), shutdown)
+                        .await
+                        .is_err()
+                    {
+                        log::warn!("等待轮询任务退出超时");
+                    }
+                });
+
+                // 退出前保存行情快照，下次启动可立即显示
+                if let Err(e) = state.poller.persist_snapshot() {
+                    log::warn!("保存行情快照失败: {}", e);
+                }
+            }
+        });
 }
 
 /// 设置应用菜单（系统菜单，macOS 显示在左上角应用名称下）
@@ -412,17 +1130,58 @@ fn setup_app_menu(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// 设置系统托盘
-fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+/// 托盘菜单中配置方案项的 ID 前缀
+const PROFILE_MENU_PREFIX: &str = "profile:";
+
+/// 构建托盘菜单：配置方案子菜单（勾选当前方案）、设置、退出
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    let (names, current) = match app.try_state::<AppState>() {
+        Some(state) => (
+            state.config_store.list_profiles().unwrap_or_default(),
+            state.config_store.get().profile,
+        ),
+        None => (Vec::new(), String::new()),
+    };
+    let mut profiles = SubmenuBuilder::new(app, "配置方案");
+    for name in &names {
+        let item = CheckMenuItemBuilder::with_id(format!("{}{}", PROFILE_MENU_PREFIX, name), name)
+            .checked(*name == current)
+            .build(app)?;
+        profiles = profiles.item(&item);
+    }
+
     let settings_item = MenuItemBuilder::with_id("settings", "设置").build(app)?;
     let quit_item = MenuItemBuilder::with_id("quit", "退出").build(app)?;
-    let menu = MenuBuilder::new(app)
+    MenuBuilder::new(app)
+        .item(&profiles.build()?)
+        .separator()
         .item(&settings_item)
         .separator()
         .item(&quit_item)
-        .build()?;
+        .build()
+}
+
+/// 配置方案变化后重建托盘菜单
+fn refresh_tray_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_tray_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => log::error!("重建托盘菜单失败: {}", e),
+    }
+}
 
-    let _tray = TrayIconBuilder::new()
+/// 系统托盘 ID
+const TRAY_ID: &str = "main";
+
+/// 设置系统托盘
+fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_tray_menu(app.handle())?;
+
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
         .on_menu_event(|app, event| {
             match event.id().as_ref() {
@@ -435,7 +1194,22 @@ fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                 "quit" => {
                     app.exit(0);
                 }
-                _ => {}
+                id => {
+                    if let Some(name) = id.strip_prefix(PROFILE_MENU_PREFIX) {
+                        let Some(state) = app.try_state::<AppState>() else {
+                            return;
+                        };
+                        if let Err(e) = activate_profile(&state, app, name) {
+                            log::error!("切换配置方案失败: {:#}", e);
+                            let _ = app.emit(
+                                "error",
+                                serde_json::json!({ "code": "PROFILE_SWITCH_ERROR", "message": format!("{:#}", e) }),
+                            );
+                            // 恢复勾选状态
+                            refresh_tray_menu(app);
+                        }
+                    }
+                }
             }
         })
         .on_tray_icon_event(|tray, event| {
@@ -445,15 +1219,7 @@ fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                 ..
             } = event
             {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("monitor") {
-                    if window.is_visible().unwrap_or(false) {
-                        let _ = window.hide();
-                    } else {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
-                }
+                let _ = window::toggle_monitor(tray.app_handle());
             }
         })
         .build(app)?;
@@ -0,0 +1,81 @@
+use tokio::time::Duration;
+
+use crate::models::{MarketBreadth, MarketSummary, PriceUpdate};
+use crate::sources;
+
+/// 大盘概况中的主要指数：(市场, 代码, 简称)
+pub const INDICES: &[(&str, &str, &str)] = &[
+    ("sh", "000001", "上证"),
+    ("sz", "399001", "深成"),
+    ("sz", "399006", "创业板"),
+    ("sh", "000300", "沪深300"),
+    ("sh", "000688", "科创50"),
+];
+
+/// 涨跌统计的刷新间隔（需要额外请求全市场数据，不随行情每次刷新）
+pub const BREADTH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 获取涨跌统计的超时（需要请求多个接口）
+pub const BREADTH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 需要随自选股一起请求的指数代码 (market, code)
+pub fn index_codes() -> Vec<(String, String)> {
+    INDICES
+        .iter()
+        .map(|(market, code, _)| (market.to_string(), code.to_string()))
+        .collect()
+}
+
+/// 从一次轮询结果中挑出指数行情生成大盘概况，指数名称替换为简称
+/// 没有任何指数行情时返回 None
+pub fn build_summary(
+    updates: &[PriceUpdate],
+    breadth: Option<MarketBreadth>,
+    timestamp: u64,
+) -> Option<MarketSummary> {
+    let indices: Vec<PriceUpdate> = INDICES
+        .iter()
+        .filter_map(|(market, code, short_name)| {
+            let id = sources::make_stock_id(market, code);
+            updates.iter().find(|u| u.id == id).map(|u| PriceUpdate {
+                name: short_name.to_string(),
+                ..u.clone()
+            })
+        })
+        .collect();
+    if indices.is_empty() {
+        return None;
+    }
+    Some(MarketSummary {
+        indices,
+        breadth,
+        timestamp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn quote(id: &str, name: &str) -> PriceUpdate {
+        PriceUpdate {
+            name: name.to_string(),
+            ..test_support::quote(id, 3000.0, 2990.0)
+        }
+    }
+
+    #[test]
+    fn test_build_summary_orders_and_renames_indices() {
+        let updates = vec![
+            quote("sz399006", "创业板指"),
+            quote("sh600519", "贵州茅台"),
+            quote("sh000001", "上证指数"),
+        ];
+        let summary = build_summary(&updates, None, 1).unwrap();
+        let names: Vec<&str> = summary.indices.iter().map(|q| q.name.as_str()).collect();
+        assert_eq!(names, ["上证", "创业板"]);
+
+        assert!(build_summary(&updates[1..2], None, 1).is_none());
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::{HashMap, HashSet};
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::models::{Config, Stock, CONFIG_VERSION};
+use crate::{groups, sources};
+
+/// 单步迁移：将配置从版本 n 升级到 n + 1
+type Migration = fn(&mut Value) -> Result<()>;
+
+/// 迁移链，MIGRATIONS[n - 1] 将版本 n 升级到 n + 1
+const MIGRATIONS: &[Migration] = &[v1_to_v2, v2_to_v3];
+
+/// v1 -> v2：修复早期版本添加股票时生成的错误 ID，并清理自选股列表
+/// - 早期添加 "sh600036" 会得到 code "sh600036"、id "shsh600036"，改为 code "600036"、id "sh600036"
+/// - 市场统一为小写，按 ID 去重（保留第一条），预警规则中的股票 ID 同步更新
+/// - 移除股数为 0 的持仓（v2 起有持仓的股票即使隐藏也会继续请求行情）
+fn v1_to_v2(config: &mut Value) -> Result<()> {
+    let Some(stocks) = config.get_mut("stocks").and_then(Value::as_array_mut) else {
+        return Ok(());
+    };
+
+    let mut renamed: HashMap<String, String> = HashMap::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut migrated = Vec::new();
+    for mut stock in stocks.drain(..) {
+        let Some(raw_code) = stock.get("code").and_then(Value::as_str).map(str::to_string) else {
+            log::warn!("迁移配置：丢弃缺少代码的股票 {}", stock);
+            continue;
+        };
+        let (mut market, code) = sources::parse_code(&raw_code);
+        let has_prefix = code != raw_code.trim();
+        if !has_prefix {
+            if let Some(existing) = stock.get("market").and_then(Value::as_str) {
+                market = existing.to_ascii_lowercase();
+            }
+        }
+        let id = sources::make_stock_id(&market, &code);
+        if let Some(old_id) = stock.get("id").and_then(Value::as_str) {
+            if old_id != id {
+                renamed.insert(old_id.to_string(), id.clone());
+            }
+        }
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+
+        stock["id"] = json!(id);
+        stock["code"] = json!(code);
+        stock["market"] = json!(market);
+        let empty_holding = stock
+            .get("holding")
+            .and_then(|h| h.get("shares"))
+            .and_then(Value::as_f64)
+            .is_some_and(|shares| shares <= 0.0);
+        if empty_holding {
+            if let Some(obj) = stock.as_object_mut() {
+                obj.remove("holding");
+            }
+        }
+        migrated.push(stock);
+    }
+    *stocks = migrated;
+
+    if let Some(alerts) = config.get_mut("alerts").and_then(Value::as_array_mut) {
+        for rule in alerts {
+            let new_id = rule
+                .get("stock_id")
+                .and_then(Value::as_str)
+                .and_then(|id| renamed.get(id))
+                .cloned();
+            if let Some(new_id) = new_id {
+                rule["stock_id"] = json!(new_id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// v2 -> v3：引入自选股分组，原有列表（含顺序和显示状态）成为默认分组
+fn v2_to_v3(config: &mut Value) -> Result<()> {
+    let members: Vec<Value> = config
+        .get("stocks")
+        .and_then(Value::as_array)
+        .map(|stocks| {
+            stocks
+                .iter()
+                .filter_map(|stock| {
+                    let id = stock.get("id")?.as_str()?;
+                    let visible = stock.get("visible").and_then(Value::as_bool).unwrap_or(true);
+                    Some(json!({ "id": id, "visible": visible }))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    config["groups"] = json!([{
+        "id": groups::DEFAULT_GROUP_ID,
+        "name": groups::DEFAULT_GROUP_NAME,
+        "members": members,
+    }]);
+    config["active_group"] = json!(groups::DEFAULT_GROUP_ID);
+    Ok(())
+}
+
+/// 配置文件中的版本号，缺失时视为 1
+fn version_of(config: &Value) -> u32 {
+    config
+        .get("version")
+        .and_then(Value::as_u64)
+        .map(|v| v.max(1) as u32)
+        .unwrap_or(1)
+}
+
+/// 按版本号依次执行迁移，将配置升级到 CONFIG_VERSION
+/// 返回升级前的版本；已是当前版本或来自更新版本的程序时不做改动，返回 None
+pub fn migrate(config: &mut Value) -> Result<Option<u32>> {
+    if !config.is_object() {
+        anyhow::bail!("配置文件顶层不是对象");
+    }
+    let from = version_of(config);
+    if from > CONFIG_VERSION {
+        log::warn!("配置版本 {} 高于当前程序支持的版本 {}，未识别的字段将被忽略", from, CONFIG_VERSION);
+        return Ok(None);
+    }
+    if from == CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    for version in from..CONFIG_VERSION {
+        MIGRATIONS[(version - 1) as usize](config)?;
+        config["version"] = json!(version + 1);
+        log::info!("配置已从版本 {} 迁移到 {}", version, version + 1);
+    }
+    Ok(Some(from))
+}
+
+/// 单独解析配置中的一节，失败时返回 None
+fn section<T: DeserializeOwned>(config: &Value, key: &str) -> Option<T> {
+    let value = config.get(key)?;
+    serde_json::from_value(value.clone())
+        .map_err(|e| log::warn!("配置项 {} 无效，使用默认值: {}", key, e))
+        .ok()
+}
+
+/// 整体解析失败时尽量保留可用部分：逐节解析，自选股、分组和预警规则逐条解析
+/// 无效的节使用默认值，无效的条目被丢弃，不会因为一处错误清空整个自选股列表
+pub fn salvage(config: &Value) -> Config {
+    let defaults = Config::default();
+    let items = |key: &str| -> Option<Vec<Value>> { config.get(key)?.as_array().cloned() };
+
+    let mut salvaged = Config {
+        version: CONFIG_VERSION,
+        profile: section(config, "profile").unwrap_or(defaults.profile),
+        window: section(config, "window").unwrap_or(defaults.window),
+        app: section(config, "app").unwrap_or(defaults.app),
+        shortcuts: section(config, "shortcuts").unwrap_or(defaults.shortcuts),
+        stocks: match items("stocks") {
+            Some(stocks) => stocks
+                .into_iter()
+                .filter_map(|s| serde_json::from_value::<Stock>(s).ok())
+                .collect(),
+            None => defaults.stocks,
+        },
+        groups: items("groups")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|g| serde_json::from_value(g).ok())
+            .collect(),
+        active_group: section(config, "active_group").unwrap_or_default(),
+        api: section(config, "api").unwrap_or(defaults.api),
+        portfolio: section(config, "portfolio").unwrap_or(defaults.portfolio),
+        storage: section(config, "storage").unwrap_or(defaults.storage),
+        alerts: items("alerts")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|r| serde_json::from_value(r).ok())
+            .collect(),
+    };
+    groups::normalize(&mut salvaged);
+    salvaged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CostMethod;
+
+    fn fixture(content: &str) -> Value {
+        serde_json::from_str(content).unwrap()
+    }
+
+    fn migrate_to_config(mut value: Value) -> (Option<u32>, Config) {
+        let from = migrate(&mut value).unwrap();
+        (from, serde_json::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_migrate_v1_baseline() {
+        let (from, config) = migrate_to_config(fixture(include_str!("../fixtures/config/v1_baseline.json")));
+        assert_eq!(from, Some(1));
+        assert_eq!(config.version, CONFIG_VERSION);
+
+        let ids: Vec<&str> = config.stocks.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["sh600519", "sh600036", "sz000001"]);
+        assert_eq!(config.stocks[1].code, "600036");
+
+        // 原列表成为默认分组，保留顺序和显示状态
+        assert_eq!(config.active_group, groups::DEFAULT_GROUP_ID);
+        let members: Vec<(&str, bool)> =
+            config.groups[0].members.iter().map(|m| (m.id.as_str(), m.visible)).collect();
+        assert_eq!(members, [("sh600519", true), ("sh600036", true), ("sz000001", false)]);
+        assert_eq!(config.stocks[1].alias, "招行");
+        assert!(!config.stocks[2].visible);
+
+        // 原有设置保留，后来新增的字段取默认值
+        assert_eq!(config.window.display_rows, 2);
+        assert_eq!(config.app.data_sources, ["tencent", "sina"]);
+        assert!(!config.window.show_portfolio);
+        assert_eq!(config.app.idle_threshold_secs, 300);
+        assert_eq!(config.shortcuts.open_detail, "CommandOrControl+Shift+D");
+        assert!(config.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_v1_with_holdings_and_alerts() {
+        let (from, config) = migrate_to_config(fixture(include_str!("../fixtures/config/v1_holdings.json")));
+        assert_eq!(from, Some(1));
+
+        // shsh600519 修复后与 sh600519 重复，保留第一条（含持仓）
+        let ids: Vec<&str> = config.stocks.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["sh600519", "sz300750"]);
+        assert_eq!(config.stocks[0].holding.as_ref().unwrap().shares, 100.0);
+        // 股数为 0 的持仓被移除
+        assert!(config.stocks[1].holding.is_none());
+        // 预警规则跟随新的股票 ID
+        assert_eq!(config.alerts[0].stock_id, "sz300750");
+
+        assert_eq!(config.portfolio.cost_method, CostMethod::Fifo);
+        assert_eq!(config.storage.tick_retention_days, 3);
+        assert_eq!(config.api.port, 18000);
+        assert!(config.app.idle_slowdown);
+    }
+
+    #[test]
+    fn test_migrate_v2_watchlist() {
+        let (from, config) = migrate_to_config(fixture(include_str!("../fixtures/config/v2_watchlist.json")));
+        assert_eq!(from, Some(2));
+        assert_eq!(config.version, CONFIG_VERSION);
+
+        let ids: Vec<&str> = config.stocks.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["sz000858", "sh601318", "sh600036"]);
+
+        // 自选列表成为默认分组，隐藏的股票在分组中同样隐藏
+        assert_eq!(config.groups.len(), 1);
+        assert_eq!(config.active_group, groups::DEFAULT_GROUP_ID);
+        let members: Vec<(&str, bool)> =
+            config.groups[0].members.iter().map(|m| (m.id.as_str(), m.visible)).collect();
+        assert_eq!(members, [("sz000858", true), ("sh601318", false), ("sh600036", true)]);
+
+        // v2 已有的设置原样保留
+        assert_eq!(config.stocks[1].holding.as_ref().unwrap().shares, 500.0);
+        assert_eq!(config.alerts[0].stock_id, "sh600036");
+        assert_eq!(config.shortcuts.open_detail, "CommandOrControl+Shift+X");
+        assert!(config.window.show_market_summary);
+        assert_eq!(config.app.data_sources, ["eastmoney", "sina"]);
+        assert_eq!(config.storage.tick_retention_days, 7);
+    }
+
+    #[test]
+    fn test_current_and_newer_versions_are_untouched() {
+        let mut current = serde_json::to_value(Config::default()).unwrap();
+        let before = current.clone();
+        assert_eq!(migrate(&mut current).unwrap(), None);
+        assert_eq!(current, before);
+
+        let mut newer = json!({ "version": CONFIG_VERSION + 1, "stocks": [], "future": true });
+        let before = newer.clone();
+        assert_eq!(migrate(&mut newer).unwrap(), None);
+        assert_eq!(newer, before);
+        let config: Config = serde_json::from_value(newer).unwrap();
+        assert!(config.stocks.is_empty());
+
+        assert!(migrate(&mut json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn test_salvage_keeps_valid_sections_and_stocks() {
+        let mut broken = fixture(include_str!("../fixtures/config/v1_holdings.json"));
+        migrate(&mut broken).unwrap();
+        broken["window"]["opacity"] = json!("半透明");
+        broken["stocks"].as_array_mut().unwrap().push(json!({ "alias": "缺少代码" }));
+        assert!(serde_json::from_value::<Config>(broken.clone()).is_err());
+
+        let config = salvage(&broken);
+        assert_eq!(config.stocks.len(), 2);
+        assert_eq!(config.window.opacity, Config::default().window.opacity);
+        assert_eq!(config.portfolio.cost_method, CostMethod::Fifo);
+        assert_eq!(config.alerts.len(), 1);
+    }
+}
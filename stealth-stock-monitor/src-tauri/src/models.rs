@@ -1,7 +1,8 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 /// 股票基本信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Stock {
     /// 唯一标识，如 "sh600519"
     pub id: String,
@@ -10,11 +11,97 @@ pub struct Stock {
     /// 市场前缀，如 "sh" / "sz"
     pub market: String,
     /// 自定义别名，如 "茅台"
+    #[serde(default)]
     pub alias: String,
+    /// 是否在悬浮窗中显示（加入分组时的默认显示状态，分组内的显示状态见 GroupMember）
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    /// 持仓信息（未持有时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holding: Option<Holding>,
+    /// 单只股票的显示与刷新设置（未设置的项使用全局设置）
+    #[serde(default, skip_serializing_if = "StockOverrides::is_default")]
+    pub overrides: StockOverrides,
+}
+
+/// 单只股票的显示与刷新设置，全部为可选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StockOverrides {
+    /// 价格小数位数（ETF、可转债为 3），未设置时为 2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    /// 上涨颜色，未设置时使用 app.up_color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up_color: Option<String>,
+    /// 下跌颜色，未设置时使用 app.down_color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub down_color: Option<String>,
+    /// 置顶：固定显示在悬浮窗股票行的最上方，不参与轮播和快捷键滚动
+    #[serde(default)]
+    pub pinned: bool,
+    /// 备注，悬停在悬浮窗中该股票上时显示
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    /// 隐藏价格，只显示涨跌幅
+    #[serde(default)]
+    pub percent_only: bool,
+    /// 刷新优先级
+    #[serde(default)]
+    pub priority: RefreshPriority,
+}
+
+impl StockOverrides {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 单只股票的刷新优先级
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshPriority {
+    /// 系统空闲降频期间仍按 refresh_interval_ms 刷新
+    High,
+    /// 按 refresh_interval_ms 刷新，空闲时降频
+    #[default]
+    Normal,
+    /// 每 LOW_PRIORITY_EVERY 次刷新请求一次（如只偶尔关注的股票）
+    Low,
+}
+
+/// 自选股分组，如 "持仓"、"观察"；引用 Config.stocks 中的股票，每个分组有自己的顺序和显示状态
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchGroup {
+    /// 分组 ID，默认分组为 "default"
+    pub id: String,
+    pub name: String,
+    /// 分组内的股票（按显示顺序）
+    #[serde(default)]
+    pub members: Vec<GroupMember>,
+}
+
+/// 分组中的一只股票
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroupMember {
+    /// 股票 ID，对应 Stock.id
+    pub id: String,
     /// 是否在悬浮窗中显示
+    #[serde(default = "default_true")]
     pub visible: bool,
 }
 
+/// 持仓信息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Holding {
+    /// 持股数量
+    pub shares: f64,
+    /// 持仓均价（成本价）
+    pub avg_cost: f64,
+    /// 建仓日期；当日建仓的持仓按成本价计算当日盈亏
+    #[serde(default)]
+    pub open_date: Option<NaiveDate>,
+}
+
 /// 实时行情数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceUpdate {
@@ -42,10 +129,14 @@ pub struct PriceUpdate {
     pub timestamp: u64,
     /// 数据源标识
     pub source: String,
+    /// 是否为上次退出时保存的旧数据（启动后首次刷新前为 true）
+    #[serde(default)]
+    pub stale: bool,
 }
 
 /// 窗口配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct WindowConfig {
     pub always_on_top: bool,
     pub opacity: f64,
@@ -56,22 +147,60 @@ pub struct WindowConfig {
     pub display_rows: u32,
     pub enable_carousel: bool,      // 是否启用自动轮播
     pub carousel_interval_ms: u64,  // 轮播间隔（毫秒）
+    pub show_portfolio: bool,       // 是否显示持仓当日盈亏汇总行
+    pub show_market_summary: bool,  // 是否显示大盘概况行（同时启用指数与涨跌家数获取）
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            always_on_top: true,
+            opacity: 0.85,
+            position: Position::default(),
+            size: Size::default(),
+            hide_in_taskbar: true,
+            click_through: false,
+            display_rows: 1,
+            enable_carousel: false,        // 默认禁用轮播
+            carousel_interval_ms: 5000,    // 默认5秒
+            show_portfolio: false,
+            show_market_summary: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
+impl Default for Position {
+    fn default() -> Self {
+        Self { x: 100, y: 100 }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Size {
     pub width: u32,
     pub height: u32,
 }
 
+impl Default for Size {
+    fn default() -> Self {
+        Self {
+            width: 300,
+            height: 80,
+        }
+    }
+}
+
 /// 应用配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub theme: String,
     pub up_color: String,
@@ -82,66 +211,198 @@ pub struct AppConfig {
     pub pause_when_hidden: bool,
     pub autostart: bool,
     pub data_sources: Vec<String>,
+    /// 系统空闲（或锁屏）时降低刷新频率
+    pub idle_slowdown: bool,
+    /// 无键鼠输入多少秒后视为空闲
+    pub idle_threshold_secs: u64,
+    /// 空闲期间的刷新间隔
+    pub idle_refresh_interval_ms: u64,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            theme: "auto".to_string(),
+            up_color: "#ff0000".to_string(),
+            down_color: "#00b300".to_string(),
+            neutral_color: "#888888".to_string(),
+            font_size: "medium".to_string(),
+            refresh_interval_ms: 3000,
+            pause_when_hidden: false,
+            autostart: false,
+            data_sources: vec![
+                "sina".to_string(),
+                "tencent".to_string(),
+                "eastmoney".to_string(),
+            ],
+            idle_slowdown: false,
+            idle_threshold_secs: 300,
+            idle_refresh_interval_ms: 30000,
+        }
+    }
 }
 
 /// 快捷键配置
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct ShortcutConfig {
     pub toggle_visible: String,
     pub next_stock: String,
     pub prev_stock: String,
     pub open_settings: String,  // 打开设置窗口（用于鼠标穿透后无法通过右键菜单进入设置的情况）
+    /// 打开当前首行股票的详情窗口
+    pub open_detail: String,
+    /// 切换到下一个自选股分组
+    pub cycle_group: String,
+    /// 切换到下一个配置方案
+    pub cycle_profile: String,
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        Self {
+            toggle_visible: "CommandOrControl+Shift+S".to_string(),
+            next_stock: "Alt+J".to_string(),
+            prev_stock: "Alt+K".to_string(),
+            open_settings: "CommandOrControl+Shift+O".to_string(),  // 打开设置
+            open_detail: "CommandOrControl+Shift+D".to_string(),
+            cycle_group: "CommandOrControl+Shift+G".to_string(),
+            cycle_profile: "CommandOrControl+Shift+P".to_string(),
+        }
+    }
+}
+
+/// 持仓成本计算方法
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CostMethod {
+    /// 先进先出：卖出时按买入批次顺序结转成本
+    Fifo,
+    /// 移动加权平均
+    #[default]
+    WeightedAverage,
+}
+
+/// 交易费用标准（未手动填写费用时按此计算）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FeeSchedule {
+    /// 佣金费率（双向）
+    pub commission_rate: f64,
+    /// 最低佣金（元）
+    pub min_commission: f64,
+    /// 印花税率（仅卖出）
+    pub stamp_duty_rate: f64,
+    /// 过户费率（双向）
+    pub transfer_fee_rate: f64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            commission_rate: 0.00025,
+            min_commission: 5.0,
+            stamp_duty_rate: 0.0005,
+            transfer_fee_rate: 0.00001,
+        }
+    }
+}
+
+/// 持仓核算配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PortfolioConfig {
+    #[serde(default)]
+    pub cost_method: CostMethod,
+    #[serde(default)]
+    pub fees: FeeSchedule,
+}
+
+/// 本地行情 API 配置（仅监听 127.0.0.1）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 17380,
+        }
+    }
+}
+
+/// 本地行情存储配置（SQLite，位于配置目录）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StorageConfig {
+    /// 是否记录逐笔行情，默认关闭
+    #[serde(default)]
+    pub enabled: bool,
+    /// 逐笔行情保留天数
+    #[serde(default = "default_tick_retention_days")]
+    pub tick_retention_days: u32,
+    /// 1 分钟 K 线保留天数，0 表示永久保留
+    #[serde(default)]
+    pub bar_retention_days: u32,
+}
+
+fn default_tick_retention_days() -> u32 {
+    7
 }
 
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tick_retention_days: default_tick_retention_days(),
+            bar_retention_days: 0,
+        }
+    }
+}
+
+/// 当前配置结构版本，结构变化需要迁移时递增（见 migrate 模块）
+pub const CONFIG_VERSION: u32 = 3;
+
+/// 默认配置方案名称
+pub const DEFAULT_PROFILE: &str = "默认";
+
 /// 完整应用配置
+/// 所有字段缺失时取默认值，新增字段不会导致旧配置解析失败
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub version: u32,
+    /// 当前配置方案名称；其他方案保存在配置目录下的 profiles 目录（见 ConfigStore::switch_profile）
+    pub profile: String,
     pub window: WindowConfig,
     pub app: AppConfig,
     pub shortcuts: ShortcutConfig,
+    /// 自选股；缺失时为空列表（而不是默认示例股票）
+    #[serde(default)]
     pub stocks: Vec<Stock>,
+    /// 自选股分组；每只股票至少属于一个分组，缺失时加载后自动生成默认分组（见 groups 模块）
+    #[serde(default)]
+    pub groups: Vec<WatchGroup>,
+    /// 当前分组 ID，悬浮窗只显示该分组的股票；缺失时为空，加载后切换到第一个分组
+    #[serde(default)]
+    pub active_group: String,
+    pub api: ApiConfig,
+    pub portfolio: PortfolioConfig,
+    pub storage: StorageConfig,
+    /// 预警规则
+    pub alerts: Vec<AlertRule>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            version: 1,
-            window: WindowConfig {
-                always_on_top: true,
-                opacity: 0.85,
-                position: Position { x: 100, y: 100 },
-                size: Size {
-                    width: 300,
-                    height: 80,
-                },
-                hide_in_taskbar: true,
-                click_through: false,
-                display_rows: 1,
-                enable_carousel: false,        // 默认禁用轮播
-                carousel_interval_ms: 5000,    // 默认5秒
-            },
-            app: AppConfig {
-                theme: "auto".to_string(),
-                up_color: "#ff0000".to_string(),
-                down_color: "#00b300".to_string(),
-                neutral_color: "#888888".to_string(),
-                font_size: "medium".to_string(),
-                refresh_interval_ms: 3000,
-                pause_when_hidden: false,
-                autostart: false,
-                data_sources: vec![
-                    "sina".to_string(),
-                    "tencent".to_string(),
-                    "eastmoney".to_string(),
-                ],
-            },
-            shortcuts: ShortcutConfig {
-                toggle_visible: "CommandOrControl+Shift+S".to_string(),
-                next_stock: "Alt+J".to_string(),
-                prev_stock: "Alt+K".to_string(),
-                open_settings: "CommandOrControl+Shift+O".to_string(),  // 打开设置
-            },
+            version: CONFIG_VERSION,
+            profile: DEFAULT_PROFILE.to_string(),
+            window: WindowConfig::default(),
+            app: AppConfig::default(),
+            shortcuts: ShortcutConfig::default(),
             stocks: vec![
                 Stock {
                     id: "sh600519".to_string(),
@@ -149,12 +410,172 @@ impl Default for Config {
                     market: "sh".to_string(),
                     alias: "茅台".to_string(),
                     visible: true,
+                    holding: None,
+                    overrides: StockOverrides::default(),
                 },
             ],
+            groups: vec![WatchGroup {
+                id: crate::groups::DEFAULT_GROUP_ID.to_string(),
+                name: crate::groups::DEFAULT_GROUP_NAME.to_string(),
+                members: vec![GroupMember {
+                    id: "sh600519".to_string(),
+                    visible: true,
+                }],
+            }],
+            active_group: crate::groups::DEFAULT_GROUP_ID.to_string(),
+            api: ApiConfig::default(),
+            portfolio: PortfolioConfig::default(),
+            storage: StorageConfig::default(),
+            alerts: Vec::new(),
         }
     }
 }
 
+/// 配置备份（位于配置目录下的 backups 目录）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBackup {
+    /// 文件名，如 config-20240603-153000.json
+    pub name: String,
+    /// 备份时间（毫秒）
+    pub timestamp: u64,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+/// 自选股编辑的撤销 / 重做状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct UndoState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+/// 单只持仓的盈亏
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionPnl {
+    pub id: String,
+    pub name: String,
+    pub shares: f64,
+    pub avg_cost: f64,
+    pub price: f64,
+    /// 市值
+    pub market_value: f64,
+    /// 持仓成本
+    pub cost_basis: f64,
+    /// 当日盈亏
+    pub daily_pnl: f64,
+    /// 浮动盈亏（市值 - 成本）
+    pub unrealized_pnl: f64,
+    /// 浮动盈亏比例（小数）
+    pub unrealized_percent: f64,
+}
+
+/// 组合盈亏汇总（portfolio-update 事件）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortfolioUpdate {
+    pub positions: Vec<PositionPnl>,
+    pub market_value: f64,
+    pub cost_basis: f64,
+    pub daily_pnl: f64,
+    /// 当日盈亏比例（相对昨日市值，小数）
+    pub daily_percent: f64,
+    pub unrealized_pnl: f64,
+    pub unrealized_percent: f64,
+    /// 时间戳（毫秒）
+    pub timestamp: u64,
+}
+
+/// 交易类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    Buy,
+    Sell,
+    /// 现金分红（amount 为到账金额）
+    Dividend,
+    /// 送转/拆股（ratio 为每 1 股变为多少股，如 10 送 5 为 1.5）
+    Split,
+}
+
+/// 交易记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Transaction {
+    pub id: String,
+    /// 股票 ID，如 "sh600519"
+    pub stock_id: String,
+    pub kind: TransactionKind,
+    pub date: NaiveDate,
+    /// 成交数量（买入/卖出）
+    #[serde(default)]
+    pub shares: f64,
+    /// 成交价格（买入/卖出）
+    #[serde(default)]
+    pub price: f64,
+    /// 分红到账金额
+    #[serde(default)]
+    pub amount: f64,
+    /// 送转比例
+    #[serde(default)]
+    pub ratio: f64,
+    /// 佣金
+    #[serde(default)]
+    pub commission: f64,
+    /// 印花税
+    #[serde(default)]
+    pub stamp_duty: f64,
+    /// 过户费
+    #[serde(default)]
+    pub transfer_fee: f64,
+    #[serde(default)]
+    pub note: String,
+    /// 导入来源标识（如券商合同编号），用于重复导入去重；手动录入为空
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source_ref: String,
+}
+
+/// 新增交易的输入；费用留空时按 FeeSchedule 计算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewTransaction {
+    /// 股票代码，支持 "600519" / "sh600519"
+    pub code: String,
+    pub kind: TransactionKind,
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub shares: f64,
+    #[serde(default)]
+    pub price: f64,
+    #[serde(default)]
+    pub amount: f64,
+    #[serde(default)]
+    pub ratio: f64,
+    #[serde(default)]
+    pub commission: Option<f64>,
+    #[serde(default)]
+    pub stamp_duty: Option<f64>,
+    #[serde(default)]
+    pub transfer_fee: Option<f64>,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub source_ref: String,
+}
+
+/// 由交易记录推导出的持仓
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DerivedPosition {
+    pub stock_id: String,
+    pub shares: f64,
+    /// 持仓均价（含买入费用）
+    pub avg_cost: f64,
+    /// 持仓成本
+    pub cost_basis: f64,
+    /// 已实现盈亏（卖出差价 + 分红，已扣除费用）
+    pub realized_pnl: f64,
+    /// 累计交易费用
+    pub total_fees: f64,
+    /// 当前持仓的建仓日期（清仓后为空）
+    pub open_date: Option<NaiveDate>,
+}
+
 /// 数据源切换通知
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceSwitchedEvent {
@@ -168,3 +589,425 @@ pub struct ErrorEvent {
     pub code: String,
     pub message: String,
 }
+
+/// 券商对账单格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Broker {
+    /// 华泰证券（涨乐财富通交割单）
+    Huatai,
+    /// 中信证券
+    Citic,
+    /// 东方财富证券
+    Eastmoney,
+    /// 其他券商，按通用列名识别
+    Generic,
+}
+
+/// 导入行的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    /// 将被导入
+    New,
+    /// 已导入过（同一来源标识），跳过
+    Duplicate,
+    /// 非交易行或无法识别，跳过
+    Skipped(String),
+}
+
+/// 对账单中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRow {
+    /// 文件中的行号（从 1 开始）
+    pub line: usize,
+    pub name: String,
+    #[serde(flatten)]
+    pub status: ImportRowStatus,
+    pub transaction: Option<NewTransaction>,
+}
+
+/// 对账单导入预览 / 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub broker: Broker,
+    pub rows: Vec<ImportRow>,
+    pub new_count: usize,
+    pub duplicate_count: usize,
+    pub skipped_count: usize,
+    /// 追加后流水不一致（如卖出早于对账单起始日期前的持仓）时的错误信息
+    pub error: Option<String>,
+}
+
+/// 自选股导入导出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchlistFormat {
+    /// [{"code": "sh600519", "alias": "茅台", "visible": true}, ...]
+    Json,
+    /// code,alias,visible 三列，带表头
+    Csv,
+    /// 每行一个代码，可带 sh/sz 前缀或 .SH/.SZ 后缀
+    Codes,
+}
+
+/// 交易软件导出的自选股格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingSoftware {
+    /// 通达信板块文件 zxg.blk，每行市场位 + 代码，如 1600519、0000001
+    Tdx,
+    /// 同花顺导出的 TXT/CSV，代码带 SH/SZ 前缀
+    Ths,
+    /// 东方财富导出的 TXT/CSV
+    Eastmoney,
+}
+
+/// 自选股导入方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// 追加到当前分组，已有的股票保持不变
+    Merge,
+    /// 用导入的列表替换当前分组
+    Replace,
+}
+
+/// 导入冲突类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchlistConflictKind {
+    /// 文件中重复出现，只导入第一次
+    Duplicate,
+    /// 已在当前分组中（合并时保持原样）
+    Existing,
+    /// 无法识别的代码
+    Invalid,
+}
+
+/// 导入时跳过的条目
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchlistConflict {
+    /// 文件中的行号（JSON 为数组下标 + 1）
+    pub line: usize,
+    /// 原始代码
+    pub entry: String,
+    /// 识别出的股票 ID（无法识别时为空）
+    pub id: Option<String>,
+    pub kind: WatchlistConflictKind,
+}
+
+/// 自选股导入预览 / 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistImport {
+    pub format: WatchlistFormat,
+    /// 从交易软件导出文件导入时为文件来源
+    #[serde(default)]
+    pub software: Option<TradingSoftware>,
+    pub mode: ImportMode,
+    /// 新加入当前分组的股票 ID
+    pub added: Vec<String>,
+    /// 替换时移出当前分组的股票 ID
+    pub removed: Vec<String>,
+    pub conflicts: Vec<WatchlistConflict>,
+}
+
+/// 单只股票的收盘记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EodQuote {
+    pub id: String,
+    pub name: String,
+    pub close: f64,
+    pub prev_close: f64,
+    pub change: f64,
+    /// 涨跌幅（小数）
+    pub percent: f64,
+}
+
+/// 某个交易日的收盘快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailySnapshot {
+    pub date: NaiveDate,
+    pub quotes: Vec<EodQuote>,
+    /// 当日组合盈亏（无持仓时为空）
+    #[serde(default)]
+    pub portfolio: Option<PortfolioUpdate>,
+}
+
+/// K 线周期
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BarInterval {
+    #[serde(rename = "1m")]
+    Min1,
+    #[serde(rename = "5m")]
+    Min5,
+    #[serde(rename = "15m")]
+    Min15,
+    #[serde(rename = "30m")]
+    Min30,
+    #[serde(rename = "60m")]
+    Min60,
+    #[serde(rename = "1d")]
+    Day,
+}
+
+/// K 线（由本地记录的行情聚合，暂无成交量）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bar {
+    /// 周期起始时间（毫秒）
+    pub timestamp: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// 一条记录的行情
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Tick {
+    pub timestamp: u64,
+    pub price: f64,
+    pub prev_close: f64,
+}
+
+/// 分时数据中的一分钟
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IntradayPoint {
+    /// 时间 "HH:MM"
+    pub time: String,
+    pub price: f64,
+    /// 截至该分钟的成交均价
+    pub avg_price: f64,
+    /// 该分钟成交量（股）
+    pub volume: f64,
+}
+
+/// 当日分时数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntradayData {
+    pub id: String,
+    pub date: NaiveDate,
+    pub prev_close: f64,
+    pub points: Vec<IntradayPoint>,
+    pub source: String,
+}
+
+/// 沪深两市涨跌统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MarketBreadth {
+    /// 上涨家数
+    pub advancers: u32,
+    /// 下跌家数
+    pub decliners: u32,
+    /// 平盘家数
+    pub unchanged: u32,
+    /// 涨停家数
+    pub limit_up: u32,
+    /// 跌停家数
+    pub limit_down: u32,
+    /// 两市成交额（元）
+    pub turnover: f64,
+}
+
+/// 大盘概况（market-summary 事件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketSummary {
+    /// 主要指数行情，按固定顺序排列，名称为简称
+    pub indices: Vec<PriceUpdate>,
+    /// 涨跌统计（获取失败或尚未获取时为空）
+    pub breadth: Option<MarketBreadth>,
+    pub timestamp: u64,
+}
+
+/// 盘口档位
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DepthLevel {
+    pub price: f64,
+    /// 挂单量（股）
+    pub volume: f64,
+}
+
+/// 个股详情：五档盘口与关键统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockDetail {
+    pub quote: PriceUpdate,
+    /// 今开
+    pub open: f64,
+    /// 成交量（股）
+    pub volume: f64,
+    /// 成交额（元）
+    pub amount: f64,
+    /// 换手率（小数）
+    pub turnover_rate: Option<f64>,
+    /// 市盈率（动态）
+    pub pe: Option<f64>,
+    /// 市净率
+    pub pb: Option<f64>,
+    /// 振幅（小数）
+    pub amplitude: Option<f64>,
+    /// 流通市值（元）
+    pub float_market_cap: Option<f64>,
+    /// 总市值（元）
+    pub market_cap: Option<f64>,
+    pub limit_up: Option<f64>,
+    pub limit_down: Option<f64>,
+    /// 买一至买五
+    pub bids: Vec<DepthLevel>,
+    /// 卖一至卖五
+    pub asks: Vec<DepthLevel>,
+}
+
+/// 历史 K 线周期
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum KlinePeriod {
+    Day,
+    Week,
+    Month,
+}
+
+/// 复权方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Adjust {
+    /// 不复权
+    None,
+    /// 前复权
+    #[default]
+    Forward,
+    /// 后复权
+    Backward,
+}
+
+/// 历史 K 线
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Kline {
+    /// 周期最后一个交易日
+    pub date: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// 成交量（股）
+    pub volume: f64,
+    /// 成交额（元）
+    pub amount: f64,
+}
+
+/// 技术指标及参数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndicatorSpec {
+    /// 简单移动平均，输出 ma
+    Ma { period: usize },
+    /// 指数移动平均，输出 ema
+    Ema { period: usize },
+    /// 输出 dif / dea / macd（柱，2 × (dif - dea)）
+    Macd { fast: usize, slow: usize, signal: usize },
+    /// 输出 rsi
+    Rsi { period: usize },
+    /// 输出 k / d / j
+    Kdj { n: usize, m1: usize, m2: usize },
+    /// 输出 mid / upper / lower
+    Boll { period: usize, k: f64 },
+    /// 成交量加权均价，输出 vwap
+    Vwap,
+}
+
+/// 指标计算所用的 K 线来源
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum IndicatorInterval {
+    /// 当日分时
+    #[serde(rename = "intraday")]
+    Intraday,
+    /// 本地记录的分钟 K 线
+    #[serde(rename = "1m")]
+    Min1,
+    #[serde(rename = "5m")]
+    Min5,
+    #[serde(rename = "15m")]
+    Min15,
+    #[serde(rename = "30m")]
+    Min30,
+    #[serde(rename = "60m")]
+    Min60,
+    /// 历史 K 线（前复权）
+    #[default]
+    #[serde(rename = "day")]
+    Day,
+    #[serde(rename = "week")]
+    Week,
+    #[serde(rename = "month")]
+    Month,
+}
+
+/// 一个指标的计算结果，每条线与 labels 一一对应，数据不足处为 null
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorSeries {
+    pub spec: IndicatorSpec,
+    pub lines: std::collections::BTreeMap<String, Vec<Option<f64>>>,
+}
+
+/// get_indicators 返回结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorResult {
+    pub id: String,
+    pub interval: IndicatorInterval,
+    /// 每根 K 线的时间标签（日期或 "HH:MM"）
+    pub labels: Vec<String>,
+    pub close: Vec<f64>,
+    pub series: Vec<IndicatorSeries>,
+}
+
+/// 预警条件中的取值
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Operand {
+    /// 收盘价（最后一根为最新价）
+    Close,
+    /// 固定数值
+    Value { value: f64 },
+    /// 指标的某条线，如 MA20 的 "ma"
+    Indicator { spec: IndicatorSpec, line: String },
+}
+
+/// 预警条件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    Above { left: Operand, right: Operand },
+    Below { left: Operand, right: Operand },
+    /// 上穿：上一根 left <= right，当前 left > right
+    CrossAbove { left: Operand, right: Operand },
+    /// 下穿：上一根 left >= right，当前 left < right
+    CrossBelow { left: Operand, right: Operand },
+}
+
+/// 预警规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertRule {
+    pub id: String,
+    pub stock_id: String,
+    #[serde(default)]
+    pub interval: IndicatorInterval,
+    pub condition: AlertCondition,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub note: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 预警触发通知（alert-triggered 事件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertTriggered {
+    pub rule_id: String,
+    pub stock_id: String,
+    pub name: String,
+    pub message: String,
+    pub price: f64,
+    pub timestamp: u64,
+}
@@ -1,11 +1,19 @@
-use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
-use tokio::time::{interval, Duration};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, atomic::{AtomicBool, Ordering}};
+use tokio::time::{interval, Duration, Instant};
 use tauri::{AppHandle, Emitter};
-use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+use anyhow::{Context, Result};
 
-use crate::config::ConfigStore;
-use crate::models::PriceUpdate;
-use crate::sources::{DataSource, sina::SinaSource, tencent::TencentSource, eastmoney::EastmoneySource};
+use crate::calendar;
+use crate::config::{self, ConfigStore};
+use crate::groups;
+use crate::market;
+use crate::models::{MarketBreadth, MarketSummary, PriceUpdate, RefreshPriority, Stock};
+use crate::portfolio;
+use crate::sources::{self, DataSource, eastmoney::EastmoneySource, sina::SinaSource};
 
 /// 数据源管理器（含故障转移）
 struct SourceManager {
@@ -19,11 +27,9 @@ impl SourceManager {
     fn new(source_names: &[String]) -> Self {
         let mut sources: Vec<Box<dyn DataSource>> = Vec::new();
         for name in source_names {
-            match name.as_str() {
-                "sina" => sources.push(Box::new(SinaSource::new())),
-                "tencent" => sources.push(Box::new(TencentSource::new())),
-                "eastmoney" => sources.push(Box::new(EastmoneySource::new())),
-                _ => log::warn!("未知数据源: {}", name),
+            match sources::create_source(name) {
+                Some(source) => sources.push(source),
+                None => log::warn!("未知数据源: {}", name),
             }
         }
         if sources.is_empty() {
@@ -90,117 +96,453 @@ impl SourceManager {
     }
 }
 
+/// 最新行情快照（id -> 行情），由轮询器维护，供命令和本地 API 读取
+pub type QuoteSnapshot = Arc<RwLock<HashMap<String, PriceUpdate>>>;
+
+/// 最新大盘概况，由轮询器维护（未启用大盘概况时为空）
+pub type MarketSnapshot = Arc<RwLock<Option<MarketSummary>>>;
+
+/// 空闲状态的检测间隔（macOS/Linux 需要启动外部进程，避免每个 tick 都查询）
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 每隔多少次成功刷新推送一次完整的 price-update（关键帧），其余只推送 price-delta
+const KEYFRAME_EVERY: u32 = 20;
+
+/// 低优先级股票每隔多少轮刷新请求一次
+const LOW_PRIORITY_EVERY: u64 = 5;
+
+/// 本轮是否请求该股票：空闲降频等待期间只请求高优先级股票，低优先级股票每 LOW_PRIORITY_EVERY 轮
+/// 及关键帧时请求
+fn due_this_round(stock: &Stock, round: u64, idle_wait: bool, keyframe: bool) -> bool {
+    match stock.overrides.priority {
+        RefreshPriority::High => true,
+        _ if idle_wait => false,
+        RefreshPriority::Normal => true,
+        RefreshPriority::Low => keyframe || round.is_multiple_of(LOW_PRIORITY_EVERY),
+    }
+}
+
+/// 判断行情是否有实质变化（忽略时间戳和数据源）
+fn quote_changed(prev: &PriceUpdate, next: &PriceUpdate) -> bool {
+    prev.stale != next.stale
+        || prev.price != next.price
+        || prev.prev_close != next.prev_close
+        || prev.change != next.change
+        || prev.percent != next.percent
+        || prev.high != next.high
+        || prev.low != next.low
+        || prev.name != next.name
+}
+
+/// 关键帧内容：按自选顺序取快照中所有关注股票的行情，包括本轮未请求的低优先级股票
+fn keyframe_quotes(snapshot: &HashMap<String, PriceUpdate>, ids: &[String]) -> Vec<PriceUpdate> {
+    ids.iter().filter_map(|id| snapshot.get(id).cloned()).collect()
+}
+
+/// 与上一次快照对比，返回发生变化（或新出现）的行情
+fn diff_quotes(snapshot: &HashMap<String, PriceUpdate>, updates: &[PriceUpdate]) -> Vec<PriceUpdate> {
+    updates
+        .iter()
+        .filter(|u| match snapshot.get(&u.id) {
+            Some(prev) => quote_changed(prev, u),
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn snapshot_file_path() -> Result<PathBuf> {
+    Ok(config::get_config_dir()?.join("quotes.json"))
+}
+
+/// 正在运行的轮询任务
+struct PollerTask {
+    cancel: CancellationToken,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
 /// 轮询器状态
 pub struct Poller {
     is_paused: Arc<AtomicBool>,
-    is_running: Arc<AtomicBool>,
+    quotes: QuoteSnapshot,
+    market: MarketSnapshot,
+    task: Mutex<Option<PollerTask>>,
 }
 
 impl Poller {
     pub fn new() -> Self {
         Self {
             is_paused: Arc::new(AtomicBool::new(false)),
-            is_running: Arc::new(AtomicBool::new(false)),
+            quotes: Arc::new(RwLock::new(HashMap::new())),
+            market: Arc::new(RwLock::new(None)),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 获取最新行情快照的副本
+    pub fn quotes(&self) -> HashMap<String, PriceUpdate> {
+        self.quotes.read().unwrap().clone()
+    }
+
+    /// 获取共享的行情快照（供本地 API 等模块直接读取）
+    pub fn shared_quotes(&self) -> QuoteSnapshot {
+        self.quotes.clone()
+    }
+
+    /// 获取最新的大盘概况
+    pub fn market_summary(&self) -> Option<MarketSummary> {
+        self.market.read().unwrap().clone()
+    }
+
+    /// 从磁盘恢复上次退出时的行情快照，恢复的数据标记为 stale
+    pub fn restore_snapshot(&self) -> Result<()> {
+        self.restore_snapshot_from(&snapshot_file_path()?)
+    }
+
+    fn restore_snapshot_from(&self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(path).context("读取行情快照失败")?;
+        let mut restored: HashMap<String, PriceUpdate> =
+            serde_json::from_str(&content).context("解析行情快照失败")?;
+        for update in restored.values_mut() {
+            update.stale = true;
+        }
+
+        let mut quotes = self.quotes.write().unwrap();
+        for (id, update) in restored {
+            // 已有新数据时不覆盖
+            quotes.entry(id).or_insert(update);
         }
+        log::info!("已恢复 {} 条行情快照", quotes.len());
+        Ok(())
+    }
+
+    /// 将当前行情快照写入磁盘（退出时调用）
+    pub fn persist_snapshot(&self) -> Result<()> {
+        self.persist_snapshot_to(&snapshot_file_path()?)
+    }
+
+    fn persist_snapshot_to(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string(&*self.quotes.read().unwrap())
+            .context("序列化行情快照失败")?;
+        config::atomic_write(path, content.as_bytes()).context("写入行情快照失败")?;
+        Ok(())
     }
 
     /// 设置暂停状态
     pub fn set_paused(&self, paused: bool) {
-        self.is_paused.store(paused, Ordering::Relaxed);
+        if self.is_paused.swap(paused, Ordering::Relaxed) != paused {
+            log::info!("轮询器{}", if paused { "已暂停（窗口隐藏）" } else { "已恢复" });
+        }
     }
 
-    /// 启动轮询任务
+    /// 启动轮询任务（已在运行时忽略）
     pub fn start(&self, app_handle: AppHandle, config_store: Arc<ConfigStore>) {
-        if self.is_running.load(Ordering::Relaxed) {
+        let is_paused = self.is_paused.clone();
+        let quotes = self.quotes.clone();
+        let market = self.market.clone();
+        self.launch(move |cancel| run(app_handle, config_store, is_paused, quotes, market, cancel));
+    }
+
+    /// 启动任务；上一个任务已停止但尚未退出时，新任务先等待它退出，避免新旧循环同时推送
+    fn launch<F, Fut>(&self, make_task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut task = self.task.lock().unwrap();
+        if task.as_ref().is_some_and(|t| !t.cancel.is_cancelled()) {
             return;
         }
-        self.is_running.store(true, Ordering::Relaxed);
 
-        let is_paused = self.is_paused.clone();
-        let is_running = self.is_running.clone();
+        let previous = task.take();
+        let cancel = CancellationToken::new();
+        let future = make_task(cancel.clone());
+        let token = cancel.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            if let Some(previous) = previous {
+                if let Err(e) = previous.handle.await {
+                    log::warn!("轮询任务退出异常: {}", e);
+                }
+            }
+            // 等待期间又被停止时不再启动
+            if !token.is_cancelled() {
+                future.await;
+            }
+        });
+        *task = Some(PollerTask { cancel, handle });
+    }
+
+    /// 停止轮询（不等待任务退出，下次启动时会先等待它退出）
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap().as_ref() {
+            task.cancel.cancel();
+        }
+    }
+
+    /// 停止并重新启动轮询，用于刷新间隔或数据源变更后立即生效
+    /// 新的轮询循环在旧循环退出后才开始
+    pub fn restart(&self, app_handle: AppHandle, config_store: Arc<ConfigStore>) {
+        log::info!("轮询配置变更，重启轮询任务");
+        self.stop();
+        self.start(app_handle, config_store);
+    }
+
+    /// 停止轮询并等待任务退出（应用退出时调用）
+    pub async fn shutdown(&self) {
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            task.cancel.cancel();
+            if let Err(e) = task.handle.await {
+                log::warn!("轮询任务退出异常: {}", e);
+            }
+        }
+    }
+}
 
-        tauri::async_runtime::spawn(async move {
-            let mut config = config_store.get();
-            let mut current_sources = config.app.data_sources.clone();
-            let mut source_manager = SourceManager::new(&current_sources);
-            let mut tick_interval = interval(Duration::from_millis(config.app.refresh_interval_ms));
+/// 轮询主循环，收到取消信号后立即退出（包括正在等待网络请求时）
+async fn run(
+    app_handle: AppHandle,
+    config_store: Arc<ConfigStore>,
+    is_paused: Arc<AtomicBool>,
+    quotes: QuoteSnapshot,
+    market: MarketSnapshot,
+    cancel: CancellationToken,
+) {
+    let mut config = config_store.get();
+    let mut source_manager = SourceManager::new(&config.app.data_sources);
+    let mut tick_interval = interval(Duration::from_millis(config.app.refresh_interval_ms));
+    // 首次刷新和自选股列表变化时强制推送关键帧
+    let mut ticks_since_keyframe = 0u32;
+    let mut last_ids: Vec<String> = Vec::new();
+    // 空闲降频：记录上次请求时间和空闲检测结果
+    let mut last_fetch: Option<Instant> = None;
+    let mut last_idle_check: Option<Instant> = None;
+    let mut user_idle = false;
+    // 涨跌统计：只有东财支持，在单独的任务中低频获取，不拖慢行情刷新
+    let breadth: Arc<RwLock<Option<MarketBreadth>>> = Arc::new(RwLock::new(None));
+    let mut breadth_task: Option<tokio::task::JoinHandle<()>> = None;
+    let mut last_breadth_fetch: Option<Instant> = None;
+    // 刷新轮次（不含空闲降频期间只刷新高优先级股票的轮次），用于低优先级股票
+    let mut round = 0u64;
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tick_interval.tick() => {}
+        }
 
-            loop {
-                tick_interval.tick().await;
+        if is_paused.load(Ordering::Relaxed) {
+            continue;
+        }
 
-                if !is_running.load(Ordering::Relaxed) {
-                    break;
+        config = config_store.get();
+
+        // 系统空闲时按 idle_refresh_interval_ms 降频，两次降频刷新之间只刷新高优先级股票
+        let mut idle_wait = false;
+        if config.app.idle_slowdown {
+            if last_idle_check.is_none_or(|t| t.elapsed() >= IDLE_CHECK_INTERVAL) {
+                last_idle_check = Some(Instant::now());
+                let threshold = Duration::from_secs(config.app.idle_threshold_secs);
+                let idle_now = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    idle = crate::idle::idle_duration() => idle.is_some_and(|d| d >= threshold),
+                };
+                if idle_now != user_idle {
+                    if idle_now {
+                        log::info!("系统空闲，降低刷新频率");
+                    } else {
+                        log::info!("系统恢复活动，恢复刷新频率");
+                    }
+                    user_idle = idle_now;
                 }
+            }
+            if user_idle {
+                let idle_interval = Duration::from_millis(config.app.idle_refresh_interval_ms);
+                idle_wait = last_fetch.is_some_and(|t| t.elapsed() < idle_interval);
+            }
+        } else {
+            user_idle = false;
+        }
 
-                if is_paused.load(Ordering::Relaxed) {
-                    continue;
+        // 收集要请求的股票列表：当前分组中显示的股票，以及其他分组中有预警规则或持仓（用于盈亏汇总）的股票
+        let shown: Vec<String> = groups::active_stocks(&config)
+            .into_iter()
+            .filter(|s| s.visible)
+            .map(|s| s.id)
+            .collect();
+        let watched: Vec<&Stock> = config
+            .stocks
+            .iter()
+            .filter(|s| {
+                shown.contains(&s.id)
+                    || s.holding.is_some()
+                    || config.alerts.iter().any(|r| r.enabled && r.stock_id == s.id)
+            })
+            .collect();
+        let ids: Vec<String> = watched.iter().map(|s| s.id.clone()).collect();
+        if ids != last_ids {
+            last_ids = ids;
+            ticks_since_keyframe = 0;
+        }
+        let keyframe = ticks_since_keyframe == 0;
+        if !idle_wait {
+            round += 1;
+        }
+        let mut stocks: Vec<(String, String)> = watched
+            .iter()
+            .filter(|s| due_this_round(s, round, idle_wait, keyframe))
+            .map(|s| (s.market.clone(), s.code.clone()))
+            .collect();
+
+        // 启用大盘概况时，指数随自选股一起请求（空闲降频等待期间跳过，保留上次的大盘概况）
+        let show_market = config.window.show_market_summary;
+        let fetch_market = show_market && !idle_wait;
+        if fetch_market {
+            for index in market::index_codes() {
+                if !stocks.contains(&index) {
+                    stocks.push(index);
                 }
+            }
+            // 非交易时段涨跌统计不再变化，只在还没有数据时获取一次
+            let trading = calendar::market_phase(calendar::china_now()).is_trading();
+            let running = breadth_task.as_ref().is_some_and(|t| !t.is_finished());
+            if !running
+                && (trading || breadth.read().unwrap().is_none())
+                && last_breadth_fetch.is_none_or(|t| t.elapsed() >= market::BREADTH_INTERVAL)
+            {
+                last_breadth_fetch = Some(Instant::now());
+                breadth_task = Some(tokio::spawn(refresh_breadth(breadth.clone(), market.clone())));
+            }
+        } else if !show_market {
+            if let Some(task) = breadth_task.take() {
+                task.abort();
+            }
+            breadth.write().unwrap().take();
+            last_breadth_fetch = None;
+            market.write().unwrap().take();
+        }
 
-                config = config_store.get();
+        if stocks.is_empty() {
+            continue;
+        }
 
-                // 检查数据源配置是否变更
-                if config.app.data_sources != current_sources {
-                    log::info!("数据源配置变更，重建 SourceManager");
-                    current_sources = config.app.data_sources.clone();
-                    source_manager = SourceManager::new(&current_sources);
+        if !idle_wait {
+            last_fetch = Some(Instant::now());
+        }
+        let result = tokio::select! {
+            _ = cancel.cancelled() => break,
+            result = source_manager.fetch(&stocks) => result,
+        };
+        match result {
+            Ok(all_updates) => {
+                if fetch_market {
+                    let breadth = breadth.read().unwrap().clone();
+                    if let Some(summary) = market::build_summary(&all_updates, breadth, now_millis()) {
+                        let _ = app_handle.emit("market-summary", &summary);
+                        *market.write().unwrap() = Some(summary);
+                    }
                 }
-
-                // 收集要请求的股票列表
-                let stocks: Vec<(String, String)> = config
-                    .stocks
-                    .iter()
-                    .filter(|s| s.visible)
-                    .map(|s| (s.market.clone(), s.code.clone()))
+                // 只为自选股推送行情（指数仅用于大盘概况）
+                let updates: Vec<PriceUpdate> = all_updates
+                    .into_iter()
+                    .filter(|u| last_ids.contains(&u.id))
                     .collect();
 
-                if stocks.is_empty() {
-                    continue;
-                }
+                let (changed, frame) = {
+                    let mut snapshot = quotes.write().unwrap();
+                    // 清理已移出自选的股票
+                    snapshot.retain(|id, _| config.stocks.iter().any(|s| &s.id == id));
+                    let changed = diff_quotes(&snapshot, &updates);
+                    for update in &updates {
+                        snapshot.insert(update.id.clone(), update.clone());
+                    }
+                    let frame = if keyframe { keyframe_quotes(&snapshot, &last_ids) } else { Vec::new() };
+                    (changed, frame)
+                };
 
-                match source_manager.fetch(&stocks).await {
-                    Ok(updates) => {
-                        if !updates.is_empty() {
-                            let _ = app_handle.emit("price-update", &updates);
-                        }
+                if keyframe {
+                    if !frame.is_empty() {
+                        let _ = app_handle.emit("price-update", &frame);
                     }
-                    Err(e) => {
-                        let err_msg = e.to_string();
-                        if err_msg.starts_with("source_switched:") {
-                            // 解析数据源切换事件
-                            let parts: Vec<&str> = err_msg.splitn(3, ':').collect();
-                            if parts.len() == 3 {
-                                let _ = app_handle.emit(
-                                    "source-switched",
-                                    serde_json::json!({
-                                        "from": parts[1],
-                                        "to": parts[2]
-                                    }),
-                                );
-                            }
-                        } else {
-                            let _ = app_handle.emit(
-                                "error",
-                                serde_json::json!({
-                                    "code": "FETCH_ERROR",
-                                    "message": format!("数据获取失败: {}", e)
-                                }),
-                            );
-                        }
+                } else if !changed.is_empty() {
+                    let _ = app_handle.emit("price-delta", &changed);
+                }
+                ticks_since_keyframe = (ticks_since_keyframe + 1) % KEYFRAME_EVERY;
+
+                // 持仓盈亏随行情变化推送
+                if keyframe || !changed.is_empty() {
+                    let snapshot = quotes.read().unwrap().clone();
+                    if let Some(portfolio) = portfolio::compute(
+                        &config.stocks,
+                        &snapshot,
+                        portfolio::china_today(),
+                        now_millis(),
+                    ) {
+                        let _ = app_handle.emit("portfolio-update", &portfolio);
                     }
                 }
-
-                // 检查刷新间隔是否变更
-                let new_interval = config_store.get().app.refresh_interval_ms;
-                if new_interval != config.app.refresh_interval_ms {
-                    tick_interval = interval(Duration::from_millis(new_interval));
+            }
+            Err(e) => {
+                let err_msg = e.to_string();
+                if err_msg.starts_with("source_switched:") {
+                    // 解析数据源切换事件
+                    let parts: Vec<&str> = err_msg.splitn(3, ':').collect();
+                    if parts.len() == 3 {
+                        let _ = app_handle.emit(
+                            "source-switched",
+                            serde_json::json!({
+                                "from": parts[1],
+                                "to": parts[2]
+                            }),
+                        );
+                    }
+                } else {
+                    let _ = app_handle.emit(
+                        "error",
+                        serde_json::json!({
+                            "code": "FETCH_ERROR",
+                            "message": format!("数据获取失败: {}", e)
+                        }),
+                    );
                 }
             }
-        });
+        }
     }
+    if let Some(task) = breadth_task {
+        task.abort();
+    }
+    log::info!("轮询任务已退出");
+}
 
-    /// 停止轮询
-    pub fn stop(&self) {
-        self.is_running.store(false, Ordering::Relaxed);
+/// 获取涨跌统计（带超时），成功后保存供之后的大盘概况使用，并更新当前的大盘概况
+async fn refresh_breadth(breadth: Arc<RwLock<Option<MarketBreadth>>>, market: MarketSnapshot) {
+    let source = EastmoneySource::new();
+    let date = calendar::china_now().date();
+    match tokio::time::timeout(market::BREADTH_TIMEOUT, source.fetch_breadth(date)).await {
+        Ok(Ok(result)) => {
+            if let Some(summary) = market.write().unwrap().as_mut() {
+                summary.breadth = Some(result.clone());
+            }
+            *breadth.write().unwrap() = Some(result);
+        }
+        Ok(Err(e)) => log::warn!("获取涨跌统计失败: {}", e),
+        Err(_) => log::warn!("获取涨跌统计超时"),
+    }
+}
+
+impl Default for Poller {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -208,6 +550,7 @@ impl Poller {
 mod tests {
     use super::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::test_support::quote;
 
     struct MockSource {
         name: String,
@@ -230,6 +573,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_diff_quotes() {
+        let mut snapshot = HashMap::new();
+        snapshot.insert("sh600000".to_string(), quote("sh600000", 10.5, 10.0));
+        snapshot.insert("sz000001".to_string(), quote("sz000001", 9.8, 10.0));
+
+        // 只有时间戳/数据源不同视为未变化
+        let mut same = quote("sh600000", 10.5, 10.0);
+        same.timestamp = 1000;
+        same.source = "other".to_string();
+
+        let moved = quote("sz000001", 9.9, 10.0);
+        let new_entry = quote("sh600519", 11.0, 10.0);
+
+        let changed = diff_quotes(&snapshot, &[same, moved, new_entry]);
+        let ids: Vec<&str> = changed.iter().map(|u| u.id.as_str()).collect();
+        assert_eq!(ids, vec!["sz000001", "sh600519"]);
+
+        // 旧数据被刷新（stale -> fresh）也要推送
+        snapshot.get_mut("sh600000").unwrap().stale = true;
+        let changed = diff_quotes(&snapshot, &[quote("sh600000", 10.5, 10.0)]);
+        assert_eq!(changed.len(), 1);
+    }
+
+    #[test]
+    fn test_keyframe_includes_quotes_skipped_this_round() {
+        let mut snapshot = HashMap::new();
+        snapshot.insert("sh600000".to_string(), quote("sh600000", 10.5, 10.0));
+        snapshot.insert("sz000001".to_string(), quote("sz000001", 9.8, 10.0));
+        snapshot.insert("sh601318".to_string(), quote("sh601318", 45.0, 44.0));
+        let ids = ["sz000001".to_string(), "sh600519".to_string(), "sh600000".to_string()];
+
+        // 按自选顺序返回，没有行情的股票和已不关注的股票不返回
+        let frame: Vec<String> = keyframe_quotes(&snapshot, &ids).into_iter().map(|q| q.id).collect();
+        assert_eq!(frame, ["sz000001", "sh600000"]);
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip_marks_stale() {
+        let dir = std::env::temp_dir().join(format!("ssm-poller-snapshot-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("quotes.json");
+
+        let poller = Poller::new();
+        poller.quotes.write().unwrap().insert("sh600000".to_string(), quote("sh600000", 10.5, 10.0));
+        poller.persist_snapshot_to(&path).unwrap();
+        assert!(!dir.join("quotes.json.tmp").exists());
+
+        let restored = Poller::new();
+        // 已有的新数据不被快照覆盖
+        restored.quotes.write().unwrap().insert("sz000001".to_string(), quote("sz000001", 9.8, 10.0));
+        restored.restore_snapshot_from(&path).unwrap();
+        let quotes = restored.quotes();
+        assert_eq!(quotes["sh600000"].price, 10.5);
+        assert!(quotes["sh600000"].stale);
+        assert!(!quotes["sz000001"].stale);
+
+        // 没有快照文件时什么都不做
+        Poller::new().restore_snapshot_from(&dir.join("missing.json")).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_refresh_priority() {
+        let with_priority = |priority| {
+            let mut stock = sources::make_stock("600519");
+            stock.overrides.priority = priority;
+            stock
+        };
+        let (high, normal, low) = (
+            with_priority(RefreshPriority::High),
+            with_priority(RefreshPriority::Normal),
+            with_priority(RefreshPriority::Low),
+        );
+        let due = |stock: &Stock, idle_wait, keyframe| -> Vec<bool> {
+            (1..=LOW_PRIORITY_EVERY).map(|round| due_this_round(stock, round, idle_wait, keyframe)).collect()
+        };
+
+        assert!(due(&high, true, false).iter().all(|d| *d));
+        assert!(due(&normal, false, false).iter().all(|d| *d));
+        // 空闲降频等待期间只刷新高优先级股票
+        assert!(!due(&normal, true, false).iter().any(|d| *d));
+        assert!(!due(&low, true, true).iter().any(|d| *d));
+        // 低优先级股票每 LOW_PRIORITY_EVERY 轮及关键帧时刷新
+        assert_eq!(due(&low, false, false).iter().filter(|d| **d).count(), 1);
+        assert!(due(&low, false, true).iter().all(|d| *d));
+    }
+
     #[tokio::test]
     async fn test_failover() {
         let calls_1 = Arc::new(AtomicUsize::new(0));
@@ -280,4 +712,57 @@ mod tests {
         let _ = manager.fetch(&[]).await;
         assert_eq!(calls_2.load(Ordering::SeqCst), 1);
     }
+
+    /// 模拟轮询循环：记录同时运行的循环数，收到取消信号后延迟一段时间才退出
+    fn fake_loop(
+        running: Arc<AtomicUsize>,
+        max_running: Arc<AtomicUsize>,
+        started: Arc<AtomicUsize>,
+    ) -> impl FnOnce(CancellationToken) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send>> {
+        move |cancel| {
+            Box::pin(async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                cancel.cancelled().await;
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_stop_restart_lifecycle() {
+        let poller = Poller::new();
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let started = Arc::new(AtomicUsize::new(0));
+        let launch = || poller.launch(fake_loop(running.clone(), max_running.clone(), started.clone()));
+
+        launch();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(running.load(Ordering::SeqCst), 1);
+
+        // 已在运行时再次启动被忽略
+        launch();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+
+        // 重启：新循环等旧循环退出后才开始
+        poller.stop();
+        launch();
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+        assert_eq!(running.load(Ordering::SeqCst), 1);
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+
+        // 停止后立即重启再停止：排队中的新循环不再启动
+        poller.stop();
+        launch();
+        poller.stop();
+        poller.shutdown().await;
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+        assert!(poller.task.lock().unwrap().is_none());
+    }
 }
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use crate::calendar;
+use crate::models::{PortfolioUpdate, PositionPnl, PriceUpdate, Stock};
+
+/// 当前北京时间的日期（A 股交易日以北京时间为准）
+pub fn china_today() -> NaiveDate {
+    calendar::china_now().date()
+}
+
+/// 根据持仓和最新行情计算组合盈亏
+/// 没有任何持仓时返回 None；持仓但暂无行情的股票不计入汇总
+/// 启动时从快照恢复的 stale 行情是上个交易日的数据，只计入市值和浮动盈亏，不计入当日盈亏
+/// 当日盈亏是按整笔持仓估算的近似值：建仓日为今天的持仓以成本价为基准，否则整笔以昨收为基准。
+/// 持仓来自交易流水时建仓日是最早一批的日期，当日加仓的部分也按昨收计算，当日卖出的部分不计入
+pub fn compute(
+    stocks: &[Stock],
+    quotes: &HashMap<String, PriceUpdate>,
+    today: NaiveDate,
+    timestamp: u64,
+) -> Option<PortfolioUpdate> {
+    let held: Vec<_> = stocks
+        .iter()
+        .filter_map(|s| s.holding.as_ref().map(|h| (s, h)))
+        .filter(|(_, h)| h.shares > 0.0)
+        .collect();
+    if held.is_empty() {
+        return None;
+    }
+
+    let mut update = PortfolioUpdate {
+        timestamp,
+        ..Default::default()
+    };
+    let mut prev_value = 0.0;
+
+    for (stock, holding) in held {
+        let Some(quote) = quotes.get(&stock.id) else {
+            continue;
+        };
+
+        let market_value = holding.shares * quote.price;
+        let cost_basis = holding.shares * holding.avg_cost;
+        // 当日建仓：以成本价为基准；否则以昨收为基准（不区分当日加仓和卖出，见函数说明）
+        let opened_today = holding.open_date == Some(today);
+        let base_price = if opened_today { holding.avg_cost } else { quote.prev_close };
+        let daily_pnl = if quote.stale { 0.0 } else { holding.shares * (quote.price - base_price) };
+        let unrealized_pnl = market_value - cost_basis;
+
+        update.positions.push(PositionPnl {
+            id: stock.id.clone(),
+            name: if stock.alias.is_empty() { quote.name.clone() } else { stock.alias.clone() },
+            shares: holding.shares,
+            avg_cost: holding.avg_cost,
+            price: quote.price,
+            market_value,
+            cost_basis,
+            daily_pnl,
+            unrealized_pnl,
+            unrealized_percent: ratio(unrealized_pnl, cost_basis),
+        });
+
+        update.market_value += market_value;
+        update.cost_basis += cost_basis;
+        update.daily_pnl += daily_pnl;
+        update.unrealized_pnl += unrealized_pnl;
+        if !quote.stale {
+            prev_value += holding.shares * base_price;
+        }
+    }
+
+    update.daily_percent = ratio(update.daily_pnl, prev_value);
+    update.unrealized_percent = ratio(update.unrealized_pnl, update.cost_basis);
+    Some(update)
+}
+
+fn ratio(value: f64, base: f64) -> f64 {
+    if base > 0.0 {
+        value / base
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Holding;
+    use crate::test_support::{quote, stock};
+
+    #[test]
+    fn test_compute_portfolio() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let stocks = vec![
+            stock("sh600000", Some(Holding { shares: 1000.0, avg_cost: 10.0, open_date: None })),
+            // 当日建仓，当日盈亏按成本价计算
+            stock("sz000001", Some(Holding { shares: 500.0, avg_cost: 12.0, open_date: Some(today) })),
+            stock("sh600519", None),
+        ];
+        let mut quotes = HashMap::new();
+        quotes.insert("sh600000".to_string(), quote("sh600000", 11.0, 10.5));
+        quotes.insert("sz000001".to_string(), quote("sz000001", 12.4, 12.5));
+        quotes.insert("sh600519".to_string(), quote("sh600519", 1700.0, 1690.0));
+
+        let result = compute(&stocks, &quotes, today, 0).unwrap();
+        assert_eq!(result.positions.len(), 2);
+        assert!((result.market_value - (11000.0 + 6200.0)).abs() < 1e-6);
+        assert!((result.cost_basis - (10000.0 + 6000.0)).abs() < 1e-6);
+        // 500 + 200
+        assert!((result.daily_pnl - 700.0).abs() < 1e-6);
+        assert!((result.unrealized_pnl - 1200.0).abs() < 1e-6);
+        assert!((result.daily_percent - 700.0 / 16500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stale_quotes_excluded_from_daily_pnl() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let stocks = vec![
+            stock("sh600000", Some(Holding { shares: 1000.0, avg_cost: 10.0, open_date: None })),
+            stock("sz000001", Some(Holding { shares: 500.0, avg_cost: 12.0, open_date: None })),
+        ];
+        let mut quotes = HashMap::new();
+        quotes.insert("sh600000".to_string(), quote("sh600000", 11.0, 10.5));
+        // 启动时恢复的昨日行情
+        let mut restored = quote("sz000001", 12.4, 12.0);
+        restored.stale = true;
+        quotes.insert("sz000001".to_string(), restored);
+
+        let result = compute(&stocks, &quotes, today, 0).unwrap();
+        assert_eq!(result.positions.len(), 2);
+        assert!((result.market_value - (11000.0 + 6200.0)).abs() < 1e-6);
+        assert_eq!(result.positions[1].daily_pnl, 0.0);
+        assert!((result.daily_pnl - 500.0).abs() < 1e-6);
+        assert!((result.daily_percent - 500.0 / 10500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compute_without_holdings() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let stocks = vec![stock("sh600519", None)];
+        assert!(compute(&stocks, &HashMap::new(), today, 0).is_none());
+    }
+}
@@ -0,0 +1,431 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Listener};
+use tokio::sync::{broadcast, oneshot};
+
+use crate::config::ConfigStore;
+use crate::models::{ApiConfig, PriceUpdate, Stock};
+use crate::poller::QuoteSnapshot;
+use crate::sources;
+
+/// 将一条行情事件包装为 WebSocket 消息，payload 为事件原始 JSON
+fn ws_message(event: &str, payload_json: &str) -> String {
+    format!(r#"{{"event":"{}","payload":{}}}"#, event, payload_json)
+}
+
+/// 按自选股顺序返回快照中的最新行情
+fn ordered_quotes(quotes: &QuoteSnapshot, stocks: &[Stock]) -> Vec<PriceUpdate> {
+    let quotes = quotes.read().unwrap();
+    stocks
+        .iter()
+        .filter_map(|s| quotes.get(&s.id).cloned())
+        .collect()
+}
+
+/// Origin 是否为本机页面（本机 http/https 页面或应用自身的 WebView）
+fn is_local_origin(origin: &str) -> bool {
+    let Some((scheme, rest)) = origin.split_once("://") else {
+        return false;
+    };
+    let host = if rest.starts_with('[') {
+        rest.split_inclusive(']').next().unwrap_or(rest)
+    } else {
+        rest.split(':').next().unwrap_or(rest)
+    };
+    matches!(scheme, "http" | "https" | "tauri")
+        && matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "tauri.localhost")
+}
+
+/// 请求头中的 Origin 是否为本机页面，未携带 Origin 时返回 None
+fn local_origin(headers: &HeaderMap) -> Option<bool> {
+    headers
+        .get(header::ORIGIN)
+        .map(|origin| origin.to_str().is_ok_and(is_local_origin))
+}
+
+/// 拒绝其他网页发起的请求：浏览器中的页面都会带上 Origin，
+/// 命令行、Python 等非浏览器客户端通常不带，REST 接口允许
+async fn reject_foreign_origin(request: Request, next: Next) -> Response {
+    if local_origin(request.headers()) == Some(false) {
+        return (StatusCode::FORBIDDEN, "不接受来自其他网页的请求").into_response();
+    }
+    next.run(request).await
+}
+
+/// Host 是否为本服务的本机地址（127.0.0.1:端口 或 localhost:端口）
+fn is_local_host(host: &str, port: u16) -> bool {
+    let Some((name, host_port)) = host.rsplit_once(':') else {
+        return false;
+    };
+    host_port.parse() == Ok(port) && (name == "127.0.0.1" || name.eq_ignore_ascii_case("localhost"))
+}
+
+/// 防止 DNS 重绑定：恶意域名解析到 127.0.0.1 后，浏览器对该域名的同源 GET 不带 Origin，
+/// 但 Host 仍是该域名，因此只接受本机 Host
+async fn require_local_host(State(port): State<u16>, request: Request, next: Next) -> Response {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .or_else(|| request.uri().authority().map(|a| a.as_str()));
+    if !host.is_some_and(|host| is_local_host(host, port)) {
+        return (StatusCode::FORBIDDEN, "只接受发往本机地址的请求").into_response();
+    }
+    next.run(request).await
+}
+
+/// WebSocket 不受浏览器同源策略限制，任何网页都能连接，必须携带本机 Origin
+/// （Python websockets 可传 origin="http://localhost"）
+async fn require_local_origin(request: Request, next: Next) -> Response {
+    if local_origin(request.headers()) != Some(true) {
+        return (StatusCode::FORBIDDEN, "WebSocket 连接需要本机 Origin").into_response();
+    }
+    next.run(request).await
+}
+
+/// 路由处理函数共享的状态
+#[derive(Clone)]
+struct ServerState {
+    /// 通过 API 修改自选股后通知各窗口（发出 config-changed）
+    on_change: Arc<dyn Fn(&ConfigStore) + Send + Sync>,
+    config_store: Arc<ConfigStore>,
+    quotes: QuoteSnapshot,
+    tx: broadcast::Sender<String>,
+}
+
+/// 正在运行的服务实例
+struct RunningServer {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// 本地 HTTP/WebSocket 行情服务
+/// - 仅绑定 127.0.0.1，默认关闭，在设置中开启
+/// - REST: 自选股列表 / 最新行情 / 增删股票
+/// - WebSocket: 推送轮询器产生的每一批 price-update / price-delta
+pub struct ApiServer {
+    state: ServerState,
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl ApiServer {
+    pub fn new(app: AppHandle, config_store: Arc<ConfigStore>, quotes: QuoteSnapshot) -> Self {
+        let (tx, _) = broadcast::channel(64);
+
+        // 转发轮询器发出的行情事件（关键帧与增量）；没有订阅者时 send 返回错误，忽略即可
+        for event_name in ["price-update", "price-delta"] {
+            let event_tx = tx.clone();
+            app.listen_any(event_name, move |event| {
+                let _ = event_tx.send(ws_message(event_name, event.payload()));
+            });
+        }
+
+        let on_change = Arc::new(move |config_store: &ConfigStore| {
+            let _ = app.emit("config-changed", &config_store.get());
+        });
+        Self {
+            state: ServerState {
+                on_change,
+                config_store,
+                quotes,
+                tx,
+            },
+            running: Mutex::new(None),
+        }
+    }
+
+    /// 根据配置启动、停止或重启服务
+    pub fn apply(&self, api: &ApiConfig) -> Result<()> {
+        let mut running = self.running.lock().unwrap();
+
+        if let Some(server) = running.as_ref() {
+            if api.enabled && server.port == api.port {
+                return Ok(());
+            }
+        }
+        if let Some(server) = running.take() {
+            let _ = server.shutdown.send(());
+            log::info!("本地行情 API 已停止 (端口 {})", server.port);
+        }
+        if !api.enabled {
+            return Ok(());
+        }
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], api.port));
+        let listener = std::net::TcpListener::bind(addr)
+            .with_context(|| format!("本地行情 API 端口 {} 绑定失败", api.port))?;
+        listener.set_nonblocking(true)?;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let router = Self::router(self.state.clone(), api.port);
+
+        tauri::async_runtime::spawn(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(l) => l,
+                Err(e) => {
+                    log::error!("本地行情 API 启动失败: {}", e);
+                    return;
+                }
+            };
+            let result = axum::serve(listener, router)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(e) = result {
+                log::error!("本地行情 API 异常退出: {}", e);
+            }
+        });
+
+        log::info!("本地行情 API 已启动: http://{}", addr);
+        *running = Some(RunningServer {
+            port: api.port,
+            shutdown: shutdown_tx,
+        });
+        Ok(())
+    }
+
+    /// 停止服务
+    pub fn stop(&self) {
+        if let Some(server) = self.running.lock().unwrap().take() {
+            let _ = server.shutdown.send(());
+        }
+    }
+
+    fn router(state: ServerState, port: u16) -> Router {
+        Router::new()
+            .route("/api/stocks", get(list_stocks).post(add_stock))
+            .route("/api/stocks/{id}", delete(remove_stock))
+            .route("/api/quotes", get(list_quotes))
+            .route("/api/quotes/{id}", get(get_quote))
+            .route("/ws", get(ws_handler).route_layer(middleware::from_fn(require_local_origin)))
+            .layer(middleware::from_fn(reject_foreign_origin))
+            .layer(middleware::from_fn_with_state(port, require_local_host))
+            .with_state(state)
+    }
+}
+
+type ApiError = (StatusCode, String);
+
+#[derive(Deserialize)]
+struct AddStockBody {
+    code: String,
+}
+
+/// API 返回的股票信息，不包含持仓等私人数据
+#[derive(Serialize)]
+struct ApiStock {
+    id: String,
+    code: String,
+    market: String,
+    alias: String,
+    visible: bool,
+}
+
+impl From<Stock> for ApiStock {
+    fn from(stock: Stock) -> Self {
+        Self {
+            id: stock.id,
+            code: stock.code,
+            market: stock.market,
+            alias: stock.alias,
+            visible: stock.visible,
+        }
+    }
+}
+
+/// GET /api/stocks
+async fn list_stocks(State(state): State<ServerState>) -> Json<Vec<ApiStock>> {
+    Json(state.config_store.get().stocks.into_iter().map(ApiStock::from).collect())
+}
+
+/// POST /api/stocks  {"code": "600519"}
+async fn add_stock(
+    State(state): State<ServerState>,
+    Json(body): Json<AddStockBody>,
+) -> Result<(StatusCode, Json<ApiStock>), ApiError> {
+    let code = body.code.trim();
+    if code.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "股票代码不能为空".to_string()));
+    }
+    let stock = sources::make_stock(code);
+    state
+        .config_store
+        .add_stock(stock.clone())
+        .map_err(|e| (StatusCode::CONFLICT, e.to_string()))?;
+    (state.on_change)(&state.config_store);
+    Ok((StatusCode::CREATED, Json(stock.into())))
+}
+
+/// DELETE /api/stocks/{id}
+async fn remove_stock(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if !state.config_store.get().stocks.iter().any(|s| s.id == id) {
+        return Err((StatusCode::NOT_FOUND, format!("股票不在列表中: {}", id)));
+    }
+    state
+        .config_store
+        .remove_stock(&id)
+        .map_err(|e| (StatusCode::CONFLICT, e.to_string()))?;
+    (state.on_change)(&state.config_store);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/quotes
+async fn list_quotes(State(state): State<ServerState>) -> Json<Vec<PriceUpdate>> {
+    Json(ordered_quotes(&state.quotes, &state.config_store.get().stocks))
+}
+
+/// GET /api/quotes/{id}
+async fn get_quote(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+) -> Result<Json<PriceUpdate>, ApiError> {
+    state
+        .quotes
+        .read()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("暂无行情: {}", id)))
+}
+
+/// GET /ws
+async fn ws_handler(State(state): State<ServerState>, ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| stream_quotes(socket, state))
+}
+
+/// 连接建立后先推送一次当前快照，随后转发每一批行情（关键帧与增量）
+async fn stream_quotes(mut socket: WebSocket, state: ServerState) {
+    let mut rx = state.tx.subscribe();
+
+    let snapshot = ordered_quotes(&state.quotes, &state.config_store.get().stocks);
+    let initial = match serde_json::to_string(&snapshot) {
+        Ok(json) => ws_message("price-update", &json),
+        Err(_) => return,
+    };
+    if socket.send(Message::Text(initial.into())).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            msg = rx.recv() => match msg {
+                Ok(text) => {
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("WebSocket 客户端处理过慢，跳过 {} 条行情", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+    use crate::models::{Config, Holding};
+    use crate::test_support::{quote, stock};
+
+    /// 在随机端口启动服务，返回地址
+    async fn serve(config: Config, quotes: Vec<PriceUpdate>) -> SocketAddr {
+        let quotes = quotes.into_iter().map(|q| (q.id.clone(), q)).collect::<HashMap<_, _>>();
+        let state = ServerState {
+            on_change: Arc::new(|_: &ConfigStore| {}),
+            config_store: Arc::new(ConfigStore::in_memory(config)),
+            quotes: Arc::new(RwLock::new(quotes)),
+            tx: broadcast::channel(1).0,
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, ApiServer::router(state, addr.port())).await });
+        addr
+    }
+
+    #[test]
+    fn test_local_origins() {
+        let local = ["http://localhost:5173", "http://127.0.0.1", "https://[::1]:8080", "tauri://localhost", "http://tauri.localhost"];
+        for origin in local {
+            assert!(is_local_origin(origin), "{}", origin);
+        }
+        for origin in ["https://example.com", "http://localhost.evil.com", "http://127.0.0.1.evil.com", "null", "file://"] {
+            assert!(!is_local_origin(origin), "{}", origin);
+        }
+    }
+
+    #[test]
+    fn test_local_hosts() {
+        assert!(is_local_host("127.0.0.1:17380", 17380));
+        assert!(is_local_host("LocalHost:17380", 17380));
+        for host in ["127.0.0.1:8080", "127.0.0.1", "evil.example.com:17380", "localhost.evil.com:17380", "[::1]:17380"] {
+            assert!(!is_local_host(host, 17380), "{}", host);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quotes_endpoint() {
+        let config = Config {
+            stocks: vec![
+                stock("sh600519", Some(Holding { shares: 100.0, avg_cost: 1500.0, open_date: None })),
+                sources::make_stock("000001"),
+            ],
+            ..Config::default()
+        };
+        // 快照中的行情按自选股顺序返回，没有行情的股票不返回
+        let addr = serve(config, vec![quote("sz000001", 10.0, 9.9), quote("sh600000", 8.0, 8.1)]).await;
+        let client = reqwest::Client::new();
+
+        let url = format!("http://{}/api/quotes", addr);
+        let quotes: Vec<PriceUpdate> = client.get(&url).send().await.unwrap().json().await.unwrap();
+        assert_eq!(quotes.iter().map(|q| q.id.as_str()).collect::<Vec<_>>(), ["sz000001"]);
+
+        let response = client.get(format!("{}/sh600519", url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // 其他网页发起的请求被拒绝，本机页面允许
+        let response = client.get(&url).header("Origin", "https://example.com").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = client.get(&url).header("Origin", "http://localhost:1420").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // DNS 重绑定：其他域名解析到本机后，同源请求不带 Origin，但 Host 不是本机地址
+        let response = client.get(&url).header("Host", format!("evil.example.com:{}", addr.port())).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = client.get(&url).header("Host", format!("localhost:{}", addr.port())).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 自选股列表不返回持仓
+        let stocks: Vec<serde_json::Value> =
+            client.get(format!("http://{}/api/stocks", addr)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(stocks[0]["id"], "sh600519");
+        assert!(stocks[0].get("holding").is_none());
+
+        // WebSocket 必须携带本机 Origin
+        let ws = format!("http://{}/ws", addr);
+        assert_eq!(client.get(&ws).send().await.unwrap().status(), StatusCode::FORBIDDEN);
+        let response = client.get(&ws).header("Origin", "https://example.com").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -1,5 +1,8 @@
 use anyhow::{Context, Result};
-use crate::models::PriceUpdate;
+use chrono::NaiveDate;
+use crate::models::{
+    Adjust, IntradayData, IntradayPoint, Kline, KlinePeriod, MarketBreadth, PriceUpdate,
+};
 use super::DataSource;
 
 /// 东方财富行情 API 适配器
@@ -29,6 +32,12 @@ impl EastmoneySource {
     }
 }
 
+impl Default for EastmoneySource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl DataSource for EastmoneySource {
     fn name(&self) -> &str {
@@ -61,6 +70,190 @@ impl DataSource for EastmoneySource {
 
         Ok(results)
     }
+
+    async fn fetch_intraday(&self, market: &str, code: &str) -> Result<IntradayData> {
+        let url = format!(
+            "http://push2his.eastmoney.com/api/qt/stock/trends2/get?secid={}&fields1=f1,f2,f3,f4,f5,f6,f7,f8&fields2=f51,f52,f53,f54,f55,f56,f57,f58&ndays=1&iscr=0",
+            Self::to_secid(market, code)
+        );
+        let json: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("东财分时API请求失败")?
+            .json()
+            .await
+            .context("解析东财分时API JSON失败")?;
+        let id = format!("{}{}", market, code);
+        parse_eastmoney_trends(&json, &id).ok_or_else(|| anyhow::anyhow!("东财分时数据格式错误: {}", id))
+    }
+
+    async fn fetch_kline(
+        &self,
+        market: &str,
+        code: &str,
+        period: KlinePeriod,
+        adjust: Adjust,
+        since: Option<NaiveDate>,
+    ) -> Result<Vec<Kline>> {
+        // klt: 101 日线 / 102 周线 / 103 月线；fqt: 0 不复权 / 1 前复权 / 2 后复权
+        let klt = match period {
+            KlinePeriod::Day => 101,
+            KlinePeriod::Week => 102,
+            KlinePeriod::Month => 103,
+        };
+        let fqt = match adjust {
+            Adjust::None => 0,
+            Adjust::Forward => 1,
+            Adjust::Backward => 2,
+        };
+        let beg = since.map(|d| d.format("%Y%m%d").to_string()).unwrap_or_else(|| "0".to_string());
+        let url = format!(
+            "http://push2his.eastmoney.com/api/qt/stock/kline/get?secid={}&fields1=f1,f2,f3,f4,f5,f6&fields2=f51,f52,f53,f54,f55,f56,f57&klt={}&fqt={}&beg={}&end=20500101",
+            Self::to_secid(market, code),
+            klt,
+            fqt,
+            beg
+        );
+        let json: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("东财K线API请求失败")?
+            .json()
+            .await
+            .context("解析东财K线API JSON失败")?;
+        parse_eastmoney_klines(&json).ok_or_else(|| anyhow::anyhow!("东财K线数据格式错误: {}{}", market, code))
+    }
+
+    async fn fetch_breadth(&self, date: NaiveDate) -> Result<MarketBreadth> {
+        // 上证指数、深证综指的涨跌家数覆盖两市全部个股，两者成交额之和即两市成交额
+        let indices_url = "http://push2.eastmoney.com/api/qt/ulist.np/get?fltt=2&secids=1.000001,0.399106&fields=f6,f104,f105,f106";
+        let pool_url = |topic: &str| {
+            format!(
+                "http://push2ex.eastmoney.com/{}?ut=7eea3edcaed734bea9cbfc24409ed989&dpt=wz.ztzt&Pageindex=0&pagesize=1&sort=fbt:asc&date={}",
+                topic,
+                date.format("%Y%m%d")
+            )
+        };
+
+        let mut responses = Vec::new();
+        for url in [indices_url.to_string(), pool_url("getTopicZTPool"), pool_url("getTopicDTPool")] {
+            let json: serde_json::Value = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .context("东财涨跌统计API请求失败")?
+                .json()
+                .await
+                .context("解析东财涨跌统计API JSON失败")?;
+            responses.push(json);
+        }
+        parse_eastmoney_breadth(&responses[0], &responses[1], &responses[2])
+            .ok_or_else(|| anyhow::anyhow!("东财涨跌统计数据格式错误"))
+    }
+}
+
+/// 解析东财涨跌统计
+/// indices: ulist 接口 data.diff，每项 f6 成交额、f104 上涨、f105 下跌、f106 平盘家数
+/// limit_up / limit_down: 涨停池、跌停池接口，data.tc 为总数；非交易日 data 为 null，按 0 计
+fn parse_eastmoney_breadth(
+    indices: &serde_json::Value,
+    limit_up: &serde_json::Value,
+    limit_down: &serde_json::Value,
+) -> Option<MarketBreadth> {
+    let mut breadth = MarketBreadth {
+        advancers: 0,
+        decliners: 0,
+        unchanged: 0,
+        limit_up: 0,
+        limit_down: 0,
+        turnover: 0.0,
+    };
+    let diff = indices.get("data")?.get("diff")?.as_array()?;
+    if diff.is_empty() {
+        return None;
+    }
+    for item in diff {
+        let count = |field: &str| item.get(field).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        breadth.advancers += count("f104");
+        breadth.decliners += count("f105");
+        breadth.unchanged += count("f106");
+        breadth.turnover += item.get("f6").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    }
+    let pool_count = |json: &serde_json::Value| {
+        json.get("data")
+            .and_then(|d| d.get("tc"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32
+    };
+    breadth.limit_up = pool_count(limit_up);
+    breadth.limit_down = pool_count(limit_down);
+    Some(breadth)
+}
+
+/// 解析东财 K 线
+/// data.klines 每项为 "YYYY-MM-DD,开,收,高,低,成交量(手),成交额"；无数据时 data 为 null
+fn parse_eastmoney_klines(json: &serde_json::Value) -> Option<Vec<Kline>> {
+    let data = json.get("data")?;
+    if data.is_null() {
+        return Some(Vec::new());
+    }
+    let mut klines = Vec::new();
+    for item in data.get("klines")?.as_array()? {
+        let fields: Vec<&str> = item.as_str()?.split(',').collect();
+        if fields.len() < 7 {
+            continue;
+        }
+        klines.push(Kline {
+            date: NaiveDate::parse_from_str(fields[0], "%Y-%m-%d").ok()?,
+            open: fields[1].parse().ok()?,
+            close: fields[2].parse().ok()?,
+            high: fields[3].parse().ok()?,
+            low: fields[4].parse().ok()?,
+            volume: fields[5].parse::<f64>().ok()? * 100.0,
+            amount: fields[6].parse().ok()?,
+        });
+    }
+    Some(klines)
+}
+
+/// 解析东财分时数据
+/// data.trends 每项为 "YYYY-MM-DD HH:MM,开,收,高,低,成交量(手),成交额,均价"
+fn parse_eastmoney_trends(json: &serde_json::Value, id: &str) -> Option<IntradayData> {
+    let data = json.get("data")?;
+    let prev_close = data
+        .get("preClose")
+        .or_else(|| data.get("prePrice"))
+        .and_then(|v| v.as_f64())?;
+
+    let mut date = None;
+    let mut points = Vec::new();
+    for item in data.get("trends")?.as_array()? {
+        let fields: Vec<&str> = item.as_str()?.split(',').collect();
+        if fields.len() < 8 {
+            continue;
+        }
+        let (day, time) = fields[0].split_once(' ')?;
+        date = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok();
+        points.push(IntradayPoint {
+            time: time.to_string(),
+            price: fields[2].parse().ok()?,
+            avg_price: fields[7].parse().ok()?,
+            volume: fields[5].parse::<f64>().ok()? * 100.0,
+        });
+    }
+
+    Some(IntradayData {
+        id: id.to_string(),
+        date: date?,
+        prev_close,
+        points,
+        source: "eastmoney".to_string(),
+    })
 }
 
 impl EastmoneySource {
@@ -123,6 +316,7 @@ impl EastmoneySource {
             low,
             timestamp,
             source: "eastmoney".to_string(),
+            stale: false,
         }))
     }
 }
@@ -131,6 +325,71 @@ impl EastmoneySource {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_eastmoney_breadth() {
+        let indices = serde_json::json!({
+            "data": { "diff": [
+                { "f6": 4.1e11, "f104": 1200, "f105": 950, "f106": 120 },
+                { "f6": 5.3e11, "f104": 1500, "f105": 1180, "f106": 160 }
+            ] }
+        });
+        let limit_up = serde_json::json!({ "data": { "tc": 58, "pool": [] } });
+        let limit_down = serde_json::json!({ "data": null });
+
+        let breadth = parse_eastmoney_breadth(&indices, &limit_up, &limit_down).unwrap();
+        assert_eq!(breadth.advancers, 2700);
+        assert_eq!(breadth.decliners, 2130);
+        assert_eq!(breadth.unchanged, 280);
+        assert_eq!(breadth.limit_up, 58);
+        assert_eq!(breadth.limit_down, 0);
+        assert!((breadth.turnover - 9.4e11).abs() < 1.0);
+
+        let empty = serde_json::json!({ "data": { "diff": [] } });
+        assert!(parse_eastmoney_breadth(&empty, &limit_up, &limit_down).is_none());
+    }
+
+    #[test]
+    fn test_parse_eastmoney_klines() {
+        let json = serde_json::json!({
+            "data": {
+                "code": "600519",
+                "klines": [
+                    "2024-05-31,1650.00,1660.00,1670.00,1640.00,25000,4150000000.00",
+                    "2024-06-03,1660.00,1640.00,1665.00,1630.00,30000,4920000000.00"
+                ]
+            }
+        });
+        let klines = parse_eastmoney_klines(&json).unwrap();
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[1].date, NaiveDate::from_ymd_opt(2024, 6, 3).unwrap());
+        assert_eq!(klines[1].close, 1640.0);
+        assert_eq!(klines[1].high, 1665.0);
+        assert_eq!(klines[1].volume, 3_000_000.0);
+
+        assert!(parse_eastmoney_klines(&serde_json::json!({ "data": null })).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_eastmoney_trends() {
+        let json = serde_json::json!({
+            "data": {
+                "code": "600519",
+                "preClose": 1532.0,
+                "trends": [
+                    "2024-06-03 09:30,1520.00,1520.00,1520.00,1520.00,30,4560000.00,1520.000",
+                    "2024-06-03 09:31,1520.00,1522.00,1523.00,1519.00,20,3044000.00,1520.800"
+                ]
+            }
+        });
+        let data = parse_eastmoney_trends(&json, "sh600519").unwrap();
+        assert_eq!(data.date, NaiveDate::from_ymd_opt(2024, 6, 3).unwrap());
+        assert_eq!(data.points.len(), 2);
+        assert_eq!(data.points[1].time, "09:31");
+        assert_eq!(data.points[1].price, 1522.0);
+        assert_eq!(data.points[1].volume, 2000.0);
+        assert_eq!(data.points[1].avg_price, 1520.8);
+    }
+
     #[tokio::test]
     async fn test_eastmoney_fetch() {
         // use crate::sources::DataSource; // already imported by super::*? No, use super::DataSource.
@@ -3,7 +3,10 @@ pub mod tencent;
 pub mod eastmoney;
 
 use anyhow::Result;
-use crate::models::PriceUpdate;
+use chrono::NaiveDate;
+use crate::models::{
+    Adjust, IntradayData, Kline, KlinePeriod, MarketBreadth, PriceUpdate, Stock, StockDetail,
+};
 
 /// 数据源统一接口
 #[async_trait::async_trait]
@@ -13,6 +16,47 @@ pub trait DataSource: Send + Sync {
 
     /// 批量获取股票数据
     async fn fetch(&self, stocks: &[(String, String)]) -> Result<Vec<PriceUpdate>>;
+
+    /// 获取当日分时数据（每分钟价格、均价、成交量），默认不支持
+    async fn fetch_intraday(&self, market: &str, code: &str) -> Result<IntradayData> {
+        let _ = (market, code);
+        anyhow::bail!("数据源 {} 不支持分时数据", self.name())
+    }
+
+    /// 获取单只股票的五档盘口与关键统计，默认不支持
+    async fn fetch_detail(&self, market: &str, code: &str) -> Result<StockDetail> {
+        let _ = (market, code);
+        anyhow::bail!("数据源 {} 不支持盘口数据", self.name())
+    }
+
+    /// 获取沪深两市涨跌家数、涨跌停家数与成交额（date 为当天北京时间日期），默认不支持
+    async fn fetch_breadth(&self, date: NaiveDate) -> Result<MarketBreadth> {
+        let _ = date;
+        anyhow::bail!("数据源 {} 不支持涨跌统计", self.name())
+    }
+
+    /// 获取 since（含）之后的历史 K 线，since 为空时获取全部，默认不支持
+    async fn fetch_kline(
+        &self,
+        market: &str,
+        code: &str,
+        period: KlinePeriod,
+        adjust: Adjust,
+        since: Option<NaiveDate>,
+    ) -> Result<Vec<Kline>> {
+        let _ = (market, code, period, adjust, since);
+        anyhow::bail!("数据源 {} 不支持历史 K 线", self.name())
+    }
+}
+
+/// 按名称创建数据源，未知名称返回 None
+pub fn create_source(name: &str) -> Option<Box<dyn DataSource>> {
+    match name {
+        "sina" => Some(Box::new(sina::SinaSource::new())),
+        "tencent" => Some(Box::new(tencent::TencentSource::new())),
+        "eastmoney" => Some(Box::new(eastmoney::EastmoneySource::new())),
+        _ => None,
+    }
 }
 
 /// 根据股票代码自动识别市场
@@ -29,3 +73,55 @@ pub fn detect_market(code: &str) -> &str {
 pub fn make_stock_id(market: &str, code: &str) -> String {
     format!("{}{}", market, code)
 }
+
+/// 解析用户输入的股票代码，返回 (market, code)
+/// 支持 "600519"、"sh600519"、"SH600519" 等形式，无前缀时自动识别市场
+pub fn parse_code(input: &str) -> (String, String) {
+    let input = input.trim();
+    let lower = input.to_ascii_lowercase();
+    for market in ["sh", "sz"] {
+        if let Some(code) = lower.strip_prefix(market) {
+            return (market.to_string(), code.to_string());
+        }
+    }
+    (detect_market(input).to_string(), input.to_string())
+}
+
+/// 严格识别股票代码（导入自选股时使用），返回 (market, code)
+/// 支持 "600519"、"sh600519"、"SH.600519"、"600519.SH"、"600519.SS" 等形式，无市场标识时自动识别
+/// 以及交易软件的市场位写法：通达信 "1600519" / "0000001"，东方财富 "1.600519" / "0.000001"（1 为沪市，0 为深市）
+/// 代码不是 6 位数字时返回 None
+pub fn resolve_code(input: &str) -> Option<(String, String)> {
+    let lower = input.trim().to_ascii_lowercase();
+    let (market, code) = match lower.split_once('.') {
+        // 600519.SH，.SS 为雅虎财经的上交所后缀
+        Some((code, "sh" | "ss")) => ("sh".to_string(), code.to_string()),
+        Some((code, "sz")) => ("sz".to_string(), code.to_string()),
+        Some((market @ ("sh" | "sz"), code)) => (market.to_string(), code.to_string()),
+        Some(("1", code)) => ("sh".to_string(), code.to_string()),
+        Some(("0", code)) => ("sz".to_string(), code.to_string()),
+        Some(_) => return None,
+        None if lower.len() == 7 && lower.chars().all(|c| c.is_ascii_digit()) => match &lower[..1] {
+            "1" => ("sh".to_string(), lower[1..].to_string()),
+            "0" => ("sz".to_string(), lower[1..].to_string()),
+            _ => return None,
+        },
+        None => parse_code(&lower),
+    };
+    let valid = code.len() == 6 && code.chars().all(|c| c.is_ascii_digit());
+    valid.then_some((market, code))
+}
+
+/// 根据用户输入的股票代码构建自选股条目（别名留空，后续通过数据源获取名称）
+pub fn make_stock(input: &str) -> Stock {
+    let (market, code) = parse_code(input);
+    Stock {
+        id: make_stock_id(&market, &code),
+        code,
+        market,
+        alias: String::new(),
+        visible: true,
+        holding: None,
+        overrides: Default::default(),
+    }
+}
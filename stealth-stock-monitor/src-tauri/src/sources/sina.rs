@@ -18,6 +18,12 @@ impl SinaSource {
     }
 }
 
+impl Default for SinaSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl DataSource for SinaSource {
     fn name(&self) -> &str {
@@ -130,6 +136,7 @@ fn parse_sina_line(line: &str, timestamp: u64) -> Option<PriceUpdate> {
         low,
         timestamp,
         source: "sina".to_string(),
+        stale: false,
     })
 }
 
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
-use crate::models::PriceUpdate;
+use chrono::NaiveDate;
+use crate::models::{DepthLevel, IntradayData, IntradayPoint, PriceUpdate, StockDetail};
 use super::DataSource;
 
 /// 腾讯证券行情 API 适配器
@@ -18,6 +19,12 @@ impl TencentSource {
     }
 }
 
+impl Default for TencentSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl DataSource for TencentSource {
     fn name(&self) -> &str {
@@ -59,14 +66,87 @@ impl DataSource for TencentSource {
 
         Ok(results)
     }
+
+    async fn fetch_intraday(&self, market: &str, code: &str) -> Result<IntradayData> {
+        let id = format!("{}{}", market, code);
+        let url = format!("https://web.ifzq.gtimg.cn/appstock/app/minute/query?code={}", id);
+        let json: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("腾讯分时API请求失败")?
+            .json()
+            .await
+            .context("解析腾讯分时API JSON失败")?;
+        parse_tencent_minute(&json, &id).ok_or_else(|| anyhow::anyhow!("腾讯分时数据格式错误: {}", id))
+    }
+
+    async fn fetch_detail(&self, market: &str, code: &str) -> Result<StockDetail> {
+        let id = format!("{}{}", market, code);
+        let url = format!("http://qt.gtimg.cn/q={}", id);
+        let text = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("腾讯API请求失败")?
+            .text()
+            .await
+            .context("读取腾讯API响应失败")?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        text.lines()
+            .find_map(|line| parse_tencent_detail(line, now))
+            .ok_or_else(|| anyhow::anyhow!("腾讯盘口数据格式错误: {}", id))
+    }
 }
 
-/// 解析腾讯 API 单行数据
-/// 格式: v_sh600519="1~贵州茅台~600519~1755.00~1740.00~...";
-/// 字段以 ~ 分隔:
-/// [0]market_id [1]名称 [2]代码 [3]现价 [4]昨收 [5]今开 [6]成交量
-/// [31]最高 [32]最低
-fn parse_tencent_line(line: &str, timestamp: u64) -> Option<PriceUpdate> {
+/// 解析腾讯分时数据
+/// data.{id}.data.data 每项为 "HHMM 价格 累计成交量(手) 累计成交额"，昨收取自 data.{id}.qt.{id}[4]
+fn parse_tencent_minute(json: &serde_json::Value, id: &str) -> Option<IntradayData> {
+    let stock = json.get("data")?.get(id)?;
+    let minute = stock.get("data")?;
+    let date = NaiveDate::parse_from_str(minute.get("date")?.as_str()?, "%Y%m%d").ok()?;
+    let prev_close: f64 = stock.get("qt")?.get(id)?.get(4)?.as_str()?.parse().ok()?;
+
+    let mut points = Vec::new();
+    let mut last_volume = 0.0;
+    for item in minute.get("data")?.as_array()? {
+        let fields: Vec<&str> = item.as_str()?.split_whitespace().collect();
+        // 时间必须是 4 位数字，长度按字节计算，非 ASCII 字符会使后面的切片 panic
+        if fields.len() < 4 || fields[0].len() != 4 || !fields[0].bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        // 单行数据异常时跳过该分钟，不丢弃整天的分时
+        let (Ok(price), Ok(volume), Ok(amount)) =
+            (fields[1].parse::<f64>(), fields[2].parse::<f64>(), fields[3].parse::<f64>())
+        else {
+            continue;
+        };
+        let avg_price = if volume > 0.0 { amount / (volume * 100.0) } else { price };
+        points.push(IntradayPoint {
+            time: format!("{}:{}", &fields[0][..2], &fields[0][2..]),
+            price,
+            avg_price,
+            volume: (volume - last_volume) * 100.0,
+        });
+        last_volume = volume;
+    }
+
+    Some(IntradayData {
+        id: id.to_string(),
+        date,
+        prev_close,
+        points,
+        source: "tencent".to_string(),
+    })
+}
+
+/// 拆分腾讯 API 单行数据，返回 (股票 ID, ~ 分隔的字段)
+fn split_tencent_line(line: &str) -> Option<(&str, Vec<&str>)> {
     // 提取 ID
     let id_start = line.find("v_")? + 2;
     let id_end = line[id_start..].find('=')?;
@@ -78,10 +158,54 @@ fn parse_tencent_line(line: &str, timestamp: u64) -> Option<PriceUpdate> {
     if data_start >= data_end {
         return None;
     }
-    let data = &line[data_start..data_end];
-    let fields: Vec<&str> = data.split('~').collect();
+    Some((full_id, line[data_start..data_end].split('~').collect()))
+}
+
+/// 解析腾讯 API 单行数据中的盘口与统计字段
+/// [5]今开 [9..=18]买一至买五(价, 量手) [19..=28]卖一至卖五(价, 量手)
+/// [36]成交量(手) [37]成交额(万) [38]换手率% [39]市盈率 [43]振幅%
+/// [44]流通市值(亿) [45]总市值(亿) [46]市净率 [47]涨停价 [48]跌停价
+fn parse_tencent_detail(line: &str, timestamp: u64) -> Option<StockDetail> {
+    let quote = parse_tencent_line(line, timestamp)?;
+    let (_, fields) = split_tencent_line(line)?;
+    let num = |i: usize| fields.get(i).and_then(|f| f.parse::<f64>().ok());
+    // 停牌或无数据时字段为空或 0
+    let positive = |i: usize| num(i).filter(|v| *v > 0.0);
+    let levels = |start: usize| -> Vec<DepthLevel> {
+        (0..5)
+            .map(|n| DepthLevel {
+                price: num(start + n * 2).unwrap_or(0.0),
+                volume: num(start + n * 2 + 1).unwrap_or(0.0) * 100.0,
+            })
+            .collect()
+    };
+
+    Some(StockDetail {
+        open: num(5).unwrap_or(0.0),
+        volume: num(36).unwrap_or(0.0) * 100.0,
+        amount: num(37).unwrap_or(0.0) * 10_000.0,
+        turnover_rate: num(38).map(|v| v / 100.0),
+        pe: num(39),
+        pb: num(46),
+        amplitude: num(43).map(|v| v / 100.0),
+        float_market_cap: positive(44).map(|v| v * 1e8),
+        market_cap: positive(45).map(|v| v * 1e8),
+        limit_up: positive(47),
+        limit_down: positive(48),
+        bids: levels(9),
+        asks: levels(19),
+        quote,
+    })
+}
 
-    if fields.len() < 33 {
+/// 解析腾讯 API 单行数据
+/// 格式: v_sh600519="1~贵州茅台~600519~1755.00~1740.00~...";
+/// 字段以 ~ 分隔:
+/// [0]market_id [1]名称 [2]代码 [3]现价 [4]昨收 [5]今开 [6]成交量
+/// [33]最高 [34]最低
+fn parse_tencent_line(line: &str, timestamp: u64) -> Option<PriceUpdate> {
+    let (full_id, fields) = split_tencent_line(line)?;
+    if fields.len() < 35 {
         return None;
     }
 
@@ -118,6 +242,7 @@ fn parse_tencent_line(line: &str, timestamp: u64) -> Option<PriceUpdate> {
         low,
         timestamp,
         source: "tencent".to_string(),
+        stale: false,
     })
 }
 
@@ -125,6 +250,67 @@ fn parse_tencent_line(line: &str, timestamp: u64) -> Option<PriceUpdate> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_tencent_minute() {
+        let json = serde_json::json!({
+            "code": 0,
+            "data": {
+                "sh600519": {
+                    "data": {
+                        "date": "20240603",
+                        "data": [
+                            "0930 1520.00 30 4560000.00",
+                            "时0 1521.00 40 6084000.00",
+                            "0931 -- 40 6084000.00",
+                            "0931 1522.00 50 7604000.00"
+                        ]
+                    },
+                    "qt": { "sh600519": ["1", "贵州茅台", "600519", "1522.00", "1532.00"] }
+                }
+            }
+        });
+        let data = parse_tencent_minute(&json, "sh600519").unwrap();
+        assert_eq!(data.prev_close, 1532.0);
+        assert_eq!(data.points.len(), 2);
+        assert_eq!(data.points[1].time, "09:31");
+        // 第二分钟成交 20 手，均价为累计成交额 / 累计成交股数
+        assert_eq!(data.points[1].volume, 2000.0);
+        assert!((data.points[1].avg_price - 7604000.0 / 5000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_tencent_detail() {
+        let mut fields = vec!["0"; 49];
+        fields[1] = "贵州茅台";
+        fields[2] = "600519";
+        fields[3] = "1522.00";
+        fields[4] = "1532.00";
+        fields[5] = "1530.00";
+        fields[9] = "1521.99";
+        fields[10] = "3";
+        fields[19] = "1522.00";
+        fields[20] = "12";
+        fields[33] = "1535.00";
+        fields[34] = "1518.00";
+        fields[36] = "21000";
+        fields[37] = "320000.50";
+        fields[38] = "0.17";
+        fields[45] = "19120.35";
+        let line = format!("v_sh600519=\"{}\";", fields.join("~"));
+
+        let detail = parse_tencent_detail(&line, 0).unwrap();
+        assert_eq!(detail.quote.price, 1522.0);
+        assert_eq!(detail.open, 1530.0);
+        assert_eq!(detail.bids.len(), 5);
+        assert_eq!(detail.bids[0], DepthLevel { price: 1521.99, volume: 300.0 });
+        assert_eq!(detail.asks[0].volume, 1200.0);
+        assert_eq!(detail.volume, 2_100_000.0);
+        assert!((detail.turnover_rate.unwrap() - 0.0017).abs() < 1e-12);
+        assert!((detail.market_cap.unwrap() - 1.912035e12).abs() < 1.0);
+        // 为 0 的涨跌停价视为缺失
+        assert_eq!(detail.limit_up, None);
+    }
+
     #[tokio::test]
     async fn test_tencent_fetch() {
         let source = TencentSource::new();
@@ -0,0 +1,31 @@
+//! 单元测试共用的构造函数
+
+use crate::models::{Holding, PriceUpdate, Stock};
+use crate::sources;
+
+/// 构造一条行情（时间戳为 0，最高价、最低价均为当前价）
+pub fn quote(id: &str, price: f64, prev_close: f64) -> PriceUpdate {
+    PriceUpdate {
+        id: id.to_string(),
+        code: id[2..].to_string(),
+        market: id[..2].to_string(),
+        name: "测试".to_string(),
+        price,
+        prev_close,
+        change: price - prev_close,
+        percent: (price - prev_close) / prev_close,
+        high: price,
+        low: price,
+        timestamp: 0,
+        source: "mock".to_string(),
+        stale: false,
+    }
+}
+
+/// 构造一只自选股（id 如 sh600000）
+pub fn stock(id: &str, holding: Option<Holding>) -> Stock {
+    Stock {
+        holding,
+        ..sources::make_stock(id)
+    }
+}
@@ -0,0 +1,501 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Listener};
+use tokio::sync::mpsc;
+
+use crate::calendar;
+use crate::config::{get_config_dir, ConfigStore};
+use chrono::NaiveDate;
+use crate::models::{Adjust, Bar, BarInterval, Kline, KlinePeriod, PriceUpdate, StorageConfig, Tick};
+
+/// 清理过期数据的间隔
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+const MINUTE_MS: u64 = 60_000;
+const DAY_MS: u64 = 24 * 3600 * 1000;
+/// 北京时间相对 UTC 的偏移（毫秒），日线按北京时间的自然日划分
+const CHINA_OFFSET_MS: u64 = 8 * 3600 * 1000;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS ticks (
+        stock_id   TEXT    NOT NULL,
+        ts         INTEGER NOT NULL,
+        price      REAL    NOT NULL,
+        prev_close REAL    NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_ticks_stock_ts ON ticks (stock_id, ts);
+    CREATE TABLE IF NOT EXISTS bars_1m (
+        stock_id TEXT    NOT NULL,
+        ts       INTEGER NOT NULL,
+        open     REAL    NOT NULL,
+        high     REAL    NOT NULL,
+        low      REAL    NOT NULL,
+        close    REAL    NOT NULL,
+        PRIMARY KEY (stock_id, ts)
+    );
+    CREATE TABLE IF NOT EXISTS klines (
+        stock_id TEXT NOT NULL,
+        period   TEXT NOT NULL,
+        adjust   TEXT NOT NULL,
+        date     TEXT NOT NULL,
+        open     REAL NOT NULL,
+        high     REAL NOT NULL,
+        low      REAL NOT NULL,
+        close    REAL NOT NULL,
+        volume   REAL NOT NULL,
+        amount   REAL NOT NULL,
+        PRIMARY KEY (stock_id, period, adjust, date)
+    );
+";
+
+fn read_kline(row: &rusqlite::Row) -> rusqlite::Result<Kline> {
+    Ok(Kline {
+        date: row.get(0)?,
+        open: row.get(1)?,
+        high: row.get(2)?,
+        low: row.get(3)?,
+        close: row.get(4)?,
+        volume: row.get(5)?,
+        amount: row.get(6)?,
+    })
+}
+
+fn period_key(period: KlinePeriod) -> &'static str {
+    match period {
+        KlinePeriod::Day => "day",
+        KlinePeriod::Week => "week",
+        KlinePeriod::Month => "month",
+    }
+}
+
+fn adjust_key(adjust: Adjust) -> &'static str {
+    match adjust {
+        Adjust::None => "none",
+        Adjust::Forward => "forward",
+        Adjust::Backward => "backward",
+    }
+}
+
+/// SQLite 整数为 i64，超出范围的查询边界截断
+fn sql_ts(timestamp: u64) -> i64 {
+    timestamp.min(i64::MAX as u64) as i64
+}
+
+/// 周期起始时间：分钟周期按整点对齐，日线按北京时间自然日对齐
+fn bucket_start(timestamp: u64, interval: BarInterval) -> u64 {
+    let size = match interval {
+        BarInterval::Min1 => MINUTE_MS,
+        BarInterval::Min5 => 5 * MINUTE_MS,
+        BarInterval::Min15 => 15 * MINUTE_MS,
+        BarInterval::Min30 => 30 * MINUTE_MS,
+        BarInterval::Min60 => 60 * MINUTE_MS,
+        BarInterval::Day => DAY_MS,
+    };
+    let local = timestamp + CHINA_OFFSET_MS;
+    local - local % size - CHINA_OFFSET_MS
+}
+
+/// 将 1 分钟 K 线（按时间升序）聚合为更大周期
+fn aggregate(bars: Vec<Bar>, interval: BarInterval) -> Vec<Bar> {
+    let mut result: Vec<Bar> = Vec::new();
+    for bar in bars {
+        let start = bucket_start(bar.timestamp, interval);
+        match result.last_mut() {
+            Some(last) if last.timestamp == start => {
+                last.high = last.high.max(bar.high);
+                last.low = last.low.min(bar.low);
+                last.close = bar.close;
+            }
+            _ => result.push(Bar {
+                timestamp: start,
+                ..bar
+            }),
+        }
+    }
+    result
+}
+
+/// 本地行情存储（配置目录下的 ticks.db）
+/// - ticks: 轮询获取的每条行情，按 tick_retention_days 清理
+/// - bars_1m: 由行情实时聚合的 1 分钟 K 线，默认永久保留，其他周期查询时再聚合
+///
+/// 数据库在首次访问时才创建，从未开启存储也未查询时不会生成文件
+pub struct TickStore {
+    conn: Mutex<Option<Connection>>,
+    last_prune: Mutex<Option<Instant>>,
+}
+
+impl TickStore {
+    pub fn new() -> Self {
+        Self {
+            conn: Mutex::new(None),
+            last_prune: Mutex::new(None),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn open_in_memory() -> Self {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        Self {
+            conn: Mutex::new(Some(conn)),
+            last_prune: Mutex::new(None),
+        }
+    }
+
+    /// 在数据库连接上执行操作，必要时打开数据库
+    fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        let mut guard = self.conn.lock().unwrap();
+        if guard.is_none() {
+            let path = get_config_dir()?.join("ticks.db");
+            let conn = Connection::open(&path).context("打开行情数据库失败")?;
+            conn.execute_batch(SCHEMA).context("初始化行情数据库失败")?;
+            *guard = Some(conn);
+        }
+        f(guard.as_mut().unwrap())
+    }
+
+    /// 记录一批行情，并更新对应的 1 分钟 K 线（旧数据不记录）
+    pub fn insert(&self, quotes: &[PriceUpdate]) -> Result<()> {
+        let quotes: Vec<&PriceUpdate> = quotes.iter().filter(|q| !q.stale && q.price > 0.0).collect();
+        if quotes.is_empty() {
+            return Ok(());
+        }
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut insert_tick = tx.prepare_cached(
+                    "INSERT INTO ticks (stock_id, ts, price, prev_close) VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                let mut upsert_bar = tx.prepare_cached(
+                    "INSERT INTO bars_1m (stock_id, ts, open, high, low, close)
+                     VALUES (?1, ?2, ?3, ?3, ?3, ?3)
+                     ON CONFLICT (stock_id, ts) DO UPDATE SET
+                        high = max(high, excluded.high),
+                        low = min(low, excluded.low),
+                        close = excluded.close",
+                )?;
+                for q in quotes {
+                    insert_tick.execute(params![q.id, q.timestamp as i64, q.price, q.prev_close])?;
+                    let minute = bucket_start(q.timestamp, BarInterval::Min1);
+                    upsert_bar.execute(params![q.id, minute as i64, q.price])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    /// 按保留策略删除过期数据
+    pub fn prune(&self, now: u64, storage: &StorageConfig) -> Result<()> {
+        self.with_conn(|conn| {
+            let tick_cutoff = now.saturating_sub(storage.tick_retention_days as u64 * DAY_MS);
+            let removed = conn.execute("DELETE FROM ticks WHERE ts < ?1", params![tick_cutoff as i64])?;
+            let mut removed_bars = 0;
+            if storage.bar_retention_days > 0 {
+                let bar_cutoff = now.saturating_sub(storage.bar_retention_days as u64 * DAY_MS);
+                removed_bars = conn.execute("DELETE FROM bars_1m WHERE ts < ?1", params![bar_cutoff as i64])?;
+            }
+            if removed > 0 || removed_bars > 0 {
+                log::info!("清理过期行情: 逐笔 {} 条, 1 分钟 K 线 {} 条", removed, removed_bars);
+            }
+            Ok(())
+        })
+    }
+
+    /// 查询 [start, end] 区间内的 K 线（毫秒，按周期起始时间），按时间升序
+    pub fn bars(&self, stock_id: &str, interval: BarInterval, start: u64, end: u64) -> Result<Vec<Bar>> {
+        let bars = self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT ts, open, high, low, close FROM bars_1m
+                 WHERE stock_id = ?1 AND ts >= ?2 AND ts <= ?3 ORDER BY ts",
+            )?;
+            let rows = stmt.query_map(params![stock_id, sql_ts(start), sql_ts(end)], |row| {
+                Ok(Bar {
+                    timestamp: row.get::<_, i64>(0)? as u64,
+                    open: row.get(1)?,
+                    high: row.get(2)?,
+                    low: row.get(3)?,
+                    close: row.get(4)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })?;
+        Ok(aggregate(bars, interval))
+    }
+
+    /// 查询 [start, end] 区间内的逐笔行情，按时间升序
+    pub fn ticks(&self, stock_id: &str, start: u64, end: u64) -> Result<Vec<Tick>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT ts, price, prev_close FROM ticks
+                 WHERE stock_id = ?1 AND ts >= ?2 AND ts <= ?3 ORDER BY ts",
+            )?;
+            let rows = stmt.query_map(params![stock_id, sql_ts(start), sql_ts(end)], |row| {
+                Ok(Tick {
+                    timestamp: row.get::<_, i64>(0)? as u64,
+                    price: row.get(1)?,
+                    prev_close: row.get(2)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// 写入或覆盖历史 K 线（按日期升序），缓存中不早于第一根的旧数据先删除
+    /// 周线、月线以周期内最后一个交易日为日期，未走完的一根每天日期都会变化，不删除会留下重复的 K 线
+    pub fn upsert_klines(&self, stock_id: &str, period: KlinePeriod, adjust: Adjust, klines: &[Kline]) -> Result<()> {
+        let Some(first) = klines.first() else {
+            return Ok(());
+        };
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM klines WHERE stock_id = ?1 AND period = ?2 AND adjust = ?3 AND date >= ?4",
+                params![stock_id, period_key(period), adjust_key(adjust), first.date],
+            )?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO klines
+                     (stock_id, period, adjust, date, open, high, low, close, volume, amount)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                )?;
+                for k in klines {
+                    stmt.execute(params![
+                        stock_id,
+                        period_key(period),
+                        adjust_key(adjust),
+                        k.date,
+                        k.open,
+                        k.high,
+                        k.low,
+                        k.close,
+                        k.volume,
+                        k.amount
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    /// 删除某只股票某周期、复权方式下的全部 K 线（前复权数据因除权需整体重建时使用）
+    pub fn clear_klines(&self, stock_id: &str, period: KlinePeriod, adjust: Adjust) -> Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "DELETE FROM klines WHERE stock_id = ?1 AND period = ?2 AND adjust = ?3",
+                params![stock_id, period_key(period), adjust_key(adjust)],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// 查询 [start, end] 区间内缓存的历史 K 线，按日期升序，边界为空时不限制
+    /// 日期以文本存储，不能用 NaiveDate::MIN/MAX 代替空边界（"+262142-12-31" 排在所有日期之前）
+    pub fn klines(
+        &self,
+        stock_id: &str,
+        period: KlinePeriod,
+        adjust: Adjust,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> Result<Vec<Kline>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT date, open, high, low, close, volume, amount FROM klines
+                 WHERE stock_id = ?1 AND period = ?2 AND adjust = ?3
+                   AND (?4 IS NULL OR date >= ?4) AND (?5 IS NULL OR date <= ?5)
+                 ORDER BY date",
+            )?;
+            let rows = stmt.query_map(
+                params![stock_id, period_key(period), adjust_key(adjust), start, end],
+                read_kline,
+            )?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// 缓存中最新的 count 根 K 线，按日期降序
+    pub fn latest_klines(&self, stock_id: &str, period: KlinePeriod, adjust: Adjust, count: usize) -> Result<Vec<Kline>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT date, open, high, low, close, volume, amount FROM klines
+                 WHERE stock_id = ?1 AND period = ?2 AND adjust = ?3
+                 ORDER BY date DESC LIMIT ?4",
+            )?;
+            let rows = stmt.query_map(
+                params![stock_id, period_key(period), adjust_key(adjust), count as i64],
+                read_kline,
+            )?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+    }
+
+    /// 距上次清理超过 PRUNE_INTERVAL 时执行清理
+    fn prune_if_due(&self, now: u64, storage: &StorageConfig) {
+        let mut last = self.last_prune.lock().unwrap();
+        if last.is_some_and(|t| t.elapsed() < PRUNE_INTERVAL) {
+            return;
+        }
+        *last = Some(Instant::now());
+        if let Err(e) = self.prune(now, storage) {
+            log::error!("清理过期行情失败: {}", e);
+        }
+    }
+}
+
+impl Default for TickStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 订阅轮询器发出的行情事件（关键帧与增量），在开启存储且处于交易时段时写入数据库
+/// 收盘后、午休和非交易日轮询器仍会定期推送关键帧，这些行情不记录，避免产生交易时段外的 K 线
+/// 事件回调只做过滤和解析，数据库写入由单独的任务串行执行，不阻塞事件分发
+pub fn attach(app: &AppHandle, config_store: Arc<ConfigStore>, store: Arc<TickStore>) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<PriceUpdate>>();
+
+    for event_name in ["price-update", "price-delta"] {
+        let config_store = config_store.clone();
+        let tx = tx.clone();
+        app.listen_any(event_name, move |event| {
+            if !config_store.get().storage.enabled || !calendar::market_phase(calendar::china_now()).is_trading() {
+                return;
+            }
+            match serde_json::from_str(event.payload()) {
+                Ok(quotes) => {
+                    let _ = tx.send(quotes);
+                }
+                Err(e) => log::warn!("解析行情事件失败: {}", e),
+            }
+        });
+    }
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(quotes) = rx.recv().await {
+            let storage = config_store.get().storage;
+            let store = store.clone();
+            let written = tokio::task::spawn_blocking(move || {
+                if let Err(e) = store.insert(&quotes) {
+                    log::error!("写入行情数据库失败: {}", e);
+                }
+                let now = quotes.iter().map(|q| q.timestamp).max().unwrap_or(0);
+                store.prune_if_due(now, &storage);
+            })
+            .await;
+            if let Err(e) = written {
+                log::error!("行情写入任务异常: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    /// 2024-06-03 09:30 北京时间
+    const OPEN: u64 = 1_717_378_200_000;
+
+    fn quote(price: f64, timestamp: u64) -> PriceUpdate {
+        PriceUpdate {
+            timestamp,
+            ..test_support::quote("sh600000", price, 10.0)
+        }
+    }
+
+    #[test]
+    fn test_ticks_aggregate_into_bars() {
+        let store = TickStore::open_in_memory();
+        let quotes = [
+            quote(10.0, OPEN),
+            quote(10.5, OPEN + 20_000),
+            quote(9.8, OPEN + 40_000),
+            quote(10.1, OPEN + MINUTE_MS + 5_000),
+            quote(10.3, OPEN + 6 * MINUTE_MS),
+        ];
+        for q in &quotes {
+            store.insert(std::slice::from_ref(q)).unwrap();
+        }
+
+        let bars = store.bars("sh600000", BarInterval::Min1, 0, u64::MAX).unwrap();
+        assert_eq!(bars.len(), 3);
+        assert_eq!(bars[0], Bar { timestamp: OPEN, open: 10.0, high: 10.5, low: 9.8, close: 9.8 });
+
+        // 09:30 与 09:31 属于 09:30-09:35，09:36 属于下一根
+        let bars = store.bars("sh600000", BarInterval::Min5, 0, u64::MAX).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0], Bar { timestamp: OPEN, open: 10.0, high: 10.5, low: 9.8, close: 10.1 });
+
+        let days = store.bars("sh600000", BarInterval::Day, 0, u64::MAX).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].timestamp, OPEN - (9 * 60 + 30) * MINUTE_MS);
+        assert_eq!(days[0].close, 10.3);
+
+        assert_eq!(store.ticks("sh600000", 0, u64::MAX).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_prune_keeps_bars_by_default() {
+        let store = TickStore::open_in_memory();
+        store.insert(&[quote(10.0, OPEN)]).unwrap();
+        let mut stale = quote(11.0, OPEN + 1000);
+        stale.stale = true;
+        store.insert(&[stale]).unwrap();
+
+        let storage = StorageConfig::default();
+        store.prune(OPEN + 8 * DAY_MS, &storage).unwrap();
+        assert!(store.ticks("sh600000", 0, u64::MAX).unwrap().is_empty());
+        assert_eq!(store.bars("sh600000", BarInterval::Min1, 0, u64::MAX).unwrap().len(), 1);
+
+        let storage = StorageConfig { bar_retention_days: 7, ..storage };
+        store.prune(OPEN + 8 * DAY_MS, &storage).unwrap();
+        assert!(store.bars("sh600000", BarInterval::Min1, 0, u64::MAX).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_kline_cache_upsert_and_latest() {
+        let store = TickStore::open_in_memory();
+        let kline = |d: &str, close: f64| Kline {
+            date: d.parse().unwrap(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 100.0,
+            amount: close * 100.0,
+        };
+        let (period, adjust) = (KlinePeriod::Day, Adjust::Forward);
+        store
+            .upsert_klines("sh600000", period, adjust, &[kline("2024-05-31", 10.0), kline("2024-06-03", 10.2)])
+            .unwrap();
+        // 同一日期覆盖（未收盘的最后一根会随补全更新）
+        store.upsert_klines("sh600000", period, adjust, &[kline("2024-06-03", 10.4)]).unwrap();
+
+        let latest = store.latest_klines("sh600000", period, adjust, 2).unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].close, 10.4);
+        assert!(store.latest_klines("sh600000", period, Adjust::None, 1).unwrap().is_empty());
+
+        // 不限区间时返回全部，按区间查询时只返回区间内的 K 线
+        assert_eq!(store.klines("sh600000", period, adjust, None, None).unwrap().len(), 2);
+        let date = "2024-06-03".parse().ok();
+        assert_eq!(store.klines("sh600000", period, adjust, date, None).unwrap().len(), 1);
+        assert_eq!(store.klines("sh600000", period, adjust, None, date).unwrap().len(), 2);
+
+        // 周线本周的一根日期变化后，旧日期的一根被替换
+        let week = KlinePeriod::Week;
+        store.upsert_klines("sh600000", week, adjust, &[kline("2024-05-31", 10.0), kline("2024-06-03", 10.2)]).unwrap();
+        store.upsert_klines("sh600000", week, adjust, &[kline("2024-05-31", 10.0), kline("2024-06-04", 10.3)]).unwrap();
+        let weeks = store.klines("sh600000", week, adjust, None, None).unwrap();
+        assert_eq!(weeks.iter().map(|k| k.close).collect::<Vec<_>>(), [10.0, 10.3]);
+
+        store.clear_klines("sh600000", period, adjust).unwrap();
+        assert!(store.klines("sh600000", period, adjust, None, None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use crate::groups;
+use crate::models::{Config, Stock, UndoState, WatchGroup};
+
+/// 保留的撤销步数
+const MAX_HISTORY: usize = 50;
+
+/// 可撤销的自选股状态：股票列表和分组（含顺序与显示状态）
+/// 当前分组不属于编辑内容，撤销时保持不变
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    stocks: Vec<Stock>,
+    groups: Vec<WatchGroup>,
+}
+
+impl Snapshot {
+    fn of(config: &Config) -> Self {
+        Self {
+            stocks: config.stocks.clone(),
+            groups: config.groups.clone(),
+        }
+    }
+
+    /// 将快照恢复到配置中
+    /// 持仓由交易流水和持仓设置维护，不随撤销回退：现有股票保留当前持仓，
+    /// 快照中没有但当前有持仓的股票也保留（由 normalize 放回当前分组）
+    fn restore(self, config: &mut Config) {
+        let mut current: HashMap<String, Stock> =
+            config.stocks.drain(..).map(|s| (s.id.clone(), s)).collect();
+        config.stocks = self
+            .stocks
+            .into_iter()
+            .map(|mut stock| {
+                if let Some(existing) = current.remove(&stock.id) {
+                    stock.holding = existing.holding;
+                }
+                stock
+            })
+            .collect();
+        config
+            .stocks
+            .extend(current.into_values().filter(|s| s.holding.is_some()));
+        config.groups = self.groups;
+        groups::normalize(config);
+    }
+}
+
+/// 自选股编辑历史（添加、删除、排序、分组修改、导入等）
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl EditHistory {
+    /// 记录一次编辑：before 为编辑前的配置，after 为编辑后的配置
+    /// 自选股没有变化时（如只修改了外观设置）不记录；新的编辑会清空重做记录
+    pub fn record(&mut self, before: &Config, after: &Config) {
+        let snapshot = Snapshot::of(before);
+        if snapshot == Snapshot::of(after) {
+            return;
+        }
+        self.undo.push(snapshot);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// 撤销上一次编辑，返回撤销后的配置；没有可撤销的编辑时返回 None
+    /// 只有调用 commit_undo 后才从历史中移除，保存失败时历史保持不变
+    pub fn undo(&self, config: &Config) -> Option<Config> {
+        Self::apply(self.undo.last()?, config)
+    }
+
+    /// 重做上一次撤销的编辑，返回重做后的配置
+    pub fn redo(&self, config: &Config) -> Option<Config> {
+        Self::apply(self.redo.last()?, config)
+    }
+
+    fn apply(snapshot: &Snapshot, config: &Config) -> Option<Config> {
+        let mut updated = config.clone();
+        snapshot.clone().restore(&mut updated);
+        Some(updated)
+    }
+
+    /// 撤销结果已保存：before 为撤销前的配置，存入重做记录
+    pub fn commit_undo(&mut self, before: &Config) {
+        self.undo.pop();
+        self.redo.push(Snapshot::of(before));
+    }
+
+    /// 重做结果已保存：before 为重做前的配置，存回撤销记录
+    pub fn commit_redo(&mut self, before: &Config) {
+        self.redo.pop();
+        self.undo.push(Snapshot::of(before));
+    }
+
+    /// 清空历史（切换配置方案、配置文件被外部修改后，旧的快照不再适用）
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn state(&self) -> UndoState {
+        UndoState {
+            can_undo: !self.undo.is_empty(),
+            can_redo: !self.redo.is_empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Holding;
+    use crate::sources::make_stock;
+
+    fn ids(config: &Config) -> Vec<String> {
+        groups::active_stocks(config).into_iter().map(|s| s.id).collect()
+    }
+
+    fn with_stocks(config: &Config, codes: &[&str]) -> Config {
+        let mut updated = Config {
+            stocks: codes.iter().map(|c| make_stock(c)).collect(),
+            ..config.clone()
+        };
+        updated.groups[0].members.clear();
+        groups::normalize(&mut updated);
+        updated
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = EditHistory::default();
+        let v0 = Config::default();
+        let v1 = with_stocks(&v0, &["600519", "000001"]);
+        let v2 = with_stocks(&v1, &["000001"]);
+        history.record(&v0, &v1);
+        history.record(&v1, &v2);
+        // 自选股没有变化的修改不记录
+        let mut styled = v2.clone();
+        styled.window.opacity = 0.5;
+        history.record(&v2, &styled);
+
+        // 撤销时保留后来设置的持仓和其他设置
+        styled.stocks[0].holding = Some(Holding {
+            shares: 100.0,
+            avg_cost: 10.0,
+            open_date: None,
+        });
+        let undone = history.undo(&styled).unwrap();
+        history.commit_undo(&styled);
+        assert_eq!(ids(&undone), ["sh600519", "sz000001"]);
+        assert!(undone.stocks[1].holding.is_some());
+        assert_eq!(undone.window.opacity, 0.5);
+        assert_eq!(history.state(), UndoState { can_undo: true, can_redo: true });
+
+        let redone = history.redo(&undone).unwrap();
+        history.commit_redo(&undone);
+        assert_eq!(ids(&redone), ["sz000001"]);
+
+        let undone = history.undo(&redone).unwrap();
+        history.commit_undo(&redone);
+        let first = history.undo(&undone).unwrap();
+        history.commit_undo(&undone);
+        // 有持仓的股票不会因撤销被删除
+        assert_eq!(ids(&first), ["sh600519", "sz000001"]);
+        assert!(history.undo(&first).is_none());
+
+        // 新的编辑清空重做记录
+        history.record(&first, &v2);
+        assert_eq!(history.state(), UndoState { can_undo: true, can_redo: false });
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use anyhow::Result;
+use serde_json::Value;
+use tauri_plugin_global_shortcut::Shortcut;
+
+use crate::{alerts, groups};
+use crate::models::{Config, Stock};
+use crate::sources;
+
+/// 刷新、轮播等定时器间隔的下限（毫秒），0 会使 tokio interval panic
+pub const MIN_INTERVAL_MS: u64 = 500;
+
+/// 单只股票价格小数位数的上限
+pub const MAX_DECIMALS: u8 = 4;
+
+/// 快捷键冲突的错误信息前缀
+const SHORTCUT_CONFLICT: &str = "conflicts with";
+
+/// 单个字段的校验错误，path 为 JSON 路径，如 app.refresh_interval_ms、stocks[2].id
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.path, self.message)
+    }
+}
+
+/// 按字段收集错误
+#[derive(Default)]
+struct Errors(Vec<FieldError>);
+
+impl Errors {
+    fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    fn at_least(&mut self, path: &str, value: u64, min: u64) {
+        if value < min {
+            self.push(path, format!("must be >= {}", min));
+        }
+    }
+
+    fn one_of(&mut self, path: &str, value: &str, allowed: &[&str]) {
+        if !allowed.contains(&value) {
+            self.push(path, format!("must be one of {}", allowed.join(", ")));
+        }
+    }
+
+    fn color(&mut self, path: &str, value: &str) {
+        if !is_color(value) {
+            self.push(path, "must be a color like #rrggbb");
+        }
+    }
+
+    fn rate(&mut self, path: &str, value: f64) {
+        if !(0.0..1.0).contains(&value) {
+            self.push(path, "must be between 0 and 1");
+        }
+    }
+
+    fn positive(&mut self, path: &str, value: f64) {
+        if !(value.is_finite() && value > 0.0) {
+            self.push(path, "must be > 0");
+        }
+    }
+}
+
+/// #rgb、#rrggbb 或 #rrggbbaa
+fn is_color(value: &str) -> bool {
+    value
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// 配置方案名称是否有效：用作 profiles 目录下的文件名，不能包含路径分隔符等字符
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && name.chars().count() <= 32
+        && !name.starts_with('.')
+        && !name.chars().any(|c| c.is_control() || "/\\:*?\"<>|".contains(c))
+}
+
+/// 校验单只股票的市场、代码和 ID，返回是否有效
+fn stock_errors(errors: &mut Errors, path: &str, stock: &Stock) -> bool {
+    let before = errors.0.len();
+    if !matches!(stock.market.as_str(), "sh" | "sz") {
+        errors.push(format!("{}.market", path), "must be sh or sz");
+    }
+    if stock.code.len() != 6 || !stock.code.chars().all(|c| c.is_ascii_digit()) {
+        errors.push(format!("{}.code", path), "must be 6 digits");
+    }
+    if stock.id != sources::make_stock_id(&stock.market, &stock.code) {
+        errors.push(format!("{}.id", path), "must be market followed by code");
+    }
+    errors.0.len() == before
+}
+
+/// 股票代码是否有效（添加股票时使用）
+pub fn is_valid_stock(stock: &Stock) -> bool {
+    stock_errors(&mut Errors::default(), "stock", stock)
+}
+
+/// 校验配置，返回所有字段错误（为空表示有效）
+pub fn validate(config: &Config) -> Vec<FieldError> {
+    let mut errors = Errors::default();
+
+    if !is_valid_profile_name(&config.profile) {
+        errors.push("profile", "must be a valid profile name");
+    }
+
+    let window = &config.window;
+    if !(0.0..=1.0).contains(&window.opacity) {
+        errors.push("window.opacity", "must be between 0 and 1");
+    }
+    errors.at_least("window.display_rows", window.display_rows as u64, 1);
+    errors.at_least("window.carousel_interval_ms", window.carousel_interval_ms, MIN_INTERVAL_MS);
+    errors.at_least("window.size.width", window.size.width as u64, 1);
+    errors.at_least("window.size.height", window.size.height as u64, 1);
+
+    let app = &config.app;
+    errors.one_of("app.theme", &app.theme, &["auto", "light", "dark"]);
+    errors.one_of("app.font_size", &app.font_size, &["small", "medium", "large"]);
+    errors.color("app.up_color", &app.up_color);
+    errors.color("app.down_color", &app.down_color);
+    errors.color("app.neutral_color", &app.neutral_color);
+    errors.at_least("app.refresh_interval_ms", app.refresh_interval_ms, MIN_INTERVAL_MS);
+    errors.at_least("app.idle_refresh_interval_ms", app.idle_refresh_interval_ms, MIN_INTERVAL_MS);
+    errors.at_least("app.idle_threshold_secs", app.idle_threshold_secs, 1);
+    if app.data_sources.is_empty() {
+        errors.push("app.data_sources", "must not be empty");
+    }
+    let mut seen_sources = HashSet::new();
+    for (i, name) in app.data_sources.iter().enumerate() {
+        let path = format!("app.data_sources[{}]", i);
+        if sources::create_source(name).is_none() {
+            errors.push(path, format!("unknown data source \"{}\"", name));
+        } else if !seen_sources.insert(name) {
+            errors.push(path, format!("duplicate data source \"{}\"", name));
+        }
+    }
+
+    let shortcuts = &config.shortcuts;
+    let mut seen_shortcuts: HashMap<Shortcut, &str> = HashMap::new();
+    for (name, value) in [
+        ("toggle_visible", &shortcuts.toggle_visible),
+        ("next_stock", &shortcuts.next_stock),
+        ("prev_stock", &shortcuts.prev_stock),
+        ("open_settings", &shortcuts.open_settings),
+        ("open_detail", &shortcuts.open_detail),
+        ("cycle_group", &shortcuts.cycle_group),
+        ("cycle_profile", &shortcuts.cycle_profile),
+    ] {
+        // 空字符串表示未设置
+        if value.is_empty() {
+            continue;
+        }
+        let path = format!("shortcuts.{}", name);
+        match value.parse::<Shortcut>() {
+            Ok(shortcut) => {
+                if let Some(other) = seen_shortcuts.insert(shortcut, name) {
+                    errors.push(path, format!("{} shortcuts.{}", SHORTCUT_CONFLICT, other));
+                }
+            }
+            Err(_) => errors.push(path, format!("is not a valid shortcut: \"{}\"", value)),
+        }
+    }
+
+    let mut seen_stocks = HashMap::new();
+    for (i, stock) in config.stocks.iter().enumerate() {
+        let path = format!("stocks[{}]", i);
+        let overrides = &stock.overrides;
+        if overrides.decimals.is_some_and(|d| d > MAX_DECIMALS) {
+            errors.push(format!("{}.overrides.decimals", path), format!("must be between 0 and {}", MAX_DECIMALS));
+        }
+        if let Some(color) = &overrides.up_color {
+            errors.color(&format!("{}.overrides.up_color", path), color);
+        }
+        if let Some(color) = &overrides.down_color {
+            errors.color(&format!("{}.overrides.down_color", path), color);
+        }
+        if let Some(holding) = &stock.holding {
+            errors.positive(&format!("{}.holding.shares", path), holding.shares);
+            errors.positive(&format!("{}.holding.avg_cost", path), holding.avg_cost);
+        }
+        if !stock_errors(&mut errors, &path, stock) {
+            continue;
+        }
+        if let Some(first) = seen_stocks.get(stock.id.as_str()) {
+            errors.push(format!("{}.id", path), format!("duplicates stocks[{}].id", first));
+        } else {
+            seen_stocks.insert(stock.id.as_str(), i);
+        }
+    }
+
+    let mut seen_groups = HashSet::new();
+    for (i, group) in config.groups.iter().enumerate() {
+        if group.id.is_empty() {
+            errors.push(format!("groups[{}].id", i), "must not be empty");
+        } else if !seen_groups.insert(group.id.as_str()) {
+            errors.push(format!("groups[{}].id", i), format!("duplicate group id \"{}\"", group.id));
+        }
+        if group.name.trim().is_empty() {
+            errors.push(format!("groups[{}].name", i), "must not be empty");
+        }
+    }
+
+    errors.at_least("api.port", config.api.port as u64, 1024);
+
+    let fees = &config.portfolio.fees;
+    errors.rate("portfolio.fees.commission_rate", fees.commission_rate);
+    errors.rate("portfolio.fees.stamp_duty_rate", fees.stamp_duty_rate);
+    errors.rate("portfolio.fees.transfer_fee_rate", fees.transfer_fee_rate);
+    if fees.min_commission.is_nan() || fees.min_commission < 0.0 {
+        errors.push("portfolio.fees.min_commission", "must be >= 0");
+    }
+
+    errors.at_least("storage.tick_retention_days", config.storage.tick_retention_days as u64, 1);
+
+    let mut seen_alerts = HashSet::new();
+    for (i, rule) in config.alerts.iter().enumerate() {
+        if !seen_alerts.insert(rule.id.as_str()) {
+            errors.push(format!("alerts[{}].id", i), format!("duplicate alert id \"{}\"", rule.id));
+        }
+        if let Err(e) = alerts::validate(&rule.condition, rule.interval) {
+            errors.push(format!("alerts[{}].condition", i), e.to_string());
+        }
+    }
+
+    errors.0
+}
+
+/// 校验配置，无效时返回列出全部字段错误的 Err
+pub fn check(config: &Config) -> Result<()> {
+    let errors = validate(config);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let list: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    anyhow::bail!("配置无效: {}", list.join("; "))
+}
+
+/// 将字段路径转换为 JSON Pointer，返回 (pointer, 路径中最后一个数组下标所在元素的 pointer)
+fn pointers(path: &str) -> (String, Option<String>) {
+    let mut pointer = String::new();
+    let mut item = None;
+    for part in path.split('.') {
+        let (key, index) = match part.split_once('[') {
+            Some((key, rest)) => (key, rest.strip_suffix(']')),
+            None => (part, None),
+        };
+        pointer.push('/');
+        pointer.push_str(key);
+        if let Some(index) = index {
+            pointer.push('/');
+            pointer.push_str(index);
+            item = Some(pointer.clone());
+        }
+    }
+    (pointer, item)
+}
+
+/// 修复无效配置（加载时使用）：数组中的无效条目被移除，其他无效字段恢复默认值
+/// 与前面的快捷键冲突的快捷键被清除（恢复默认值可能再次冲突，如 v1 配置中
+/// 已占用新增快捷键的默认组合）
+/// 修复后仍无效（理论上不会发生）时只将出错的顶层部分恢复默认，不会丢失自选股等其他部分
+pub fn repair(config: Config) -> Config {
+    let defaults = serde_json::to_value(Config::default()).unwrap_or(Value::Null);
+    let mut config = config;
+    // 每轮修复都可能暴露新的错误（如移除条目后的重复项），最多重复几次
+    for _ in 0..4 {
+        // 移除无效股票或分组后重新整理分组成员
+        groups::normalize(&mut config);
+        let errors = validate(&config);
+        if errors.is_empty() {
+            return config;
+        }
+        let Ok(mut value) = serde_json::to_value(&config) else {
+            break;
+        };
+        let mut removed: Vec<String> = Vec::new();
+        for error in &errors {
+            log::warn!("配置字段无效，已修复: {}", error);
+            let (pointer, item) = pointers(&error.path);
+            match item {
+                // 单只股票的可选设置无效时只清除该项，不移除股票
+                Some(_) if error.path.contains(".overrides.") => {
+                    if let Some(slot) = value.pointer_mut(&pointer) {
+                        *slot = Value::Null;
+                    }
+                }
+                // 持仓数据无效时清除持仓，保留股票
+                Some(item) if error.path.contains(".holding.") => {
+                    if let Some(slot) = value.pointer_mut(&format!("{}/holding", item)) {
+                        *slot = Value::Null;
+                    }
+                }
+                Some(item) => {
+                    // 先置为 null，全部处理完再统一移除，避免下标错位
+                    if let Some(slot) = value.pointer_mut(&item) {
+                        *slot = Value::Null;
+                    }
+                    if let Some((array, _)) = item.rsplit_once('/') {
+                        removed.push(array.to_string());
+                    }
+                }
+                None if error.message.starts_with(SHORTCUT_CONFLICT) => {
+                    if let Some(slot) = value.pointer_mut(&pointer) {
+                        *slot = Value::String(String::new());
+                    }
+                }
+                None => {
+                    if let (Some(slot), Some(default)) = (value.pointer_mut(&pointer), defaults.pointer(&pointer)) {
+                        *slot = default.clone();
+                    }
+                }
+            }
+        }
+        for array in removed {
+            if let Some(Value::Array(items)) = value.pointer_mut(&array) {
+                items.retain(|v| !v.is_null());
+            }
+        }
+        match serde_json::from_value(value) {
+            Ok(repaired) => config = repaired,
+            Err(_) => break,
+        }
+    }
+    let remaining = validate(&config);
+    if remaining.is_empty() {
+        return config;
+    }
+    let Ok(mut value) = serde_json::to_value(&config) else {
+        return config;
+    };
+    for error in &remaining {
+        let section = error.path.split(['.', '[']).next().unwrap_or_default();
+        log::warn!("配置 {} 无法修复，已恢复默认: {}", section, error);
+        if let (Some(slot), Some(default)) = (value.get_mut(section), defaults.get(section)) {
+            *slot = default.clone();
+        }
+    }
+    let mut repaired = serde_json::from_value(value).unwrap_or(config);
+    groups::normalize(&mut repaired);
+    repaired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Holding, ShortcutConfig};
+
+    fn paths(config: &Config) -> Vec<String> {
+        validate(config).into_iter().map(|e| e.path).collect()
+    }
+
+    fn stock(id: &str) -> Stock {
+        sources::make_stock(id)
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(validate(&Config::default()).is_empty());
+        assert!(check(&Config::default()).is_ok());
+    }
+
+    #[test]
+    fn test_field_paths() {
+        let mut config = Config::default();
+        config.app.refresh_interval_ms = 0;
+        config.window.opacity = 1.5;
+        config.app.up_color = "red".to_string();
+        config.app.data_sources = vec!["tencent".to_string(), "yahoo".to_string()];
+        config.shortcuts.next_stock = "Ctrl+Nope".to_string();
+        config.shortcuts.prev_stock = config.shortcuts.toggle_visible.clone();
+        config.stocks = vec![stock("600519"), stock("000001"), stock("sh600519")];
+
+        assert_eq!(
+            paths(&config),
+            [
+                "window.opacity",
+                "app.up_color",
+                "app.refresh_interval_ms",
+                "app.data_sources[1]",
+                "shortcuts.next_stock",
+                "shortcuts.prev_stock",
+                "stocks[2].id",
+            ]
+        );
+        let message = check(&config).unwrap_err().to_string();
+        assert!(message.contains("app.refresh_interval_ms must be >= 500"));
+        assert!(message.contains("stocks[2].id duplicates stocks[0].id"));
+        assert!(message.contains("shortcuts.prev_stock conflicts with shortcuts.toggle_visible"));
+    }
+
+    #[test]
+    fn test_profile_names() {
+        for name in ["默认", "Office", "家里 2"] {
+            assert!(is_valid_profile_name(name), "{}", name);
+        }
+        for name in ["", " 办公", "../config", "a/b", "a\\b", ".hidden", "c:d"] {
+            assert!(!is_valid_profile_name(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_repair_resets_fields_and_drops_items() {
+        let mut config = Config::default();
+        config.app.refresh_interval_ms = 0;
+        config.app.up_color = "#00ff00".to_string();
+        config.app.down_color = "green".to_string();
+        config.app.data_sources = vec!["yahoo".to_string(), "tencent".to_string(), "sina".to_string()];
+        config.stocks = vec![stock("600519"), stock("abc"), stock("000001"), stock("sh600519")];
+        config.stocks[2].overrides.decimals = Some(3);
+        config.stocks[2].overrides.up_color = Some("red".to_string());
+        assert!(paths(&config).contains(&"stocks[2].overrides.up_color".to_string()));
+        config.stocks[3].holding = Some(Holding { shares: f64::NAN, avg_cost: 10.0, open_date: None });
+        config.stocks[0].holding = Some(Holding { shares: 100.0, avg_cost: -1.0, open_date: None });
+        assert!(paths(&config).contains(&"stocks[3].holding.shares".to_string()));
+        assert!(paths(&config).contains(&"stocks[0].holding.avg_cost".to_string()));
+
+        let repaired = repair(config);
+        assert!(validate(&repaired).is_empty());
+        let defaults = Config::default();
+        assert_eq!(repaired.app.refresh_interval_ms, defaults.app.refresh_interval_ms);
+        assert_eq!(repaired.app.down_color, defaults.app.down_color);
+        // 有效字段保持不变
+        assert_eq!(repaired.app.up_color, "#00ff00");
+        assert_eq!(repaired.app.data_sources, ["tencent", "sina"]);
+        let ids: Vec<&str> = repaired.stocks.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["sh600519", "sz000001"]);
+        // 无效的单只股票设置只清除该项
+        assert_eq!(repaired.stocks[1].overrides.decimals, Some(3));
+        assert_eq!(repaired.stocks[1].overrides.up_color, None);
+        // 无效的持仓只清除持仓
+        assert!(repaired.stocks[0].holding.is_none());
+    }
+
+    #[test]
+    fn test_repair_clears_conflicting_shortcuts() {
+        // v1 配置中的显示/隐藏快捷键与后来新增快捷键的默认值相同
+        let mut config = Config::default();
+        config.shortcuts.toggle_visible = "CommandOrControl+Shift+D".to_string();
+        config.shortcuts.cycle_group = "Nope+".to_string();
+        config.shortcuts.cycle_profile = config.shortcuts.next_stock.clone();
+        config.stocks = vec![stock("600519"), stock("000001")];
+        groups::normalize(&mut config);
+
+        let repaired = repair(config);
+        assert!(validate(&repaired).is_empty());
+        let shortcuts = &repaired.shortcuts;
+        assert_eq!(shortcuts.toggle_visible, "CommandOrControl+Shift+D");
+        assert_eq!(shortcuts.open_detail, "");
+        // 无效的快捷键恢复默认值，冲突的快捷键被清除
+        assert_eq!(shortcuts.cycle_group, ShortcutConfig::default().cycle_group);
+        assert_eq!(shortcuts.cycle_profile, "");
+        assert_eq!(repaired.stocks.len(), 2);
+        assert_eq!(repaired.groups[0].members.len(), 2);
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::{interval, Duration};
+
+use crate::config::{self, ConfigStore};
+use crate::AppState;
+
+/// 配置文件检查间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 启动配置文件监听：手动编辑 config.json 后自动重新加载
+/// 按修改时间和大小轮询，变化时读取内容；与本程序最近一次写入相同的内容视为自身写入并忽略
+/// 外部修改解析成功后替换当前配置并应用（快捷键、轮询等），解析失败时保留当前配置并发出 error 事件
+pub fn spawn_watcher(app: AppHandle, config_store: Arc<ConfigStore>) {
+    tauri::async_runtime::spawn(async move {
+        let path = match config::get_config_dir() {
+            Ok(dir) => dir.join("config.json"),
+            Err(e) => {
+                log::error!("无法监听配置文件: {}", e);
+                return;
+            }
+        };
+        let stamp = || -> Option<(SystemTime, u64)> {
+            let meta = std::fs::metadata(&path).ok()?;
+            Some((meta.modified().ok()?, meta.len()))
+        };
+
+        let mut ticker = interval(WATCH_INTERVAL);
+        let mut last_stamp = stamp();
+        loop {
+            ticker.tick().await;
+
+            // 文件暂时不存在（如被编辑器删除后重建）时等待下次检查
+            let current = stamp();
+            if current.is_none() || current == last_stamp {
+                continue;
+            }
+            last_stamp = current;
+
+            let content = match config_store.external_content() {
+                Ok(Some(content)) => content,
+                Ok(None) => continue,
+                Err(e) => {
+                    log::warn!("读取配置文件失败: {}", e);
+                    continue;
+                }
+            };
+            let config = match config::parse_content(&content) {
+                Ok(config) => config,
+                Err(e) => {
+                    log::warn!("外部修改的配置无效，保留当前配置: {:#}", e);
+                    let _ = app.emit(
+                        "error",
+                        serde_json::json!({
+                            "code": "CONFIG_RELOAD_ERROR",
+                            "message": format!("配置文件修改无效，未重新加载: {:#}", e)
+                        }),
+                    );
+                    continue;
+                }
+            };
+
+            let Some(state) = app.try_state::<AppState>() else {
+                continue;
+            };
+            log::info!("检测到配置文件被外部修改，重新加载");
+            let old_config = config_store.get();
+            config_store.reload_external(config, content);
+            crate::apply_config_change(&state, &app, &old_config);
+        }
+    });
+}
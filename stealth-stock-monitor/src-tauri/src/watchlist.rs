@@ -0,0 +1,568 @@
+use std::collections::HashSet;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::broker_import::{self, split_line};
+use crate::config::ConfigStore;
+use crate::groups;
+use crate::models::{
+    Config, GroupMember, ImportMode, Stock, TradingSoftware, WatchlistConflict,
+    WatchlistConflictKind, WatchlistFormat, WatchlistImport,
+};
+use crate::sources;
+
+/// CSV 中可作为代码列、别名列、显示列的表头（导出格式、config.json 字段名和常见中文列名）
+const CODE_HEADERS: &[&str] = &["code", "id", "symbol", "代码", "股票代码", "证券代码"];
+const ALIAS_HEADERS: &[&str] = &["alias", "name", "别名", "名称", "股票名称", "证券名称"];
+const VISIBLE_HEADERS: &[&str] = &["visible", "显示"];
+
+/// 导入内容中的一条自选股
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// 行号（JSON 为数组下标 + 1）
+    pub line: usize,
+    /// 原始代码，如 "600519.SH"
+    pub code: String,
+    pub alias: Option<String>,
+    pub visible: Option<bool>,
+}
+
+/// 导出的 JSON 条目
+#[derive(Serialize)]
+struct ExportItem<'a> {
+    code: &'a str,
+    alias: &'a str,
+    visible: bool,
+}
+
+/// 导出当前分组（按分组内顺序，含显示状态）
+pub fn export(config: &Config, format: WatchlistFormat) -> Result<String> {
+    let stocks = groups::active_stocks(config);
+    let content = match format {
+        WatchlistFormat::Json => {
+            let items: Vec<ExportItem> = stocks
+                .iter()
+                .map(|s| ExportItem {
+                    code: &s.id,
+                    alias: &s.alias,
+                    visible: s.visible,
+                })
+                .collect();
+            serde_json::to_string_pretty(&items).context("序列化自选股失败")?
+        }
+        WatchlistFormat::Csv => {
+            let mut csv = String::from("code,alias,visible\n");
+            for stock in &stocks {
+                // 别名含逗号时用双引号包裹（导入时按引号拆分，别名中的双引号去掉）
+                let alias = stock.alias.replace('"', "");
+                let alias = if alias.contains(',') { format!("\"{}\"", alias) } else { alias };
+                csv.push_str(&format!("{},{},{}\n", stock.id, alias, stock.visible));
+            }
+            csv
+        }
+        WatchlistFormat::Codes => stocks.iter().map(|s| format!("{}\n", s.id)).collect(),
+    };
+    Ok(content)
+}
+
+/// 按内容猜测格式：以 [ 或 { 开头为 JSON，首行含逗号或制表符为 CSV，否则为代码列表
+pub fn detect_format(content: &str) -> WatchlistFormat {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return WatchlistFormat::Json;
+    }
+    let first = trimmed.lines().next().unwrap_or("");
+    if first.contains(',') || first.contains('\t') {
+        WatchlistFormat::Csv
+    } else {
+        WatchlistFormat::Codes
+    }
+}
+
+/// 解析导入内容，只拆分条目，代码是否有效在 apply 中判断
+pub fn parse(content: &str, format: WatchlistFormat) -> Result<Vec<Entry>> {
+    let content = content.trim_start_matches('\u{feff}');
+    match format {
+        WatchlistFormat::Json => parse_json(content),
+        WatchlistFormat::Csv => Ok(parse_csv(content)),
+        WatchlistFormat::Codes => Ok(parse_codes(content)),
+    }
+}
+
+/// JSON：代码字符串数组、对象数组（code / id，可带 market、alias、visible），
+/// 或带 stocks 数组的对象（如导出的 config.json）
+fn parse_json(content: &str) -> Result<Vec<Entry>> {
+    let value: Value = serde_json::from_str(content).context("不是有效的 JSON")?;
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(obj) => obj
+            .get("stocks")
+            .and_then(Value::as_array)
+            .context("JSON 中没有 stocks 列表")?,
+        _ => anyhow::bail!("JSON 应为数组或包含 stocks 列表的对象"),
+    };
+
+    let entries = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let text = |key: &str| item.get(key).and_then(Value::as_str).map(str::to_string);
+            let code = match item {
+                Value::String(code) => code.clone(),
+                Value::Object(_) => match (text("market"), text("code"), text("id")) {
+                    (Some(market), Some(code), _) if !code.to_ascii_lowercase().starts_with(&market) => {
+                        format!("{}{}", market, code)
+                    }
+                    (_, Some(code), _) | (_, None, Some(code)) => code,
+                    _ => item.to_string(),
+                },
+                other => other.to_string(),
+            };
+            Entry {
+                line: i + 1,
+                code,
+                alias: text("alias").filter(|a| !a.is_empty()),
+                visible: item.get("visible").and_then(Value::as_bool),
+            }
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn parse_bool(cell: &str) -> Option<bool> {
+    match cell.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "是" => Some(true),
+        "false" | "0" | "no" | "否" => Some(false),
+        _ => None,
+    }
+}
+
+/// 非空行及其下标
+fn non_empty_lines(content: &str) -> Vec<(usize, &str)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect()
+}
+
+/// 制表符分隔优先，否则按逗号分隔
+fn delimiter_of(line: &str) -> char {
+    if line.contains('\t') { '\t' } else { ',' }
+}
+
+/// 表头中代码列的位置
+fn code_column(line: &str) -> Option<usize> {
+    split_line(line, delimiter_of(line))
+        .iter()
+        .position(|h| CODE_HEADERS.contains(&h.to_lowercase().as_str()))
+}
+
+/// CSV：有表头时按列名找代码、别名、显示列，没有表头时依次为 代码,别名,显示
+fn parse_csv(content: &str) -> Vec<Entry> {
+    parse_table(&non_empty_lines(content))
+}
+
+/// 按首行判断有无表头并拆分各行
+fn parse_table(lines: &[(usize, &str)]) -> Vec<Entry> {
+    let Some(&(_, first)) = lines.first() else {
+        return Vec::new();
+    };
+    let delimiter = delimiter_of(first);
+
+    let header = split_line(first, delimiter);
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.to_lowercase().as_str()));
+    let (skip, code_col, alias_col, visible_col) = match code_column(first) {
+        Some(code) => (1, code, column(ALIAS_HEADERS), column(VISIBLE_HEADERS)),
+        None => (0, 0, Some(1), Some(2)),
+    };
+
+    lines
+        .iter()
+        .skip(skip)
+        .map(|&(index, line)| {
+            let cells = split_line(line, delimiter);
+            let cell = |i: Option<usize>| i.and_then(|i| cells.get(i)).filter(|c| !c.is_empty());
+            Entry {
+                line: index + 1,
+                code: cell(Some(code_col)).cloned().unwrap_or_default(),
+                alias: cell(alias_col).cloned(),
+                visible: cell(visible_col).and_then(|c| parse_bool(c)),
+            }
+        })
+        .collect()
+}
+
+/// 代码列表：每行一个代码，代码后可跟别名（空白分隔），# 之后为注释
+fn parse_codes(content: &str) -> Vec<Entry> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let (code, alias) = match line.split_once(char::is_whitespace) {
+                Some((code, alias)) => (code, Some(alias.trim().to_string())),
+                None => (line, None),
+            };
+            (!code.is_empty()).then(|| Entry {
+                line: index + 1,
+                code: code.to_string(),
+                alias: alias.filter(|a| !a.is_empty()),
+                visible: None,
+            })
+        })
+        .collect()
+}
+
+/// 交易软件导出的表格中，表头之前可能有的标题行数（如"自选股 2024-06-03"）
+const MAX_TITLE_LINES: usize = 5;
+
+/// 识别交易软件导出文件：每行都是 7 位数字的为通达信板块文件，代码带 SH/SZ 前缀的为同花顺，其余按东方财富处理
+pub fn detect_software(content: &str) -> TradingSoftware {
+    let lines = non_empty_lines(content);
+    let is_tdx_code = |line: &str| line.len() == 7 && line.bytes().all(|b| b.is_ascii_digit());
+    if !lines.is_empty() && lines.iter().all(|(_, line)| is_tdx_code(line.trim())) {
+        return TradingSoftware::Tdx;
+    }
+    let is_prefixed = |cell: &String| {
+        let lower = cell.to_ascii_lowercase();
+        (lower.starts_with("sh") || lower.starts_with("sz"))
+            && lower.len() == 8
+            && lower[2..].bytes().all(|b| b.is_ascii_digit())
+    };
+    let prefixed = lines
+        .iter()
+        .any(|(_, line)| split_line(line, delimiter_of(line)).iter().any(is_prefixed));
+    if prefixed {
+        TradingSoftware::Ths
+    } else {
+        TradingSoftware::Eastmoney
+    }
+}
+
+/// 解析交易软件导出的自选股文件（UTF-8 或 GBK），software 为空时自动识别
+/// - 通达信 zxg.blk：每行市场位 + 代码
+/// - 同花顺 / 东方财富 TXT/CSV：按"代码"/"证券代码"列和"名称"列读取，表头前的标题行被跳过；
+///   被表格软件去掉前导零的代码（如 1）补足 6 位
+pub fn parse_software(
+    bytes: &[u8],
+    software: Option<TradingSoftware>,
+) -> Result<(TradingSoftware, Vec<Entry>)> {
+    let content = broker_import::decode(bytes)?;
+    let software = software.unwrap_or_else(|| detect_software(&content));
+    let entries = match software {
+        TradingSoftware::Tdx => parse_codes(&content),
+        TradingSoftware::Ths | TradingSoftware::Eastmoney => {
+            let lines = non_empty_lines(&content);
+            let header = lines
+                .iter()
+                .take(MAX_TITLE_LINES)
+                .position(|(_, line)| code_column(line).is_some())
+                .context("未找到代码列（代码 / 证券代码），请确认导出的是自选股列表")?;
+            let mut entries = parse_table(&lines[header..]);
+            for entry in &mut entries {
+                if entry.code.len() < 6 && entry.code.bytes().all(|b| b.is_ascii_digit()) {
+                    entry.code = format!("{:0>6}", entry.code);
+                }
+            }
+            entries
+        }
+    };
+    if entries.is_empty() {
+        anyhow::bail!("文件中没有股票代码");
+    }
+    Ok((software, entries))
+}
+
+/// 识别导入条目：无法识别的代码、文件内重复的代码和 existing 返回 true 的代码记为冲突
+/// 其余转换为股票，并附带条目中的显示状态
+fn classify(
+    entries: &[Entry],
+    existing: impl Fn(&str) -> bool,
+) -> (Vec<(Stock, Option<bool>)>, Vec<WatchlistConflict>) {
+    let mut seen = HashSet::new();
+    let mut conflicts = Vec::new();
+    let mut imported: Vec<(Stock, Option<bool>)> = Vec::new();
+    for entry in entries {
+        let conflict = |id: Option<String>, kind| WatchlistConflict {
+            line: entry.line,
+            entry: entry.code.clone(),
+            id,
+            kind,
+        };
+        let Some((market, code)) = sources::resolve_code(&entry.code) else {
+            conflicts.push(conflict(None, WatchlistConflictKind::Invalid));
+            continue;
+        };
+        let id = sources::make_stock_id(&market, &code);
+        if !seen.insert(id.clone()) {
+            conflicts.push(conflict(Some(id), WatchlistConflictKind::Duplicate));
+        } else if existing(&id) {
+            conflicts.push(conflict(Some(id), WatchlistConflictKind::Existing));
+        } else {
+            let stock = Stock {
+                id,
+                code,
+                market,
+                alias: entry.alias.clone().unwrap_or_default(),
+                visible: entry.visible.unwrap_or(true),
+                holding: None,
+                overrides: Default::default(),
+            };
+            imported.push((stock, entry.visible));
+        }
+    }
+    (imported, conflicts)
+}
+
+/// 将导入条目应用到当前分组
+/// - 合并：新股票追加到分组末尾，已在分组中的记为冲突并保持原样
+/// - 替换：分组按导入顺序重建，导入中带别名时覆盖原别名；移出分组且不属于其他分组、无持仓的股票被删除
+/// - 无法识别的代码和文件内重复的代码记为冲突
+pub fn apply(
+    config: &mut Config,
+    entries: &[Entry],
+    format: WatchlistFormat,
+    mode: ImportMode,
+) -> Result<WatchlistImport> {
+    groups::normalize(config);
+    let active = config.active_group.clone();
+    let previous: Vec<GroupMember> = config
+        .groups
+        .iter()
+        .find(|g| g.id == active)
+        .map(|g| g.members.clone())
+        .unwrap_or_default();
+
+    let (imported, conflicts) =
+        classify(entries, |id| mode == ImportMode::Merge && previous.iter().any(|m| m.id == id));
+    if mode == ImportMode::Replace && imported.is_empty() {
+        anyhow::bail!("没有识别到可导入的股票代码，未替换当前分组");
+    }
+
+    let mut members = match mode {
+        ImportMode::Merge => previous.clone(),
+        ImportMode::Replace => Vec::new(),
+    };
+    for (stock, visible) in imported {
+        let previous_visible = previous.iter().find(|m| m.id == stock.id).map(|m| m.visible);
+        members.push(GroupMember {
+            id: stock.id.clone(),
+            visible: visible.or(previous_visible).unwrap_or(true),
+        });
+        match config.stocks.iter_mut().find(|s| s.id == stock.id) {
+            Some(existing) => {
+                if !stock.alias.is_empty() && (mode == ImportMode::Replace || existing.alias.is_empty()) {
+                    existing.alias = stock.alias;
+                }
+            }
+            None => config.stocks.push(stock),
+        }
+    }
+
+    if let Some(group) = config.groups.iter_mut().find(|g| g.id == active) {
+        group.members = members;
+    }
+    let Config { stocks, groups: all_groups, .. } = &mut *config;
+    stocks.retain(|s| {
+        s.holding.is_some() || all_groups.iter().any(|g| g.members.iter().any(|m| m.id == s.id))
+    });
+    // 有持仓的股票被移出后会由 normalize 放回当前分组，不计入 removed
+    groups::normalize(config);
+
+    let current: Vec<&str> = groups::active_group(config)
+        .map(|g| g.members.iter().map(|m| m.id.as_str()).collect())
+        .unwrap_or_default();
+    let added = current
+        .iter()
+        .filter(|id| !previous.iter().any(|m| m.id == **id))
+        .map(|id| id.to_string())
+        .collect();
+    let removed = previous
+        .iter()
+        .filter(|m| !current.contains(&m.id.as_str()))
+        .map(|m| m.id.clone())
+        .collect();
+    Ok(WatchlistImport {
+        format,
+        software: None,
+        mode,
+        added,
+        removed,
+        conflicts,
+    })
+}
+
+/// 通过 ConfigStore::add_stocks 一次追加到当前分组（交易软件导入），已在当前分组中的记为冲突
+/// 整个导入只保存一次、可一步撤销；dry_run 时只返回导入结果，不修改配置
+pub fn add_entries(
+    store: &ConfigStore,
+    entries: &[Entry],
+    software: TradingSoftware,
+    dry_run: bool,
+) -> Result<WatchlistImport> {
+    let config = store.get();
+    let active: HashSet<&str> = groups::active_group(&config)
+        .map(|g| g.members.iter().map(|m| m.id.as_str()).collect())
+        .unwrap_or_default();
+    let (imported, conflicts) = classify(entries, |id| active.contains(id));
+
+    let stocks: Vec<Stock> = imported.into_iter().map(|(stock, _)| stock).collect();
+    let added = stocks.iter().map(|s| s.id.clone()).collect();
+    if !dry_run {
+        store.add_stocks(stocks)?;
+    }
+    let format = match software {
+        TradingSoftware::Tdx => WatchlistFormat::Codes,
+        TradingSoftware::Ths | TradingSoftware::Eastmoney => WatchlistFormat::Csv,
+    };
+    Ok(WatchlistImport {
+        format,
+        software: Some(software),
+        mode: ImportMode::Merge,
+        added,
+        removed: Vec::new(),
+        conflicts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|e| e.code.as_str()).collect()
+    }
+
+    fn config_with(ids: &[&str]) -> Config {
+        let mut config = Config {
+            stocks: ids.iter().map(|id| sources::make_stock(id)).collect(),
+            groups: Vec::new(),
+            ..Config::default()
+        };
+        groups::normalize(&mut config);
+        config
+    }
+
+    fn active_ids(config: &Config) -> Vec<String> {
+        groups::active_stocks(config).into_iter().map(|s| s.id).collect()
+    }
+
+    #[test]
+    fn test_resolve_code_forms() {
+        for input in ["600519", "sh600519", "SH600519", "600519.SH", "600519.ss", "sh.600519", " 600519 "] {
+            assert_eq!(sources::resolve_code(input), Some(("sh".into(), "600519".into())), "{}", input);
+        }
+        for input in ["000001.SZ", "0000001", "0.000001"] {
+            assert_eq!(sources::resolve_code(input), Some(("sz".into(), "000001".into())), "{}", input);
+        }
+        assert_eq!(sources::resolve_code("1600519"), Some(("sh".into(), "600519".into())));
+        for input in ["", "abc", "60051", "6005190", "2430047", "600519.HK", "sh"] {
+            assert_eq!(sources::resolve_code(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_detect_and_parse_formats() {
+        let list = "# 我的自选\n600519 茅台\n\nSZ000001\n300750.SZ  # 宁德\n";
+        assert_eq!(detect_format(list), WatchlistFormat::Codes);
+        let entries = parse(list, WatchlistFormat::Codes).unwrap();
+        assert_eq!(codes(&entries), ["600519", "SZ000001", "300750.SZ"]);
+        assert_eq!(entries[0].alias.as_deref(), Some("茅台"));
+        assert_eq!(entries[2].line, 5);
+
+        let csv = "\u{feff}证券代码,证券名称,最新价\n600519,贵州茅台,1700\n000001,\"平安,银行\",10\n";
+        assert_eq!(detect_format(csv), WatchlistFormat::Csv);
+        let entries = parse(csv, WatchlistFormat::Csv).unwrap();
+        assert_eq!(codes(&entries), ["600519", "000001"]);
+        assert_eq!(entries[1].alias.as_deref(), Some("平安,银行"));
+
+        let headerless = parse("sh600519,茅台,false\n000001\n", WatchlistFormat::Csv).unwrap();
+        assert_eq!(headerless[0].visible, Some(false));
+        assert_eq!(headerless[1].alias, None);
+
+        // config.json 的 stocks 以及导出的 JSON
+        let json = r#"{ "stocks": [ { "id": "sh600036", "code": "600036", "market": "sh", "alias": "招行" }, "sz000001" ] }"#;
+        assert_eq!(detect_format(json), WatchlistFormat::Json);
+        let entries = parse(json, WatchlistFormat::Json).unwrap();
+        assert_eq!(codes(&entries), ["sh600036", "sz000001"]);
+        assert!(parse("{}", WatchlistFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_parse_trading_software_exports() {
+        let blk = b"\r\n1600519\r\n0000001\r\n0300750\r\n";
+        let (software, entries) = parse_software(blk, None).unwrap();
+        assert_eq!(software, TradingSoftware::Tdx);
+        let ids: Vec<String> = classify(&entries, |_| false).0.into_iter().map(|(s, _)| s.id).collect();
+        assert_eq!(ids, ["sh600519", "sz000001", "sz300750"]);
+
+        // 同花顺导出为 GBK 编码、制表符分隔，表头前有标题行
+        let ths = "自选股\n代码\t名称\t涨幅\nSH600519\t贵州茅台\t1.2%\nSZ000001\t平安银行\t-0.5%\n";
+        let (software, entries) = parse_software(&encoding_rs::GBK.encode(ths).0, None).unwrap();
+        assert_eq!(software, TradingSoftware::Ths);
+        assert_eq!(codes(&entries), ["SH600519", "SZ000001"]);
+        assert_eq!(entries[1].alias.as_deref(), Some("平安银行"));
+
+        // 东方财富 CSV 被表格软件去掉了前导零
+        let em = "序号,代码,名称,最新价\n1,600036,招商银行,30.1\n2,1,平安银行,10.2\n";
+        let (software, entries) = parse_software(em.as_bytes(), None).unwrap();
+        assert_eq!(software, TradingSoftware::Eastmoney);
+        assert_eq!(codes(&entries), ["600036", "000001"]);
+
+        assert!(parse_software(b"hello\nworld\n", Some(TradingSoftware::Ths)).is_err());
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let mut config = config_with(&["600519", "000001"]);
+        let entries = parse("300750\nsh600519\n300750.SZ\nhello\n", WatchlistFormat::Codes).unwrap();
+        let report = apply(&mut config, &entries, WatchlistFormat::Codes, ImportMode::Merge).unwrap();
+
+        assert_eq!(report.added, ["sz300750"]);
+        assert!(report.removed.is_empty());
+        let kinds: Vec<(usize, &WatchlistConflictKind)> =
+            report.conflicts.iter().map(|c| (c.line, &c.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                (2, &WatchlistConflictKind::Existing),
+                (3, &WatchlistConflictKind::Duplicate),
+                (4, &WatchlistConflictKind::Invalid),
+            ]
+        );
+        assert_eq!(active_ids(&config), ["sh600519", "sz000001", "sz300750"]);
+    }
+
+    #[test]
+    fn test_replace_and_round_trip() {
+        let mut config = config_with(&["600519", "000001", "600036"]);
+        config.stocks[2].holding = Some(crate::models::Holding {
+            shares: 100.0,
+            avg_cost: 30.0,
+            open_date: None,
+        });
+        let entries = parse("code,alias\n300750,宁德\n600519,茅台\n", WatchlistFormat::Csv).unwrap();
+        let report = apply(&mut config, &entries, WatchlistFormat::Csv, ImportMode::Replace).unwrap();
+
+        // 平安银行被删除；招行有持仓，保留在分组末尾
+        assert_eq!(report.added, ["sz300750"]);
+        assert_eq!(report.removed, ["sz000001"]);
+        assert_eq!(active_ids(&config), ["sz300750", "sh600519", "sh600036"]);
+        assert!(!config.stocks.iter().any(|s| s.id == "sz000001"));
+        assert_eq!(config.stocks[0].alias, "茅台");
+
+        // 只有无效代码时不替换
+        let invalid = parse("hello\n", WatchlistFormat::Codes).unwrap();
+        assert!(apply(&mut config.clone(), &invalid, WatchlistFormat::Codes, ImportMode::Replace).is_err());
+
+        for format in [WatchlistFormat::Json, WatchlistFormat::Csv, WatchlistFormat::Codes] {
+            let content = export(&config, format).unwrap();
+            assert_eq!(detect_format(&content), format);
+            let mut copy = config_with(&[]);
+            apply(&mut copy, &parse(&content, format).unwrap(), format, ImportMode::Replace).unwrap();
+            assert_eq!(active_ids(&copy), active_ids(&config));
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use tauri::{AppHandle, Emitter, Manager};
+use crate::AppState;
+use crate::models::WindowConfig;
+
+/// 显示或隐藏悬浮窗口，并按 pause_when_hidden 同步轮询器暂停状态
+/// 所有入口（快捷键、托盘、菜单、命令）都应通过这里切换，避免遗漏暂停逻辑
+pub fn set_monitor_visible(app: &AppHandle, visible: bool) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window("monitor") {
+        if visible {
+            window.show()?;
+            window.set_focus()?;
+        } else {
+            window.hide()?;
+        }
+    } else {
+        log::error!("找不到 monitor 窗口");
+    }
+    sync_poller_pause(app, visible);
+    Ok(())
+}
+
+/// 切换悬浮窗口显隐
+pub fn toggle_monitor(app: &AppHandle) -> tauri::Result<()> {
+    let is_visible = app
+        .get_webview_window("monitor")
+        .map(|w| w.is_visible().unwrap_or(false))
+        .unwrap_or(false);
+    log::info!("当前窗口可见性: {}", is_visible);
+    set_monitor_visible(app, !is_visible)
+}
+
+/// 将窗口配置中由后端负责的部分应用到悬浮窗口（置顶、任务栏图标、鼠标穿透）
+/// 透明度、行数和字体由前端按配置渲染
+pub fn apply_window_config(app: &AppHandle, config: &WindowConfig) -> tauri::Result<()> {
+    let Some(window) = app.get_webview_window("monitor") else {
+        log::error!("找不到 monitor 窗口");
+        return Ok(());
+    };
+    window.set_always_on_top(config.always_on_top)?;
+    window.set_skip_taskbar(config.hide_in_taskbar)?;
+    window.set_ignore_cursor_events(config.click_through)?;
+    Ok(())
+}
+
+/// 悬浮窗口当前是否可见
+pub fn is_monitor_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("monitor")
+        .map(|w| w.is_visible().unwrap_or(true))
+        .unwrap_or(true)
+}
+
+/// 打开详情窗口并切换到指定股票
+/// 选中的股票同时记录在 AppState 中，供详情窗口加载时读取
+pub fn open_detail(app: &AppHandle, id: &str) -> tauri::Result<()> {
+    if let Some(state) = app.try_state::<AppState>() {
+        *state.detail_stock.lock().unwrap() = Some(id.to_string());
+    }
+    let Some(window) = app.get_webview_window("detail") else {
+        log::error!("找不到 detail 窗口");
+        return Ok(());
+    };
+    app.emit_to("detail", "detail-stock", id)?;
+    window.show()?;
+    window.set_focus()?;
+    Ok(())
+}
+
+/// 根据窗口可见性和 pause_when_hidden 配置暂停/恢复轮询
+pub fn sync_poller_pause(app: &AppHandle, monitor_visible: bool) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let pause = !monitor_visible && state.config_store.get().app.pause_when_hidden;
+    state.poller.set_paused(pause);
+}
@@ -1,25 +1,21 @@
-import type { PriceUpdate, Config } from '../types';
+import { invoke } from '@tauri-apps/api/core';
+import type { PriceUpdate, Config, Stock } from '../types';
+import { changeColor, priceDecimals } from '../hooks/useConfig';
 import './StockItem.css';
 
 interface StockItemProps {
     data: PriceUpdate;
+    stock?: Stock;
     config: Config;
     rowHeight: number;
 }
 
 /**
  * 单只股票显示组件
- * 布局: [名称/代码] [当前价格] [涨跌幅]
+ * 布局: [名称/代码] [当前价格] [涨跌幅]（单只股票可设置为只显示涨跌幅）
+ * 双击打开详情窗口
  */
-export function StockItem({ data, config, rowHeight }: StockItemProps) {
-    const { app } = config;
-
-    // 根据涨跌确定颜色
-    const getColor = () => {
-        if (data.change > 0) return app.up_color;
-        if (data.change < 0) return app.down_color;
-        return app.neutral_color;
-    };
+export function StockItem({ data, stock, config, rowHeight }: StockItemProps) {
 
     // 格式化涨跌幅
     const formatPercent = (percent: number) => {
@@ -27,16 +23,21 @@ export function StockItem({ data, config, rowHeight }: StockItemProps) {
         return percent >= 0 ? `+${pct}%` : `${pct}%`;
     };
 
-    const color = getColor();
+    const color = changeColor(config, data.change, stock);
+    const percentOnly = stock?.overrides?.percent_only ?? false;
 
     // 显示名称：优先使用 alias，如果为空则使用 name
-    const stock = config.stocks.find(s => s.id === data.id);
     const displayName = stock?.alias || data.name || data.code;
 
     return (
-        <div className="stock-item" style={{ color, height: `${rowHeight}px` }}>
+        <div
+            className={`stock-item ${data.stale ? 'stock-stale' : ''}`}
+            style={{ color, height: `${rowHeight}px` }}
+            title={data.stale ? '上次退出时的行情，等待刷新' : stock?.overrides?.note || undefined}
+            onDoubleClick={() => invoke('open_detail', { id: data.id }).catch(console.error)}
+        >
             <span className="stock-name">{displayName}</span>
-            <span className="stock-price">{data.price.toFixed(2)}</span>
+            <span className="stock-price">{percentOnly ? '' : data.price.toFixed(priceDecimals(stock))}</span>
             <span className="stock-change">
                 {formatPercent(data.percent)}
             </span>
@@ -0,0 +1,42 @@
+import { useState, useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import type { AlertTriggered } from '../types';
+
+/** 预警提示在悬浮窗中保留的时长 */
+const ALERT_SHOW_MS = 15_000;
+
+/**
+ * 订阅预警触发的 Hook
+ * - 监听后端推送的 alert-triggered 事件，返回最近一条仍在显示期内的预警
+ * - 系统通知可用且已授权时同时发送系统通知
+ */
+export function useAlerts() {
+    const [alert, setAlert] = useState<AlertTriggered | null>(null);
+
+    useEffect(() => {
+        const notify = 'Notification' in window;
+        if (notify && Notification.permission === 'default') {
+            Notification.requestPermission().catch(console.error);
+        }
+
+        let timer: ReturnType<typeof setTimeout> | undefined;
+        const unlisten = listen<AlertTriggered>('alert-triggered', (event) => {
+            const triggered = event.payload;
+            setAlert(triggered);
+            clearTimeout(timer);
+            timer = setTimeout(() => setAlert(null), ALERT_SHOW_MS);
+            if (notify && Notification.permission === 'granted') {
+                new Notification(`预警：${triggered.name}`, {
+                    body: `${triggered.message}，现价 ${triggered.price.toFixed(2)}`,
+                });
+            }
+        });
+
+        return () => {
+            clearTimeout(timer);
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    return { alert, dismiss: () => setAlert(null) };
+}
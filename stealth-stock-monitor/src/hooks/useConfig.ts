@@ -1,7 +1,54 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { Config } from '../types';
+import type { Config, Stock, StockOverrides } from '../types';
+
+/**
+ * 当前分组中的股票（按分组内顺序，visible 为分组内的显示状态）
+ * 与后端 groups::active_stocks 一致；没有任何分组时返回全部股票
+ */
+export function activeStocks(config: Config): Stock[] {
+    const group = config.groups.find(g => g.id === config.active_group) ?? config.groups[0];
+    if (!group) return config.stocks;
+    return group.members.flatMap((member) => {
+        const stock = config.stocks.find(s => s.id === member.id);
+        return stock ? [{ ...stock, visible: member.visible }] : [];
+    });
+}
+
+/** 未设置任何单只股票选项时的默认值 */
+export const DEFAULT_OVERRIDES: StockOverrides = {
+    pinned: false,
+    percent_only: false,
+    priority: 'normal',
+};
+
+/** 单只股票的价格小数位数，默认 2 位 */
+export function priceDecimals(stock?: Stock): number {
+    return stock?.overrides?.decimals ?? 2;
+}
+
+/** 按涨跌返回颜色，优先使用单只股票设置的涨跌颜色 */
+export function changeColor(config: Config, change: number, stock?: Stock): string {
+    if (change > 0) return stock?.overrides?.up_color || config.app.up_color;
+    if (change < 0) return stock?.overrides?.down_color || config.app.down_color;
+    return config.app.neutral_color;
+}
+
+/**
+ * 悬浮窗股票行布局：置顶股票固定在最上方，其余股票在剩余行中轮播 / 滚动
+ * 置顶股票超过显示行数时只显示前 display_rows 只
+ */
+export function displayLayout(config: Config) {
+    const visible = activeStocks(config).filter(s => s.visible);
+    const displayCount = Math.min(config.window.display_rows, visible.length);
+    const pinned = visible.filter(s => s.overrides?.pinned).slice(0, displayCount);
+    const scrolling = visible.filter(s => !s.overrides?.pinned);
+    const scrollRows = displayCount - pinned.length;
+    // 滚动区最大偏移量（股票数不超过可用行数或没有可用行时为 0）
+    const maxOffset = scrollRows > 0 ? Math.max(0, scrolling.length - scrollRows) : 0;
+    return { displayCount, pinned, scrolling, scrollRows, maxOffset };
+}
 
 /**
  * 管理应用配置状态的 Hook
@@ -40,7 +87,7 @@ export function useConfig() {
         };
     }, []);
 
-    /** 更新配置 */
+    /** 更新配置；后端校验失败时抛出错误（含字段路径），当前配置保持不变 */
     const updateConfig = async (newConfig: Config) => {
         try {
             console.log('[useConfig] 更新配置:', newConfig);
@@ -48,7 +95,7 @@ export function useConfig() {
             setConfig(newConfig);
         } catch (err) {
             console.error('[useConfig] 配置更新失败:', err);
-            setError(String(err));
+            throw err;
         }
     };
 
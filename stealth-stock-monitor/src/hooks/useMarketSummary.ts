@@ -0,0 +1,29 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { MarketSummary } from '../types';
+
+/**
+ * 订阅大盘概况的 Hook
+ * - 启动时通过 get_market_summary 获取当前概况
+ * - 监听后端推送的 market-summary 事件
+ */
+export function useMarketSummary() {
+    const [summary, setSummary] = useState<MarketSummary | null>(null);
+
+    useEffect(() => {
+        invoke<MarketSummary | null>('get_market_summary')
+            .then((s) => setSummary((prev) => prev ?? s))
+            .catch((err) => console.error('[useMarketSummary] 获取大盘概况失败:', err));
+
+        const unlisten = listen<MarketSummary>('market-summary', (event) => {
+            setSummary(event.payload);
+        });
+
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    return { summary };
+}
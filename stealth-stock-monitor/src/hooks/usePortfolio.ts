@@ -0,0 +1,29 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { PortfolioUpdate } from '../types';
+
+/**
+ * 订阅持仓盈亏的 Hook
+ * - 启动时通过 get_portfolio 获取当前汇总
+ * - 监听后端推送的 portfolio-update 事件
+ */
+export function usePortfolio() {
+    const [portfolio, setPortfolio] = useState<PortfolioUpdate | null>(null);
+
+    useEffect(() => {
+        invoke<PortfolioUpdate | null>('get_portfolio')
+            .then((p) => setPortfolio((prev) => prev ?? p))
+            .catch((err) => console.error('[usePortfolio] 获取持仓盈亏失败:', err));
+
+        const unlisten = listen<PortfolioUpdate>('portfolio-update', (event) => {
+            setPortfolio(event.payload);
+        });
+
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    return { portfolio };
+}
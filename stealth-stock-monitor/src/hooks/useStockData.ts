@@ -1,28 +1,48 @@
 import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import type { PriceUpdate } from '../types';
 
 /**
  * 订阅股票行情数据更新的 Hook
- * - 监听后端推送的 price-update 事件
+ * - 启动时通过 get_quotes 拉取后端快照，窗口打开即可显示
+ * - 监听后端推送的 price-update（全量关键帧）与 price-delta（增量）事件
  * - 维护一个 Map<id, PriceUpdate> 作为最新数据快照
  */
 export function useStockData() {
     const [stockData, setStockData] = useState<Map<string, PriceUpdate>>(new Map());
 
     useEffect(() => {
-        const unlisten = listen<PriceUpdate[]>('price-update', (event) => {
+        // 初始快照：只补充尚未收到推送的股票，避免覆盖更新的数据
+        invoke<Record<string, PriceUpdate>>('get_quotes')
+            .then((quotes) => {
+                setStockData((prev) => {
+                    const next = new Map(prev);
+                    for (const [id, update] of Object.entries(quotes)) {
+                        if (!next.has(id)) next.set(id, update);
+                    }
+                    return next;
+                });
+            })
+            .catch((err) => console.error('[useStockData] 获取行情快照失败:', err));
+
+        const merge = (updates: PriceUpdate[]) => {
             setStockData((prev) => {
                 const next = new Map(prev);
-                for (const update of event.payload) {
+                for (const update of updates) {
                     next.set(update.id, update);
                 }
                 return next;
             });
-        });
+        };
+
+        // price-update 为周期性全量关键帧，price-delta 只包含有变化的股票
+        const unlistenFull = listen<PriceUpdate[]>('price-update', (event) => merge(event.payload));
+        const unlistenDelta = listen<PriceUpdate[]>('price-delta', (event) => merge(event.payload));
 
         return () => {
-            unlisten.then((fn) => fn());
+            unlistenFull.then((fn) => fn());
+            unlistenDelta.then((fn) => fn());
         };
     }, []);
 
@@ -3,6 +3,7 @@ import ReactDOM from 'react-dom/client';
 import { listen } from '@tauri-apps/api/event';
 import { FloatingWindow } from './windows/FloatingWindow';
 import { SettingsWindow } from './windows/SettingsWindow';
+import { DetailWindow } from './windows/DetailWindow';
 import './App.css';
 import type { ErrorEvent } from './types';
 
@@ -58,7 +59,9 @@ function App() {
 
   return (
     <>
-      {path === '/settings' ? <SettingsWindow /> : <FloatingWindow />}
+      {path === '/settings'
+        ? <SettingsWindow />
+        : path === '/detail' ? <DetailWindow /> : <FloatingWindow />}
       <GlobalToast />
     </>
   );
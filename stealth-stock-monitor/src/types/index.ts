@@ -1,12 +1,48 @@
 // ==================== 数据模型 ====================
 
+/** 持仓信息 */
+export interface Holding {
+  shares: number;
+  avg_cost: number;
+  open_date: string | null;  // "2024-06-03"，当日建仓按成本价计算当日盈亏
+}
+
 /** 股票基本信息 */
 export interface Stock {
   id: string;       // "sh600519"
   code: string;     // "600519"
   market: string;   // "sh"
   alias: string;    // "茅台"
-  visible: boolean;
+  visible: boolean; // 加入分组时的默认显示状态
+  holding?: Holding;
+  overrides?: StockOverrides;  // 单只股票的显示与刷新设置，未设置的项使用全局设置
+}
+
+/** 刷新优先级：high 系统空闲时也不降频，low 每 5 次刷新请求一次 */
+export type RefreshPriority = 'high' | 'normal' | 'low';
+
+/** 单只股票的显示与刷新设置 */
+export interface StockOverrides {
+  decimals?: number | null;      // 价格小数位数（ETF 为 3），默认 2
+  up_color?: string | null;      // 默认 app.up_color
+  down_color?: string | null;    // 默认 app.down_color
+  pinned: boolean;               // 固定在悬浮窗最上方，不参与轮播和滚动
+  note?: string;                 // 备注，悬停时显示
+  percent_only: boolean;         // 隐藏价格，只显示涨跌幅
+  priority: RefreshPriority;
+}
+
+/** 分组中的一只股票 */
+export interface GroupMember {
+  id: string;       // 对应 Stock.id
+  visible: boolean; // 是否在悬浮窗中显示
+}
+
+/** 自选股分组（每个分组有自己的顺序和显示状态） */
+export interface WatchGroup {
+  id: string;       // 默认分组为 "default"
+  name: string;
+  members: GroupMember[];
 }
 
 /** 实时行情数据 */
@@ -23,6 +59,50 @@ export interface PriceUpdate {
   low: number;
   timestamp: number;
   source: string;
+  stale: boolean;   // 上次退出时保存的旧数据，首次刷新前为 true
+}
+
+/** 单只持仓盈亏 */
+export interface PositionPnl {
+  id: string;
+  name: string;
+  shares: number;
+  avg_cost: number;
+  price: number;
+  market_value: number;
+  cost_basis: number;
+  daily_pnl: number;
+  unrealized_pnl: number;
+  unrealized_percent: number;
+}
+
+/** 组合盈亏汇总（portfolio-update 事件） */
+export interface PortfolioUpdate {
+  positions: PositionPnl[];
+  market_value: number;
+  cost_basis: number;
+  daily_pnl: number;
+  daily_percent: number;
+  unrealized_pnl: number;
+  unrealized_percent: number;
+  timestamp: number;
+}
+
+/** 沪深两市涨跌统计 */
+export interface MarketBreadth {
+  advancers: number;
+  decliners: number;
+  unchanged: number;
+  limit_up: number;
+  limit_down: number;
+  turnover: number;      // 两市成交额（元）
+}
+
+/** 大盘概况（market-summary 事件） */
+export interface MarketSummary {
+  indices: PriceUpdate[];  // 上证、深成、创业板、沪深300、科创50（名称为简称）
+  breadth: MarketBreadth | null;
+  timestamp: number;
 }
 
 // ==================== 配置结构 ====================
@@ -47,6 +127,8 @@ export interface WindowConfig {
   display_rows: number;
   enable_carousel: boolean;      // 是否启用自动轮播
   carousel_interval_ms: number;  // 轮播间隔（毫秒）
+  show_portfolio: boolean;       // 显示持仓当日盈亏汇总行
+  show_market_summary: boolean;  // 显示大盘概况行（指数与涨跌家数）
 }
 
 export interface AppConfig {
@@ -59,6 +141,9 @@ export interface AppConfig {
   pause_when_hidden: boolean;
   autostart: boolean;
   data_sources: string[];
+  idle_slowdown: boolean;            // 系统空闲/锁屏时降低刷新频率
+  idle_threshold_secs: number;       // 无输入多少秒视为空闲
+  idle_refresh_interval_ms: number;  // 空闲期间的刷新间隔
 }
 
 export interface ShortcutConfig {
@@ -66,14 +151,307 @@ export interface ShortcutConfig {
   next_stock: string;
   prev_stock: string;
   open_settings: string;  // 打开设置窗口（用于鼠标穿透后无法通过右键菜单进入设置的情况）
+  open_detail: string;    // 打开当前首行股票的详情窗口
+  cycle_group: string;    // 切换到下一个自选股分组
+  cycle_profile: string;  // 切换到下一个配置方案
+}
+
+/** 本地行情 API（仅监听 127.0.0.1） */
+export interface ApiConfig {
+  enabled: boolean;
+  port: number;
+}
+
+/** 持仓成本计算方式：先进先出 / 加权平均 */
+export type CostMethod = 'fifo' | 'weighted_average';
+
+/** 交易费用标准（费率为小数） */
+export interface FeeSchedule {
+  commission_rate: number;
+  min_commission: number;
+  stamp_duty_rate: number;   // 仅卖出收取
+  transfer_fee_rate: number;
+}
+
+export interface PortfolioConfig {
+  cost_method: CostMethod;
+  fees: FeeSchedule;
+}
+
+/** 本地行情存储（SQLite） */
+export interface StorageConfig {
+  enabled: boolean;
+  tick_retention_days: number;
+  bar_retention_days: number;   // 0 表示永久保留
 }
 
 export interface Config {
   version: number;
+  profile: string;        // 当前配置方案名称
   window: WindowConfig;
   app: AppConfig;
   shortcuts: ShortcutConfig;
   stocks: Stock[];
+  groups: WatchGroup[];
+  active_group: string;   // 当前分组 ID，悬浮窗只显示该分组的股票
+  api: ApiConfig;
+  portfolio: PortfolioConfig;
+  storage: StorageConfig;
+  alerts: AlertRule[];
+}
+
+/** 自选股编辑的撤销 / 重做状态 */
+export interface UndoState {
+  can_undo: boolean;
+  can_redo: boolean;
+}
+
+/** 配置备份 */
+export interface ConfigBackup {
+  name: string;          // config-YYYYMMDD-HHMMSS.json
+  timestamp: number;     // 毫秒
+  size: number;          // 字节
+}
+
+// ==================== 交易流水 ====================
+
+export type TransactionKind = 'buy' | 'sell' | 'dividend' | 'split';
+
+export interface Transaction {
+  id: string;
+  stock_id: string;
+  kind: TransactionKind;
+  date: string;          // YYYY-MM-DD
+  shares: number;
+  price: number;
+  amount: number;        // 分红到账金额
+  ratio: number;         // 送转比例（每 1 股变为多少股）
+  commission: number;
+  stamp_duty: number;
+  transfer_fee: number;
+  note: string;
+  source_ref?: string;   // 导入来源标识（用于去重）
+}
+
+/** add_transaction 的输入，费用留空时按费用标准计算 */
+export interface NewTransaction {
+  code: string;
+  kind: TransactionKind;
+  date: string;
+  shares?: number;
+  price?: number;
+  amount?: number;
+  ratio?: number;
+  commission?: number | null;
+  stamp_duty?: number | null;
+  transfer_fee?: number | null;
+  note?: string;
+  source_ref?: string;
+}
+
+/** 由交易流水推导的持仓 */
+export interface DerivedPosition {
+  stock_id: string;
+  shares: number;
+  avg_cost: number;
+  cost_basis: number;
+  realized_pnl: number;
+  total_fees: number;
+  open_date: string | null;
+}
+
+// ==================== 每日收盘记录 ====================
+
+export interface EodQuote {
+  id: string;
+  name: string;
+  close: number;
+  prev_close: number;
+  change: number;
+  percent: number;
+}
+
+/** 交易日收盘快照（daily-snapshot 事件 / get_daily_history） */
+export interface DailySnapshot {
+  date: string;          // YYYY-MM-DD
+  quotes: EodQuote[];
+  portfolio: PortfolioUpdate | null;
+}
+
+// ==================== 本地行情存储 ====================
+
+export type BarInterval = '1m' | '5m' | '15m' | '30m' | '60m' | '1d';
+
+export interface Bar {
+  timestamp: number;     // 周期起始时间（毫秒）
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+}
+
+export interface Tick {
+  timestamp: number;
+  price: number;
+  prev_close: number;
+}
+
+// ==================== 分时 ====================
+
+export interface IntradayPoint {
+  time: string;          // "HH:MM"
+  price: number;
+  avg_price: number;
+  volume: number;        // 该分钟成交量（股）
+}
+
+export interface IntradayData {
+  id: string;
+  date: string;
+  prev_close: number;
+  points: IntradayPoint[];
+  source: string;
+}
+
+// ==================== 个股详情 ====================
+
+/** 盘口档位 */
+export interface DepthLevel {
+  price: number;
+  volume: number;        // 股
+}
+
+/** 个股详情：五档盘口与关键统计 */
+export interface StockDetail {
+  quote: PriceUpdate;
+  open: number;
+  volume: number;        // 股
+  amount: number;        // 元
+  turnover_rate: number | null;   // 小数
+  pe: number | null;
+  pb: number | null;
+  amplitude: number | null;       // 小数
+  float_market_cap: number | null; // 元
+  market_cap: number | null;       // 元
+  limit_up: number | null;
+  limit_down: number | null;
+  bids: DepthLevel[];    // 买一至买五
+  asks: DepthLevel[];    // 卖一至卖五
+}
+
+// ==================== 历史 K 线 ====================
+
+export type KlinePeriod = 'day' | 'week' | 'month';
+/** 复权方式：不复权 / 前复权 / 后复权 */
+export type Adjust = 'none' | 'forward' | 'backward';
+
+export interface Kline {
+  date: string;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume: number;        // 股
+  amount: number;        // 元
+}
+
+// ==================== 技术指标与预警 ====================
+
+export type IndicatorSpec =
+  | { type: 'ma'; period: number }
+  | { type: 'ema'; period: number }
+  | { type: 'macd'; fast: number; slow: number; signal: number }
+  | { type: 'rsi'; period: number }
+  | { type: 'kdj'; n: number; m1: number; m2: number }
+  | { type: 'boll'; period: number; k: number }
+  | { type: 'vwap' };
+
+export type IndicatorInterval = 'intraday' | '1m' | '5m' | '15m' | '30m' | '60m' | 'day' | 'week' | 'month';
+
+export interface IndicatorSeries {
+  spec: IndicatorSpec;
+  lines: Record<string, (number | null)[]>;  // 如 MACD: dif / dea / macd
+}
+
+export interface IndicatorResult {
+  id: string;
+  interval: IndicatorInterval;
+  labels: string[];
+  close: number[];
+  series: IndicatorSeries[];
+}
+
+export type Operand =
+  | { type: 'close' }
+  | { type: 'value'; value: number }
+  | { type: 'indicator'; spec: IndicatorSpec; line: string };
+
+export type AlertCondition =
+  | { type: 'above' | 'below' | 'cross_above' | 'cross_below'; left: Operand; right: Operand };
+
+export interface AlertRule {
+  id: string;
+  stock_id: string;
+  interval: IndicatorInterval;
+  condition: AlertCondition;
+  enabled: boolean;
+  note: string;
+}
+
+/** alert-triggered 事件 */
+export interface AlertTriggered {
+  rule_id: string;
+  stock_id: string;
+  name: string;
+  message: string;
+  price: number;
+  timestamp: number;
+}
+
+// ==================== 券商对账单导入 ====================
+
+export type Broker = 'huatai' | 'citic' | 'eastmoney' | 'generic';
+
+export interface ImportRow {
+  line: number;
+  name: string;
+  status: 'new' | 'duplicate' | 'skipped';
+  reason?: string;       // 跳过原因
+  transaction: NewTransaction | null;
+}
+
+export interface ImportPreview {
+  broker: Broker;
+  rows: ImportRow[];
+  new_count: number;
+  duplicate_count: number;
+  skipped_count: number;
+  error: string | null;  // 追加后流水不一致时的错误信息
+}
+
+// ==================== 自选股导入导出 ====================
+
+export type WatchlistFormat = 'json' | 'csv' | 'codes';
+
+export type ImportMode = 'merge' | 'replace';
+
+/** 交易软件导出格式：通达信 zxg.blk、同花顺、东方财富 TXT/CSV */
+export type TradingSoftware = 'tdx' | 'ths' | 'eastmoney';
+
+export interface WatchlistConflict {
+  line: number;
+  entry: string;          // 原始代码
+  id: string | null;      // 识别出的股票 ID，无法识别时为 null
+  kind: 'duplicate' | 'existing' | 'invalid';
+}
+
+export interface WatchlistImport {
+  format: WatchlistFormat;
+  software: TradingSoftware | null;  // 从交易软件导出文件导入时为文件来源
+  mode: ImportMode;
+  added: string[];
+  removed: string[];      // 替换时移出当前分组的股票
+  conflicts: WatchlistConflict[];
 }
 
 // ==================== 事件 Payload ====================
@@ -0,0 +1,307 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { useConfig, changeColor, priceDecimals } from '../hooks/useConfig';
+import type { Config, DepthLevel, IntradayData, Kline, KlinePeriod, StockDetail } from '../types';
+import './DetailWindow.css';
+
+/** 分时数据刷新间隔（后端交易时段内缓存 60 秒） */
+const INTRADAY_REFRESH_MS = 60_000;
+/** K 线显示根数 */
+const KLINE_COUNT = 80;
+
+const CHART_WIDTH = 520;
+const INTRADAY_HEIGHT = 180;
+const KLINE_HEIGHT = 160;
+const VOLUME_HEIGHT = 50;
+
+const PERIODS: { key: KlinePeriod; label: string }[] = [
+    { key: 'day', label: '日K' },
+    { key: 'week', label: '周K' },
+    { key: 'month', label: '月K' },
+];
+
+/** 按涨跌返回颜色 */
+function colorOf(config: Config, value: number, base = 0) {
+    if (value > base) return config.app.up_color;
+    if (value < base) return config.app.down_color;
+    return config.app.neutral_color;
+}
+
+/** 大数字格式化（万 / 亿） */
+function formatLarge(value: number | null | undefined) {
+    if (value == null) return '--';
+    if (Math.abs(value) >= 1e8) return `${(value / 1e8).toFixed(2)}亿`;
+    if (Math.abs(value) >= 1e4) return `${(value / 1e4).toFixed(2)}万`;
+    return value.toFixed(0);
+}
+
+function formatPercent(value: number | null | undefined) {
+    return value == null ? '--' : `${(value * 100).toFixed(2)}%`;
+}
+
+/**
+ * 分时图：价格线 + 均价线，以昨收为中轴
+ */
+function IntradayChart({ data, config }: { data: IntradayData; config: Config }) {
+    // 全天 241 个分钟点（9:30-11:30、13:00-15:00）
+    const total = 241;
+    const prices = data.points.flatMap(p => [p.price, p.avg_price]).filter(v => v > 0);
+    const maxDiff = Math.max(
+        ...prices.map(v => Math.abs(v - data.prev_close)),
+        data.prev_close * 0.01,
+    );
+    const x = (i: number) => (i / (total - 1)) * CHART_WIDTH;
+    const y = (v: number) => INTRADAY_HEIGHT / 2 - ((v - data.prev_close) / maxDiff) * (INTRADAY_HEIGHT / 2 - 4);
+    const line = (pick: (i: number) => number) =>
+        data.points.map((_, i) => `${i === 0 ? 'M' : 'L'}${x(i).toFixed(1)},${y(pick(i)).toFixed(1)}`).join(' ');
+
+    return (
+        <svg className="chart" viewBox={`0 0 ${CHART_WIDTH} ${INTRADAY_HEIGHT}`} preserveAspectRatio="none">
+            <line className="chart-axis" x1={0} x2={CHART_WIDTH} y1={INTRADAY_HEIGHT / 2} y2={INTRADAY_HEIGHT / 2} />
+            <line className="chart-axis" x1={CHART_WIDTH / 2} x2={CHART_WIDTH / 2} y1={0} y2={INTRADAY_HEIGHT} />
+            <path d={line(i => data.points[i].avg_price)} className="chart-avg" />
+            <path d={line(i => data.points[i].price)} className="chart-price" />
+            <text className="chart-label" x={2} y={12} fill={config.app.up_color}>
+                {(data.prev_close + maxDiff).toFixed(2)}
+            </text>
+            <text className="chart-label" x={2} y={INTRADAY_HEIGHT - 4} fill={config.app.down_color}>
+                {(data.prev_close - maxDiff).toFixed(2)}
+            </text>
+        </svg>
+    );
+}
+
+/**
+ * K 线图 + 成交量柱
+ */
+function KlineChart({ klines, config }: { klines: Kline[]; config: Config }) {
+    const high = Math.max(...klines.map(k => k.high));
+    const low = Math.min(...klines.map(k => k.low));
+    const range = high - low || 1;
+    const maxVolume = Math.max(...klines.map(k => k.volume)) || 1;
+    const step = CHART_WIDTH / KLINE_COUNT;
+    const bodyWidth = Math.max(1, step * 0.7);
+    const y = (v: number) => 4 + ((high - v) / range) * (KLINE_HEIGHT - 8);
+
+    return (
+        <svg
+            className="chart"
+            viewBox={`0 0 ${CHART_WIDTH} ${KLINE_HEIGHT + VOLUME_HEIGHT}`}
+            preserveAspectRatio="none"
+        >
+            {klines.map((k, i) => {
+                const color = colorOf(config, k.close, k.open);
+                const cx = i * step + step / 2;
+                const top = y(Math.max(k.open, k.close));
+                const bottom = y(Math.min(k.open, k.close));
+                const volumeHeight = (k.volume / maxVolume) * (VOLUME_HEIGHT - 4);
+                return (
+                    <g key={k.date} stroke={color} fill={color}>
+                        <title>{`${k.date} 开${k.open} 高${k.high} 低${k.low} 收${k.close}`}</title>
+                        <line x1={cx} x2={cx} y1={y(k.high)} y2={y(k.low)} />
+                        <rect x={cx - bodyWidth / 2} y={top} width={bodyWidth} height={Math.max(1, bottom - top)} />
+                        <rect
+                            x={cx - bodyWidth / 2}
+                            y={KLINE_HEIGHT + VOLUME_HEIGHT - volumeHeight}
+                            width={bodyWidth}
+                            height={volumeHeight}
+                            opacity={0.6}
+                        />
+                    </g>
+                );
+            })}
+            <text className="chart-label" x={2} y={12}>{high.toFixed(2)}</text>
+            <text className="chart-label" x={2} y={KLINE_HEIGHT - 4}>{low.toFixed(2)}</text>
+        </svg>
+    );
+}
+
+/**
+ * 五档盘口
+ */
+function DepthTable({ detail, config, decimals }: { detail: StockDetail; config: Config; decimals: number }) {
+    const prevClose = detail.quote.prev_close;
+    const row = (label: string, level: DepthLevel) => (
+        <div key={label} className="depth-row">
+            <span className="depth-label">{label}</span>
+            <span style={{ color: colorOf(config, level.price, prevClose) }}>
+                {level.price > 0 ? level.price.toFixed(decimals) : '--'}
+            </span>
+            <span className="depth-volume">{level.volume > 0 ? Math.round(level.volume / 100) : '--'}</span>
+        </div>
+    );
+    const names = ['一', '二', '三', '四', '五'];
+
+    return (
+        <div className="depth-table">
+            {[...detail.asks].map((level, i) => row(`卖${names[i]}`, level)).reverse()}
+            <div className="depth-divider" />
+            {detail.bids.map((level, i) => row(`买${names[i]}`, level))}
+        </div>
+    );
+}
+
+/**
+ * 立即加载一次，之后定时刷新；关闭详情窗口只是隐藏窗口，隐藏期间跳过刷新，不在后台持续请求
+ * 返回停止定时器的函数
+ */
+function pollWhileVisible(load: () => void, intervalMs: number) {
+    const win = getCurrentWindow();
+    load();
+    const timer = setInterval(() => {
+        win.isVisible()
+            .then(visible => visible && load())
+            .catch(console.error);
+    }, intervalMs);
+    return () => clearInterval(timer);
+}
+
+/**
+ * 股票详情窗口
+ * - 悬浮窗双击某行或按快捷键打开，后端通过 detail-stock 事件切换股票
+ * - 显示分时图、K 线与成交量、五档盘口和关键统计
+ */
+export function DetailWindow() {
+    const { config } = useConfig();
+    const [stockId, setStockId] = useState<string | null>(null);
+    const [detail, setDetail] = useState<StockDetail | null>(null);
+    const [intraday, setIntraday] = useState<IntradayData | null>(null);
+    const [klines, setKlines] = useState<Kline[]>([]);
+    const [period, setPeriod] = useState<KlinePeriod>('day');
+    const [error, setError] = useState<string | null>(null);
+
+    // 当前股票：加载时读取一次，之后跟随 detail-stock 事件
+    useEffect(() => {
+        invoke<string | null>('get_detail_stock')
+            .then(id => id && setStockId(id))
+            .catch(console.error);
+        const unlisten = listen<string>('detail-stock', (event) => setStockId(event.payload));
+        return () => {
+            unlisten.then(fn => fn());
+        };
+    }, []);
+
+    // 切换股票时清空旧数据
+    useEffect(() => {
+        setDetail(null);
+        setIntraday(null);
+        setKlines([]);
+        setError(null);
+    }, [stockId]);
+
+    // 盘口与统计：按行情刷新间隔轮询
+    useEffect(() => {
+        if (!stockId || !config) return;
+        const load = () =>
+            invoke<StockDetail>('get_stock_detail', { id: stockId })
+                .then(d => {
+                    setDetail(d);
+                    setError(null);
+                })
+                .catch(err => setError(String(err)));
+        return pollWhileVisible(load, config.app.refresh_interval_ms);
+    }, [stockId, config?.app.refresh_interval_ms]);
+
+    // 分时
+    useEffect(() => {
+        if (!stockId) return;
+        const load = () =>
+            invoke<IntradayData>('get_intraday', { id: stockId })
+                .then(setIntraday)
+                .catch(err => console.error('[DetailWindow] 获取分时失败:', err));
+        return pollWhileVisible(load, INTRADAY_REFRESH_MS);
+    }, [stockId]);
+
+    // K 线
+    useEffect(() => {
+        if (!stockId) return;
+        invoke<Kline[]>('get_klines', { id: stockId, period })
+            .then(data => setKlines(data.slice(-KLINE_COUNT)))
+            .catch(err => console.error('[DetailWindow] 获取K线失败:', err));
+    }, [stockId, period]);
+
+    if (!config) return null;
+    if (!stockId) {
+        return <div className="detail-window detail-empty">双击悬浮窗中的股票查看详情</div>;
+    }
+
+    const quote = detail?.quote;
+    const stock = config.stocks.find(s => s.id === stockId);
+    const quoteColor = quote ? changeColor(config, quote.change, stock) : config.app.neutral_color;
+    const decimals = priceDecimals(stock);
+    const stats: [string, string][] = detail
+        ? [
+            ['今开', detail.open.toFixed(decimals)],
+            ['昨收', detail.quote.prev_close.toFixed(decimals)],
+            ['最高', detail.quote.high.toFixed(decimals)],
+            ['最低', detail.quote.low.toFixed(decimals)],
+            ['成交量', formatLarge(detail.volume)],
+            ['成交额', formatLarge(detail.amount)],
+            ['换手率', formatPercent(detail.turnover_rate)],
+            ['振幅', formatPercent(detail.amplitude)],
+            ['市盈率', detail.pe?.toFixed(2) ?? '--'],
+            ['市净率', detail.pb?.toFixed(2) ?? '--'],
+            ['流通市值', formatLarge(detail.float_market_cap)],
+            ['总市值', formatLarge(detail.market_cap)],
+            ['涨停', detail.limit_up?.toFixed(decimals) ?? '--'],
+            ['跌停', detail.limit_down?.toFixed(decimals) ?? '--'],
+        ]
+        : [];
+
+    return (
+        <div className="detail-window">
+            <div className="detail-header">
+                <span className="detail-name">{stock?.alias || quote?.name || stockId}</span>
+                <span className="detail-code">{stockId}</span>
+                {quote && (
+                    <span className="detail-quote" style={{ color: quoteColor }}>
+                        {quote.price.toFixed(decimals)}
+                        {' '}
+                        {quote.change >= 0 ? '+' : ''}{quote.change.toFixed(decimals)}
+                        {' '}
+                        {quote.percent >= 0 ? '+' : ''}{(quote.percent * 100).toFixed(2)}%
+                    </span>
+                )}
+                {error && <span className="detail-error">{error}</span>}
+            </div>
+
+            <div className="detail-body">
+                <div className="detail-charts">
+                    <div className="chart-title">分时</div>
+                    {intraday && intraday.points.length > 0
+                        ? <IntradayChart data={intraday} config={config} />
+                        : <div className="chart-placeholder">暂无分时数据</div>}
+
+                    <div className="chart-title">
+                        {PERIODS.map(p => (
+                            <button
+                                key={p.key}
+                                className={`period-btn ${period === p.key ? 'active' : ''}`}
+                                onClick={() => setPeriod(p.key)}
+                            >
+                                {p.label}
+                            </button>
+                        ))}
+                    </div>
+                    {klines.length > 0
+                        ? <KlineChart klines={klines} config={config} />
+                        : <div className="chart-placeholder">暂无K线数据</div>}
+                </div>
+
+                <div className="detail-side">
+                    {detail && <DepthTable detail={detail} config={config} decimals={decimals} />}
+                    <div className="detail-stats">
+                        {stats.map(([label, value]) => (
+                            <div key={label} className="stat-row">
+                                <span className="stat-label">{label}</span>
+                                <span>{value}</span>
+                            </div>
+                        ))}
+                    </div>
+                </div>
+            </div>
+        </div>
+    );
+}
@@ -1,5 +1,8 @@
-import { useConfig } from '../hooks/useConfig';
+import { useConfig, displayLayout } from '../hooks/useConfig';
 import { useStockData } from '../hooks/useStockData';
+import { usePortfolio } from '../hooks/usePortfolio';
+import { useMarketSummary } from '../hooks/useMarketSummary';
+import { useAlerts } from '../hooks/useAlerts';
 import { StockItem } from '../components/StockItem';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
@@ -20,12 +23,16 @@ const FONT_CONFIG: Record<string, { fontSize: number; rowHeight: number; width:
  * - 显示股票行情列表
  * - 支持拖拽移动窗口（data-tauri-drag-region）
  * - 右键弹出原生系统菜单（不会被窗口裁切）
- * - 快捷键切换股票
+ * - 快捷键切换股票、打开首行股票详情
+ * - 预警触发时在顶部临时显示一行提示
  * - 窗口大小根据显示行数 + 字体大小自适应
  */
 export function FloatingWindow() {
     const { config } = useConfig();
     const { getStock } = useStockData();
+    const { portfolio } = usePortfolio();
+    const { summary } = useMarketSummary();
+    const { alert, dismiss } = useAlerts();
     const [scrollOffset, setScrollOffset] = useState(0);
     
     // 拖动状态
@@ -136,15 +143,26 @@ export function FloatingWindow() {
     useEffect(() => {
         if (!config) return;
 
-        const visibleStocks = config.stocks.filter(s => s.visible);
-        const displayCount = Math.min(config.window.display_rows, visibleStocks.length) || 1;
+        const displayCount = displayLayout(config).displayCount || 1;
         const f = FONT_CONFIG[config.app.font_size] || FONT_CONFIG.medium;
 
         const width = f.width;
-        const height = displayCount * f.rowHeight + 4;
+        const extraRows = (config.window.show_portfolio ? 1 : 0)
+            + (config.window.show_market_summary ? 1 : 0)
+            + (alert ? 1 : 0);
+        const height = (displayCount + extraRows) * f.rowHeight + 4;
 
         invoke('resize_monitor_window', { width, height }).catch(console.error);
-    }, [config?.window.display_rows, config?.app.font_size, config?.stocks]);
+    }, [
+        config?.window.display_rows,
+        config?.app.font_size,
+        config?.stocks,
+        config?.groups,
+        config?.active_group,
+        config?.window.show_portfolio,
+        config?.window.show_market_summary,
+        alert !== null,
+    ]);
 
     // ==================== 鼠标穿透 ====================
     useEffect(() => {
@@ -157,9 +175,8 @@ export function FloatingWindow() {
     useEffect(() => {
         if (!config) return;
 
-        const visibleCount = config.stocks.filter(s => s.visible).length;
-        const displayCount = Math.min(config.window.display_rows, visibleCount);
-        const maxOffset = Math.max(0, visibleCount - displayCount);
+        // 置顶股票不参与滚动
+        const { maxOffset } = displayLayout(config);
 
         const unlistenNext = listen('hotkey-next-stock', () => {
             console.log('Received: hotkey-next-stock');
@@ -179,46 +196,59 @@ export function FloatingWindow() {
             unlistenNext.then(fn => fn());
             unlistenPrev.then(fn => fn());
         };
-    }, [config?.stocks, config?.window.display_rows]);
+    }, [config?.stocks, config?.groups, config?.active_group, config?.window.display_rows]);
+
+    // 打开详情快捷键：使用当前显示的首行股票（有置顶股票时为第一只置顶股票）
+    useEffect(() => {
+        if (!config) return;
+        const { pinned, scrolling, maxOffset } = displayLayout(config);
+        const first = pinned[0] ?? scrolling[Math.min(scrollOffset, maxOffset)];
+        if (!first) return;
+
+        const unlisten = listen('hotkey-open-detail', () => {
+            invoke('open_detail', { id: first.id }).catch(console.error);
+        });
+        return () => {
+            unlisten.then(fn => fn());
+        };
+    }, [config?.stocks, config?.groups, config?.active_group, config?.window.display_rows, scrollOffset]);
 
     // ==================== 自动轮播 ====================
     useEffect(() => {
         if (!config || !config.window.enable_carousel) return;
 
-        const visibleCount = config.stocks.filter(s => s.visible).length;
-        const displayCount = Math.min(config.window.display_rows, visibleCount);
-
-        // 只有当股票数量 > 显示行数时才需要轮播
-        if (visibleCount <= displayCount) return;
+        // 只有当未置顶的股票数量 > 剩余行数时才需要轮播
+        const { maxOffset } = displayLayout(config);
+        if (maxOffset === 0) return;
 
         const interval = setInterval(() => {
             setScrollOffset(prev => {
-                const maxOffset = visibleCount - displayCount;
                 // 循环轮播：到底后回到顶部
                 return prev >= maxOffset ? 0 : prev + 1;
             });
         }, config.window.carousel_interval_ms);
 
         return () => clearInterval(interval);
-    }, [config?.window.enable_carousel, config?.window.carousel_interval_ms, config?.stocks, config?.window.display_rows]);
+    }, [config?.window.enable_carousel, config?.window.carousel_interval_ms, config?.stocks, config?.groups, config?.active_group, config?.window.display_rows]);
+
+    // 切换分组时回到顶部
+    useEffect(() => {
+        setScrollOffset(0);
+    }, [config?.active_group]);
 
     // 股票列表变化时校正偏移量
     useEffect(() => {
         if (!config) return;
-        const visibleCount = config.stocks.filter(s => s.visible).length;
-        setScrollOffset(prev => {
-            const maxOffset = Math.max(0, visibleCount - config.window.display_rows);
-            return Math.min(prev, maxOffset);
-        });
-    }, [config?.stocks, config?.window.display_rows]);
+        const { maxOffset } = displayLayout(config);
+        setScrollOffset(prev => Math.min(prev, maxOffset));
+    }, [config?.stocks, config?.groups, config?.active_group, config?.window.display_rows]);
 
     if (!config) return null;
 
     // ==================== 计算要显示的股票 ====================
-    const visibleStocks = config.stocks.filter(s => s.visible);
-    const displayCount = Math.min(config.window.display_rows, visibleStocks.length) || visibleStocks.length;
-    const startIndex = Math.min(scrollOffset, Math.max(0, visibleStocks.length - displayCount));
-    const displayStocks = visibleStocks.slice(startIndex, startIndex + displayCount);
+    const { pinned, scrolling, scrollRows, maxOffset } = displayLayout(config);
+    const startIndex = Math.min(scrollOffset, maxOffset);
+    const displayStocks = [...pinned, ...scrolling.slice(startIndex, startIndex + scrollRows)];
 
     // ==================== 原生右键菜单 ====================
     const handleContextMenu = (e: React.MouseEvent) => {
@@ -231,6 +261,22 @@ export function FloatingWindow() {
 
     const bgOpacity = config.window.opacity;
 
+    // 大盘行：显示上证指数，其余指数和涨跌统计放在悬停提示中
+    const mainIndex = summary?.indices[0];
+    const marketTitle = summary
+        ? [
+            ...summary.indices.map(q =>
+                `${q.name} ${q.price.toFixed(2)} ${q.percent >= 0 ? '+' : ''}${(q.percent * 100).toFixed(2)}%`),
+            ...(summary.breadth
+                ? [
+                    `涨 ${summary.breadth.advancers} / 跌 ${summary.breadth.decliners} / 平 ${summary.breadth.unchanged}`,
+                    `涨停 ${summary.breadth.limit_up} / 跌停 ${summary.breadth.limit_down}`,
+                    `成交额 ${(summary.breadth.turnover / 1e8).toFixed(0)} 亿`,
+                ]
+                : []),
+        ].join('\n')
+        : undefined;
+
     return (
         <div
             className="floating-window"
@@ -243,6 +289,68 @@ export function FloatingWindow() {
                 background: `rgba(30, 30, 30, ${bgOpacity})`,
             }}
         >
+            {alert && (
+                <div
+                    className="stock-item alert-line"
+                    style={{ height: `${fc.rowHeight}px`, color: config.app.up_color }}
+                    title={`${alert.name} ${alert.message}（双击关闭）`}
+                    onDoubleClick={dismiss}
+                >
+                    <span className="stock-name">{alert.name}</span>
+                    <span className="alert-message">{alert.message}</span>
+                </div>
+            )}
+            {config.window.show_market_summary && (
+                <div
+                    className="stock-item market-line"
+                    style={{
+                        height: `${fc.rowHeight}px`,
+                        color: !mainIndex || mainIndex.change === 0
+                            ? config.app.neutral_color
+                            : mainIndex.change > 0 ? config.app.up_color : config.app.down_color,
+                    }}
+                    title={marketTitle}
+                >
+                    <span className="stock-name">大盘</span>
+                    <span className="stock-price">
+                        {mainIndex ? mainIndex.price.toFixed(2) : '--'}
+                        {summary?.breadth && (
+                            <span className="market-breadth">
+                                {` ${summary.breadth.advancers}/${summary.breadth.decliners}`}
+                            </span>
+                        )}
+                    </span>
+                    <span className="stock-change">
+                        {mainIndex
+                            ? `${mainIndex.percent >= 0 ? '+' : ''}${(mainIndex.percent * 100).toFixed(2)}%`
+                            : '--'}
+                    </span>
+                </div>
+            )}
+            {config.window.show_portfolio && (
+                <div
+                    className="stock-item portfolio-line"
+                    title="按当前持仓估算：今日建仓的持仓以成本价为基准，其余以昨收为基准，未计入今日加仓和卖出"
+                    style={{
+                        height: `${fc.rowHeight}px`,
+                        color: !portfolio || portfolio.daily_pnl === 0
+                            ? config.app.neutral_color
+                            : portfolio.daily_pnl > 0 ? config.app.up_color : config.app.down_color,
+                    }}
+                >
+                    <span className="stock-name">今日</span>
+                    <span className="stock-price">
+                        {portfolio
+                            ? `${portfolio.daily_pnl >= 0 ? '+' : ''}${Math.round(portfolio.daily_pnl).toLocaleString()}`
+                            : '--'}
+                    </span>
+                    <span className="stock-change">
+                        {portfolio
+                            ? `${portfolio.daily_percent >= 0 ? '+' : ''}${(portfolio.daily_percent * 100).toFixed(2)}%`
+                            : '--'}
+                    </span>
+                </div>
+            )}
             {displayStocks.map((stock) => {
                 const data = getStock(stock.id);
                 if (!data) {
@@ -251,6 +359,8 @@ export function FloatingWindow() {
                             key={stock.id}
                             className="stock-item stock-loading"
                             style={{ height: `${fc.rowHeight}px` }}
+                            title={stock.overrides?.note || undefined}
+                            onDoubleClick={() => invoke('open_detail', { id: stock.id }).catch(console.error)}
                         >
                             <span className="stock-name">{stock.alias || stock.code}</span>
                             <span className="stock-price">--</span>
@@ -258,7 +368,7 @@ export function FloatingWindow() {
                         </div>
                     );
                 }
-                return <StockItem key={stock.id} data={data} config={config} rowHeight={fc.rowHeight} />;
+                return <StockItem key={stock.id} data={data} stock={stock} config={config} rowHeight={fc.rowHeight} />;
             })}
         </div>
     );
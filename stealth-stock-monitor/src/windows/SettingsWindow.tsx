@@ -1,7 +1,10 @@
-import { useState } from 'react';
-import { useConfig } from '../hooks/useConfig';
+import { Fragment, useState, useEffect } from 'react';
+import { useConfig, activeStocks, DEFAULT_OVERRIDES } from '../hooks/useConfig';
 import { invoke } from '@tauri-apps/api/core';
-import type { Config } from '../types';
+import type {
+    Config, ConfigBackup, Holding, ImportMode, RefreshPriority, Stock, StockOverrides, TradingSoftware,
+    UndoState, WatchGroup, WatchlistFormat, WatchlistImport,
+} from '../types';
 import './SettingsWindow.css';
 
 /**
@@ -15,6 +18,17 @@ export function SettingsWindow() {
     const [activeTab, setActiveTab] = useState<'stocks' | 'appearance' | 'shortcuts' | 'data' | 'about'>('stocks');
     const [message, setMessage] = useState<{ text: string; type: 'success' | 'error' } | null>(null);
     const [recordingKey, setRecordingKey] = useState<string | null>(null);
+    const [backups, setBackups] = useState<ConfigBackup[] | null>(null);
+    const [profiles, setProfiles] = useState<string[]>([]);
+    const [importText, setImportText] = useState('');
+    const [importMode, setImportMode] = useState<ImportMode>('merge');
+    const [importPreview, setImportPreview] = useState<WatchlistImport | null>(null);
+    const [exportFormat, setExportFormat] = useState<WatchlistFormat>('codes');
+    const [software, setSoftware] = useState<TradingSoftware | ''>('');
+    const [softwareFile, setSoftwareFile] = useState<number[] | null>(null);
+    const [undoState, setUndoState] = useState<UndoState>({ can_undo: false, can_redo: false });
+    const [exportText, setExportText] = useState('');
+    const [editingStock, setEditingStock] = useState<string | null>(null);
 
     // ==================== 辅助函数 ====================
     const showMessage = (text: string, type: 'success' | 'error' = 'success') => {
@@ -35,6 +49,26 @@ export function SettingsWindow() {
         }
     };
 
+    const handleSaveHolding = async (id: string, holding: Holding | null) => {
+        try {
+            await invoke('set_holding', { id, holding });
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    /** 修改单只股票的显示与刷新设置（只改动传入的项） */
+    const handleSaveOverrides = async (stock: Stock, changes: Partial<StockOverrides>) => {
+        try {
+            await invoke('set_stock_overrides', {
+                id: stock.id,
+                overrides: { ...DEFAULT_OVERRIDES, ...stock.overrides, ...changes },
+            });
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
     const handleRemoveStock = async (id: string) => {
         try {
             await invoke('remove_stock', { id });
@@ -44,6 +78,248 @@ export function SettingsWindow() {
         }
     };
 
+    // ==================== 撤销 / 重做 ====================
+    // 每次配置变化（包括其他窗口和快捷键引起的）后刷新按钮状态
+    useEffect(() => {
+        if (!config) return;
+        invoke<UndoState>('get_undo_state').then(setUndoState).catch(() => undefined);
+    }, [config]);
+
+    const handleUndo = async (redo = false) => {
+        try {
+            setUndoState(await invoke<UndoState>(redo ? 'redo_watchlist' : 'undo_watchlist'));
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    // Ctrl/Cmd+Z 撤销，Ctrl/Cmd+Shift+Z 或 Ctrl+Y 重做（输入框中保留浏览器自身的撤销）
+    useEffect(() => {
+        const onKeyDown = (e: KeyboardEvent) => {
+            const target = e.target as HTMLElement;
+            if (target.tagName === 'INPUT' || target.tagName === 'TEXTAREA') return;
+            if (!(e.ctrlKey || e.metaKey)) return;
+            const key = e.key.toLowerCase();
+            if (key === 'z' || key === 'y') {
+                e.preventDefault();
+                handleUndo(key === 'y' || e.shiftKey);
+            }
+        };
+        window.addEventListener('keydown', onKeyDown);
+        return () => window.removeEventListener('keydown', onKeyDown);
+    }, []);
+
+    // ==================== 分组管理 ====================
+    const handleSwitchGroup = async (id: string) => {
+        try {
+            await invoke('switch_group', { id });
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleCreateGroup = async () => {
+        const name = window.prompt('新分组名称');
+        if (!name) return;
+        try {
+            const group = await invoke<WatchGroup>('create_group', { name });
+            await invoke('switch_group', { id: group.id });
+            showMessage(`已创建分组 ${group.name}`);
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleRenameGroup = async (group: WatchGroup) => {
+        const name = window.prompt('分组名称', group.name);
+        if (!name || name === group.name) return;
+        try {
+            await invoke('rename_group', { id: group.id, name });
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleDeleteGroup = async (group: WatchGroup) => {
+        if (!window.confirm(`删除分组 ${group.name}？只在该分组中且无持仓的股票会一并删除。`)) return;
+        try {
+            await invoke('delete_group', { id: group.id });
+            showMessage('已删除分组');
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    /** 设置股票在当前分组中是否显示 */
+    const handleToggleVisible = (id: string, visible: boolean) => {
+        if (!config) return;
+        const groups = config.groups.map(g => g.id !== config.active_group ? g : {
+            ...g,
+            members: g.members.map(m => m.id === id ? { ...m, visible } : m),
+        });
+        updateConfig({ ...config, groups }).catch((err) => showMessage(String(err), 'error'));
+    };
+
+    // ==================== 导入导出 ====================
+    const conflictLabels: Record<WatchlistImport['conflicts'][number]['kind'], string> = {
+        duplicate: '重复',
+        existing: '已存在',
+        invalid: '无法识别',
+    };
+
+    const handleImportFile = (file: File | undefined) => {
+        if (!file) return;
+        const reader = new FileReader();
+        reader.onload = () => {
+            setImportText(String(reader.result ?? ''));
+            setImportPreview(null);
+        };
+        reader.readAsText(file);
+    };
+
+    const handlePreviewImport = async () => {
+        try {
+            setImportPreview(await invoke<WatchlistImport>('preview_watchlist_import', {
+                content: importText,
+                format: null,
+                mode: importMode,
+            }));
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleImport = async () => {
+        if (importMode === 'replace' && !window.confirm('用导入的列表替换当前分组？')) return;
+        try {
+            const result = await invoke<WatchlistImport>('import_watchlist', {
+                content: importText,
+                format: null,
+                mode: importMode,
+            });
+            setImportPreview(result);
+            setImportText('');
+            showMessage(`已导入 ${result.added.length} 只股票`);
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const softwareLabels: Record<TradingSoftware, string> = {
+        tdx: '通达信',
+        ths: '同花顺',
+        eastmoney: '东方财富',
+    };
+
+    /** 交易软件导出的文件多为 GBK 编码，按原始字节交给后端解码 */
+    const handleSoftwareFile = (file: File | undefined) => {
+        if (!file) return;
+        const reader = new FileReader();
+        reader.onload = async () => {
+            const bytes = Array.from(new Uint8Array(reader.result as ArrayBuffer));
+            setSoftwareFile(bytes);
+            try {
+                setImportPreview(await invoke<WatchlistImport>('preview_software_watchlist', {
+                    bytes,
+                    software: software || null,
+                }));
+            } catch (err) {
+                setImportPreview(null);
+                showMessage(String(err), 'error');
+            }
+        };
+        reader.readAsArrayBuffer(file);
+    };
+
+    const handleSoftwareImport = async () => {
+        if (!softwareFile) return;
+        try {
+            const result = await invoke<WatchlistImport>('import_software_watchlist', {
+                bytes: softwareFile,
+                software: software || null,
+            });
+            setImportPreview(result);
+            setSoftwareFile(null);
+            showMessage(`已从${result.software ? softwareLabels[result.software] : '文件'}导入 ${result.added.length} 只股票`);
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleExport = async () => {
+        try {
+            const content = await invoke<string>('export_watchlist', { format: exportFormat });
+            setExportText(content);
+            await navigator.clipboard.writeText(content).catch(() => undefined);
+            showMessage('已导出并复制到剪贴板');
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    // ==================== 配置方案 ====================
+    const loadProfiles = () => {
+        invoke<string[]>('list_profiles')
+            .then(setProfiles)
+            .catch((err) => showMessage(String(err), 'error'));
+    };
+
+    // 切换方案（包括托盘菜单和快捷键切换）后刷新列表
+    useEffect(() => {
+        if (config) loadProfiles();
+    }, [config?.profile]);
+
+    const handleSwitchProfile = async (name: string) => {
+        try {
+            await invoke('switch_profile', { name });
+            showMessage(`已切换到方案 ${name}`);
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleCreateProfile = async () => {
+        const name = window.prompt('新方案名称（以当前配置为模板）');
+        if (!name) return;
+        try {
+            await invoke('create_profile', { name });
+            showMessage(`已创建方案 ${name}`);
+            loadProfiles();
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleDeleteProfile = async (name: string) => {
+        if (!window.confirm(`删除配置方案 ${name}？`)) return;
+        try {
+            await invoke('delete_profile', { name });
+            loadProfiles();
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    // ==================== 配置备份 ====================
+    const loadBackups = async () => {
+        try {
+            setBackups(await invoke<ConfigBackup[]>('list_config_backups'));
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
+    const handleRestoreBackup = async (name: string) => {
+        if (!window.confirm(`用备份 ${name} 替换当前配置？当前配置会先备份。`)) return;
+        try {
+            await invoke('restore_config_backup', { name });
+            showMessage('已恢复配置');
+            loadBackups();
+        } catch (err) {
+            showMessage(String(err), 'error');
+        }
+    };
+
     // ==================== 配置更新 ====================
     const updateField = <K extends keyof Config>(
         section: K,
@@ -58,7 +334,7 @@ export function SettingsWindow() {
                 ? { ...sectionValue, [field]: value }
                 : value,
         };
-        updateConfig(newConfig as Config);
+        updateConfig(newConfig as Config).catch((err) => showMessage(String(err), 'error'));
     };
 
     // ==================== 快捷键录制 ====================
@@ -70,6 +346,13 @@ export function SettingsWindow() {
         if (recordingKey !== shortcutName) return;
         e.preventDefault();
 
+        // 单独按 Backspace / Delete 清除快捷键
+        if (['Backspace', 'Delete'].includes(e.key) && !e.ctrlKey && !e.metaKey && !e.altKey && !e.shiftKey) {
+            updateField('shortcuts', shortcutName, '');
+            setRecordingKey(null);
+            return;
+        }
+
         const parts: string[] = [];
         if (e.ctrlKey || e.metaKey) parts.push('CommandOrControl');
         if (e.altKey) parts.push('Alt');
@@ -148,6 +431,41 @@ export function SettingsWindow() {
             {/* 股票管理 */}
             {activeTab === 'stocks' && (
                 <div className="tab-content">
+                    <div className="group-bar">
+                        {config.groups.map((group) => (
+                            <button
+                                key={group.id}
+                                className={`group-btn ${group.id === config.active_group ? 'active' : ''}`}
+                                onClick={() => handleSwitchGroup(group.id)}
+                                onDoubleClick={() => handleRenameGroup(group)}
+                                title="双击重命名"
+                            >
+                                {group.name}
+                            </button>
+                        ))}
+                        <button onClick={handleCreateGroup} className="group-btn">＋</button>
+                        <button
+                            onClick={() => handleUndo()}
+                            disabled={!undoState.can_undo}
+                            className="group-btn"
+                            title="撤销（Ctrl+Z）"
+                        >↶</button>
+                        <button
+                            onClick={() => handleUndo(true)}
+                            disabled={!undoState.can_redo}
+                            className="group-btn"
+                            title="重做（Ctrl+Shift+Z）"
+                        >↷</button>
+                        {config.groups.length > 1 && (
+                            <button
+                                onClick={() => {
+                                    const group = config.groups.find(g => g.id === config.active_group);
+                                    if (group) handleDeleteGroup(group);
+                                }}
+                                className="btn btn-danger btn-sm"
+                            >删除分组</button>
+                        )}
+                    </div>
                     <div className="add-stock-row">
                         <input
                             type="text"
@@ -160,20 +478,253 @@ export function SettingsWindow() {
                         <button onClick={handleAddStock} className="btn btn-primary">添加</button>
                     </div>
                     <div className="stock-list">
-                        {config.stocks.map((stock) => (
-                            <div key={stock.id} className="stock-list-item">
+                        {activeStocks(config).map((stock) => (
+                            <Fragment key={stock.id}>
+                            <div className="stock-list-item">
+                                <input
+                                    type="checkbox"
+                                    title="在悬浮窗中显示"
+                                    checked={stock.visible}
+                                    onChange={(e) => handleToggleVisible(stock.id, e.target.checked)}
+                                />
                                 <span className="stock-id">{stock.id}</span>
                                 <span className="stock-alias">{stock.alias || stock.code}</span>
+                                <input
+                                    type="number"
+                                    min="0"
+                                    placeholder="持股数"
+                                    defaultValue={stock.holding?.shares ?? ''}
+                                    onBlur={(e) => {
+                                        const shares = parseFloat(e.target.value);
+                                        if (!shares) {
+                                            if (stock.holding) handleSaveHolding(stock.id, null);
+                                            return;
+                                        }
+                                        handleSaveHolding(stock.id, {
+                                            shares,
+                                            avg_cost: stock.holding?.avg_cost ?? 0,
+                                            open_date: stock.holding?.open_date ?? null,
+                                        });
+                                    }}
+                                    className="input input-sm"
+                                />
+                                <input
+                                    type="number"
+                                    min="0"
+                                    step="0.001"
+                                    placeholder="成本价"
+                                    defaultValue={stock.holding?.avg_cost ?? ''}
+                                    disabled={!stock.holding}
+                                    onBlur={(e) => {
+                                        if (!stock.holding) return;
+                                        handleSaveHolding(stock.id, {
+                                            ...stock.holding,
+                                            avg_cost: parseFloat(e.target.value) || 0,
+                                        });
+                                    }}
+                                    className="input input-sm"
+                                />
+                                <input
+                                    type="date"
+                                    title="建仓日期"
+                                    defaultValue={stock.holding?.open_date ?? ''}
+                                    disabled={!stock.holding}
+                                    onBlur={(e) => {
+                                        if (!stock.holding) return;
+                                        handleSaveHolding(stock.id, {
+                                            ...stock.holding,
+                                            open_date: e.target.value || null,
+                                        });
+                                    }}
+                                    className="input input-sm"
+                                />
+                                <button
+                                    className={`group-btn ${editingStock === stock.id ? 'active' : ''}`}
+                                    title="显示与刷新设置"
+                                    onClick={() => setEditingStock(editingStock === stock.id ? null : stock.id)}
+                                >⚙</button>
                                 <button
                                     className="btn btn-danger btn-sm"
                                     onClick={() => handleRemoveStock(stock.id)}
                                 >删除</button>
                             </div>
+                            {editingStock === stock.id && (
+                                <div className="stock-overrides">
+                                    <label>
+                                        小数位
+                                        <select
+                                            value={stock.overrides?.decimals ?? ''}
+                                            onChange={(e) => handleSaveOverrides(stock, {
+                                                decimals: e.target.value === '' ? null : Number(e.target.value),
+                                            })}
+                                            className="select"
+                                        >
+                                            <option value="">默认</option>
+                                            {[0, 1, 2, 3, 4].map(n => <option key={n} value={n}>{n}</option>)}
+                                        </select>
+                                    </label>
+                                    {(['up_color', 'down_color'] as const).map(key => (
+                                        <label key={key}>
+                                            {key === 'up_color' ? '上涨颜色' : '下跌颜色'}
+                                            <input
+                                                type="color"
+                                                value={stock.overrides?.[key] || config.app[key]}
+                                                onChange={(e) => handleSaveOverrides(stock, { [key]: e.target.value })}
+                                            />
+                                            {stock.overrides?.[key] && (
+                                                <button
+                                                    className="btn btn-sm"
+                                                    onClick={() => handleSaveOverrides(stock, { [key]: null })}
+                                                >默认</button>
+                                            )}
+                                        </label>
+                                    ))}
+                                    <label>
+                                        刷新优先级
+                                        <select
+                                            value={stock.overrides?.priority ?? 'normal'}
+                                            onChange={(e) => handleSaveOverrides(stock, {
+                                                priority: e.target.value as RefreshPriority,
+                                            })}
+                                            className="select"
+                                        >
+                                            <option value="high">高</option>
+                                            <option value="normal">普通</option>
+                                            <option value="low">低</option>
+                                        </select>
+                                    </label>
+                                    <label>
+                                        <input
+                                            type="checkbox"
+                                            checked={stock.overrides?.pinned ?? false}
+                                            onChange={(e) => handleSaveOverrides(stock, { pinned: e.target.checked })}
+                                        />
+                                        置顶
+                                    </label>
+                                    <label>
+                                        <input
+                                            type="checkbox"
+                                            checked={stock.overrides?.percent_only ?? false}
+                                            onChange={(e) => handleSaveOverrides(stock, { percent_only: e.target.checked })}
+                                        />
+                                        只显示涨跌幅
+                                    </label>
+                                    <input
+                                        type="text"
+                                        placeholder="备注（悬停时显示）"
+                                        defaultValue={stock.overrides?.note ?? ''}
+                                        onBlur={(e) => {
+                                            if (e.target.value !== (stock.overrides?.note ?? '')) {
+                                                handleSaveOverrides(stock, { note: e.target.value });
+                                            }
+                                        }}
+                                        className="input"
+                                    />
+                                </div>
+                            )}
+                            </Fragment>
                         ))}
-                        {config.stocks.length === 0 && (
+                        {activeStocks(config).length === 0 && (
                             <div className="empty-hint">暂无自选股，请添加</div>
                         )}
                     </div>
+                    <div className="form-group">
+                        <label>导入 / 导出</label>
+                        <textarea
+                            placeholder={'粘贴代码列表、CSV 或 JSON，如：\n600519 茅台\n000001.SZ\nsh600036'}
+                            value={importText}
+                            onChange={(e) => {
+                                setImportText(e.target.value);
+                                setImportPreview(null);
+                            }}
+                            rows={5}
+                            className="input watchlist-text"
+                        />
+                        <div className="add-stock-row">
+                            <input
+                                type="file"
+                                accept=".txt,.csv,.json"
+                                onChange={(e) => handleImportFile(e.target.files?.[0])}
+                            />
+                            <select
+                                value={importMode}
+                                onChange={(e) => {
+                                    setImportMode(e.target.value as ImportMode);
+                                    setImportPreview(null);
+                                }}
+                                className="select"
+                            >
+                                <option value="merge">合并到当前分组</option>
+                                <option value="replace">替换当前分组</option>
+                            </select>
+                            <button onClick={handlePreviewImport} disabled={!importText.trim()} className="btn btn-sm">预览</button>
+                            <button onClick={handleImport} disabled={!importText.trim()} className="btn btn-primary btn-sm">导入</button>
+                        </div>
+                        <div className="add-stock-row">
+                            <select
+                                value={software}
+                                onChange={(e) => setSoftware(e.target.value as TradingSoftware | '')}
+                                className="select"
+                            >
+                                <option value="">自动识别</option>
+                                <option value="tdx">通达信 zxg.blk</option>
+                                <option value="ths">同花顺</option>
+                                <option value="eastmoney">东方财富</option>
+                            </select>
+                            <input
+                                type="file"
+                                accept=".blk,.txt,.csv,.xls"
+                                onChange={(e) => handleSoftwareFile(e.target.files?.[0])}
+                            />
+                            <button onClick={handleSoftwareImport} disabled={!softwareFile} className="btn btn-primary btn-sm">从交易软件导入</button>
+                        </div>
+                        {importPreview && (
+                            <div className="stock-list">
+                                <div className="stock-list-item">
+                                    {importPreview.software && (
+                                        <span className="stock-id">{softwareLabels[importPreview.software]}</span>
+                                    )}
+                                    <span className="stock-alias">
+                                        新增 {importPreview.added.length}，移除 {importPreview.removed.length}，跳过 {importPreview.conflicts.length}
+                                    </span>
+                                </div>
+                                {importPreview.added.length > 0 && (
+                                    <div className="stock-list-item">
+                                        <span className="hint">新增：{importPreview.added.join(' ')}</span>
+                                    </div>
+                                )}
+                                {importPreview.removed.length > 0 && (
+                                    <div className="stock-list-item">
+                                        <span className="hint">移除：{importPreview.removed.join(' ')}</span>
+                                    </div>
+                                )}
+                                {importPreview.conflicts.map((conflict) => (
+                                    <div key={conflict.line} className="stock-list-item">
+                                        <span className="stock-id">第 {conflict.line} 行</span>
+                                        <span className="stock-alias">{conflict.id ?? conflict.entry}</span>
+                                        <span className="hint">{conflictLabels[conflict.kind]}</span>
+                                    </div>
+                                ))}
+                            </div>
+                        )}
+                        <div className="add-stock-row">
+                            <select
+                                value={exportFormat}
+                                onChange={(e) => setExportFormat(e.target.value as WatchlistFormat)}
+                                className="select"
+                            >
+                                <option value="codes">代码列表</option>
+                                <option value="csv">CSV</option>
+                                <option value="json">JSON</option>
+                            </select>
+                            <button onClick={handleExport} className="btn btn-primary btn-sm">导出当前分组</button>
+                        </div>
+                        {exportText && (
+                            <textarea readOnly value={exportText} rows={5} className="input watchlist-text" />
+                        )}
+                        <span className="hint">代码可写作 600519、sh600519 或 600519.SH，代码后可跟别名；格式按内容自动识别</span>
+                        <span className="hint">交易软件导入追加到当前分组：通达信为 T0002/blocknew/zxg.blk，同花顺、东方财富在自选股列表中导出为 TXT/CSV</span>
+                    </div>
                 </div>
             )}
 
@@ -266,6 +817,27 @@ export function SettingsWindow() {
                             <span className="hint">每隔几秒自动切换到下一只股票</span>
                         </div>
                     )}
+                    <div className="form-group">
+                        <label className="checkbox-label">
+                            <input
+                                type="checkbox"
+                                checked={config.window.show_portfolio}
+                                onChange={(e) => updateField('window', 'show_portfolio', e.target.checked)}
+                            />
+                            显示持仓当日盈亏（一行汇总）
+                        </label>
+                        <span className="hint">按当前持仓估算，今日加仓按昨收计算，今日卖出的部分不计入</span>
+                    </div>
+                    <div className="form-group">
+                        <label className="checkbox-label">
+                            <input
+                                type="checkbox"
+                                checked={config.window.show_market_summary}
+                                onChange={(e) => updateField('window', 'show_market_summary', e.target.checked)}
+                            />
+                            显示大盘概况（上证指数与涨跌家数，悬停查看全部指数）
+                        </label>
+                    </div>
                     <div className="form-group">
                         <label>主题模式</label>
                         <select
@@ -285,13 +857,16 @@ export function SettingsWindow() {
             {activeTab === 'shortcuts' && (
                 <div className="tab-content">
                     <div className="shortcut-hint">
-                        💡 快捷键在任何情况下都能触发，包括鼠标穿透模式
+                        💡 快捷键在任何情况下都能触发，包括鼠标穿透模式；录制时按 Backspace 清除
                     </div>
                     {[
                         { key: 'toggle_visible', label: '显示/隐藏窗口', hint: '' },
                         { key: 'next_stock', label: '下一只股票', hint: '' },
                         { key: 'prev_stock', label: '上一只股票', hint: '' },
                         { key: 'open_settings', label: '打开设置', hint: '鼠标穿透后进入设置的唯一方式' },
+                        { key: 'open_detail', label: '打开详情', hint: '查看悬浮窗首行股票的分时、K线与盘口' },
+                        { key: 'cycle_group', label: '切换分组', hint: '依次切换自选股分组' },
+                        { key: 'cycle_profile', label: '切换配置方案', hint: '依次切换配置方案（也可在托盘菜单中切换）' },
                     ].map(({ key, label, hint }) => (
                         <div key={key} className="form-group">
                             <label>{label}</label>
@@ -299,6 +874,7 @@ export function SettingsWindow() {
                                 type="text"
                                 readOnly
                                 className={`input shortcut-input ${recordingKey === key ? 'recording' : ''}`}
+                                placeholder="未设置"
                                 value={
                                     recordingKey === key
                                         ? '按下组合键...'
@@ -345,6 +921,38 @@ export function SettingsWindow() {
                             隐藏窗口时暂停数据刷新
                         </label>
                     </div>
+                    <div className="form-group">
+                        <label className="checkbox-label">
+                            <input
+                                type="checkbox"
+                                checked={config.app.idle_slowdown}
+                                onChange={(e) => updateField('app', 'idle_slowdown', e.target.checked)}
+                            />
+                            系统空闲或锁屏时降低刷新频率
+                        </label>
+                    </div>
+                    {config.app.idle_slowdown && (
+                        <div className="form-group">
+                            <label>空闲判定（分钟）</label>
+                            <input
+                                type="number"
+                                min="1"
+                                max="120"
+                                value={config.app.idle_threshold_secs / 60}
+                                onChange={(e) => updateField('app', 'idle_threshold_secs', parseInt(e.target.value) * 60)}
+                                className="input input-sm"
+                            />
+                            <label>空闲时刷新间隔（秒）</label>
+                            <input
+                                type="number"
+                                min="5"
+                                max="600"
+                                value={config.app.idle_refresh_interval_ms / 1000}
+                                onChange={(e) => updateField('app', 'idle_refresh_interval_ms', parseInt(e.target.value) * 1000)}
+                                className="input input-sm"
+                            />
+                        </div>
+                    )}
                     <div className="form-group">
                         <label className="checkbox-label">
                             <input
@@ -355,6 +963,94 @@ export function SettingsWindow() {
                             开机自启动
                         </label>
                     </div>
+                    <div className="form-group">
+                        <label className="checkbox-label">
+                            <input
+                                type="checkbox"
+                                checked={config.api.enabled}
+                                onChange={(e) => updateField('api', 'enabled', e.target.checked)}
+                            />
+                            启用本地行情 API
+                        </label>
+                        <span className="hint">仅本机可访问，供 Excel / Python / Stream Deck 等工具读取行情</span>
+                    </div>
+                    {config.api.enabled && (
+                        <div className="form-group">
+                            <label>API 端口</label>
+                            <input
+                                type="number"
+                                min="1024"
+                                max="65535"
+                                value={config.api.port}
+                                onChange={(e) => updateField('api', 'port', parseInt(e.target.value))}
+                                className="input input-sm"
+                            />
+                            <span className="hint">http://127.0.0.1:{config.api.port}/api/quotes</span>
+                        </div>
+                    )}
+                    <div className="form-group">
+                        <label className="checkbox-label">
+                            <input
+                                type="checkbox"
+                                checked={config.storage.enabled}
+                                onChange={(e) => updateField('storage', 'enabled', e.target.checked)}
+                            />
+                            记录行情到本地数据库
+                        </label>
+                        <span className="hint">用于 K 线图和指标计算，1 分钟 K 线永久保留</span>
+                    </div>
+                    {config.storage.enabled && (
+                        <div className="form-group">
+                            <label>逐笔行情保留天数</label>
+                            <input
+                                type="number"
+                                min="1"
+                                max="365"
+                                value={config.storage.tick_retention_days}
+                                onChange={(e) => updateField('storage', 'tick_retention_days', parseInt(e.target.value))}
+                                className="input input-sm"
+                            />
+                        </div>
+                    )}
+                    <div className="form-group">
+                        <label>配置方案</label>
+                        <div className="stock-list">
+                            {profiles.map((name) => (
+                                <div key={name} className="stock-list-item">
+                                    <span className="stock-alias">{name}</span>
+                                    {name === config.profile ? (
+                                        <span className="hint">当前</span>
+                                    ) : (
+                                        <>
+                                            <button onClick={() => handleSwitchProfile(name)} className="btn btn-primary btn-sm">切换</button>
+                                            <button onClick={() => handleDeleteProfile(name)} className="btn btn-danger btn-sm">删除</button>
+                                        </>
+                                    )}
+                                </div>
+                            ))}
+                        </div>
+                        <button onClick={handleCreateProfile} className="btn btn-primary btn-sm">另存为新方案</button>
+                        <span className="hint">每个方案是一套完整配置（窗口、快捷键、自选股等），如办公 / 家里</span>
+                    </div>
+                    <div className="form-group">
+                        <label>配置备份</label>
+                        {backups === null ? (
+                            <button onClick={loadBackups} className="btn btn-primary btn-sm">查看备份</button>
+                        ) : backups.length === 0 ? (
+                            <span className="hint">暂无备份</span>
+                        ) : (
+                            <div className="stock-list">
+                                {backups.map((backup) => (
+                                    <div key={backup.name} className="stock-list-item">
+                                        <span className="stock-alias">{new Date(backup.timestamp).toLocaleString()}</span>
+                                        <span className="hint">{(backup.size / 1024).toFixed(1)} KB</span>
+                                        <button onClick={() => handleRestoreBackup(backup.name)} className="btn btn-primary btn-sm">恢复</button>
+                                    </div>
+                                ))}
+                            </div>
+                        )}
+                        <span className="hint">保存配置时自动备份旧版本（最多保留 10 份）</span>
+                    </div>
                 </div>
             )}
 